@@ -1,5 +1,12 @@
 pub mod books;
 pub mod calendar;
+pub mod ical;
+pub mod migration;
 pub mod mindmap;
 pub mod notes;
+pub mod review;
+pub mod sandbox;
+pub mod search;
+pub mod settings;
 pub mod task;
+pub mod working_calendar;
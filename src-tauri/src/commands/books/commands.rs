@@ -1,7 +1,14 @@
 use crate::commands::books::db::{Book, ReadingMemo};
 use crate::commands::books::fs;
+use crate::commands::search::db::{remove_document, SearchKind};
+use crate::commands::search::indexer::index_reading_memo;
+use crate::commands::settings::timezone::now_for_db;
+use crate::commands::settings::timezone::{load_user_timezone, start_of_day};
+use crate::commands::task::sql::task_query::to_sql_datetime;
 use crate::AppState;
-use chrono::Local;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use chrono_tz::Tz;
+use sqlx::{Row, SqlitePool};
 use tauri::{AppHandle, State};
 
 // Books Commands
@@ -57,7 +64,7 @@ pub async fn update_book(
     total_pages: i32,
     cover_image_path: Option<String>,
 ) -> Result<Book, String> {
-    let updated_at = Local::now().to_rfc3339();
+    let updated_at = now_for_db();
 
     // We fetch the book first to construct the return object easily or strict updated fields.
     // For simplicity, we just update and return the constructed object.
@@ -88,13 +95,29 @@ pub async fn update_book(
 
 #[tauri::command]
 pub async fn delete_book(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let deleted_at = Local::now().to_rfc3339();
+    let deleted_at = now_for_db();
     sqlx::query("UPDATE books SET deleted_at = ? WHERE id = ?")
         .bind(deleted_at)
-        .bind(id)
+        .bind(&id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
+
+    // 削除した本の読書メモが検索に出てこないようにする
+    let memo_ids: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM reading_memos WHERE book_id = ?")
+            .bind(&id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    for memo_id in memo_ids {
+        if let Err(e) = remove_document(&state.pool, SearchKind::ReadingMemo, &memo_id).await {
+            println!(
+                "Failed to remove reading memo {} from the index: {}",
+                memo_id, e
+            );
+        }
+    }
     Ok(())
 }
 
@@ -136,7 +159,7 @@ pub async fn create_book_memo(
     content: String,
 ) -> Result<ReadingMemo, String> {
     let id = uuid::Uuid::new_v4().to_string();
-    let created_at = Local::now().to_rfc3339();
+    let created_at = now_for_db();
     let filename = format!("{}.md", id);
 
     // Save content to FS
@@ -157,6 +180,10 @@ pub async fn create_book_memo(
     .await
     .map_err(|e| e.to_string())?;
 
+    if let Err(e) = index_reading_memo(&state.pool, &id, &book_id, &content).await {
+        println!("Failed to index reading memo {}: {}", id, e);
+    }
+
     Ok(ReadingMemo {
         id,
         book_id,
@@ -192,7 +219,7 @@ pub async fn update_book_memo(
 
     fs::save_memo_content(&app_handle, filename, &content)?;
 
-    let updated_at = Local::now().to_rfc3339();
+    let updated_at = now_for_db();
 
     sqlx::query("UPDATE reading_memos SET page_number = ?, updated_at = ? WHERE id = ?")
         .bind(page_number)
@@ -208,62 +235,110 @@ pub async fn update_book_memo(
         .await
         .map_err(|e| e.to_string())?;
 
+    if let Err(e) = index_reading_memo(&state.pool, &id, &updated_memo.book_id, &content).await {
+        println!("Failed to index reading memo {}: {}", id, e);
+    }
+
     Ok(updated_memo)
 }
 
 #[tauri::command]
 pub async fn delete_book_memo(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let deleted_at = Local::now().to_rfc3339();
+    let deleted_at = now_for_db();
     sqlx::query("UPDATE reading_memos SET deleted_at = ? WHERE id = ?")
         .bind(deleted_at)
-        .bind(id)
+        .bind(&id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
+    if let Err(e) = remove_document(&state.pool, SearchKind::ReadingMemo, &id).await {
+        println!("Failed to remove reading memo {} from the index: {}", id, e);
+    }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn read_book_memo_file(path: String) -> Result<String, String> {
-    fs::read_memo_content(&path)
+pub async fn read_book_memo_file(app_handle: AppHandle, path: String) -> Result<String, String> {
+    fs::read_memo_content(&app_handle, &path)
 }
 
-#[derive(serde::Serialize, sqlx::FromRow)]
+#[derive(serde::Serialize)]
 pub struct ReadingActivity {
-    pub date: String,
+    pub date: String, // ユーザーのタイムゾーンでの日付 (YYYY-MM-DD)
     pub book_title: String,
     pub start_page: i32,
     pub end_page: i32,
     pub memo_id: String,
 }
 
+// 直前のメモのページ番号 (なければ 0) からそのメモのページまでを1日の読書量とする
+// 期間 [from, to) で絞り込み、日付は tz で判定する
+pub async fn load_reading_activities(
+    pool: &SqlitePool,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    tz: Tz,
+) -> Result<Vec<ReadingActivity>, sqlx::Error> {
+    let sql = "
+        SELECT
+            rm.created_at as created_at,
+            b.title as book_title,
+            COALESCE((
+                SELECT prev.page_number FROM reading_memos prev
+                WHERE prev.book_id = rm.book_id
+                  AND prev.deleted_at IS NULL
+                  AND prev.created_at < rm.created_at
+                ORDER BY prev.created_at DESC
+                LIMIT 1
+            ), 0) as start_page,
+            rm.page_number as end_page,
+            rm.id as memo_id
+        FROM reading_memos rm
+        JOIN books b ON rm.book_id = b.id
+        WHERE rm.deleted_at IS NULL
+          AND datetime(rm.created_at) >= ? AND datetime(rm.created_at) < ?
+        ORDER BY datetime(rm.created_at) ASC
+    ";
+
+    let rows = sqlx::query(sql)
+        .bind(to_sql_datetime(from))
+        .bind(to_sql_datetime(to))
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            let created_at: String = row.try_get("created_at")?;
+            let date = DateTime::parse_from_rfc3339(&created_at)
+                .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| created_at.chars().take(10).collect());
+            Ok(ReadingActivity {
+                date,
+                book_title: row.try_get("book_title")?,
+                start_page: row.try_get("start_page")?,
+                end_page: row.try_get("end_page")?,
+                memo_id: row.try_get("memo_id")?,
+            })
+        })
+        .collect()
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date {}: {}", date, e))
+}
+
 #[tauri::command]
 pub async fn get_reading_activities(
     state: State<'_, AppState>,
     start_date: String,
     end_date: String,
 ) -> Result<Vec<ReadingActivity>, String> {
-    let sql = "
-        WITH Activity AS (
-            SELECT 
-                strftime('%Y-%m-%d', rm.created_at) as date,
-                b.title as book_title,
-                LAG(rm.page_number, 1, 0) OVER (PARTITION BY rm.book_id ORDER BY rm.created_at) as start_page,
-                rm.page_number as end_page,
-                rm.id as memo_id
-            FROM reading_memos rm
-            JOIN books b ON rm.book_id = b.id
-            WHERE rm.deleted_at IS NULL
-        )
-        SELECT * FROM Activity
-        WHERE date >= ? AND date <= ?
-        ORDER BY date ASC
-    ";
-
-    sqlx::query_as::<_, ReadingActivity>(sql)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(&state.pool)
+    let tz = load_user_timezone(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let from = start_of_day(parse_date(&start_date)?, tz);
+    let to = start_of_day(parse_date(&end_date)? + Duration::days(1), tz);
+    load_reading_activities(&state.pool, from, to, tz)
         .await
         .map_err(|e| e.to_string())
 }
@@ -1,4 +1,4 @@
-use chrono::Local;
+use crate::commands::settings::timezone::now_for_db;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use uuid::Uuid;
@@ -36,7 +36,7 @@ impl Book {
             status: "To Read".to_string(),
             total_pages,
             cover_image_path: None,
-            created_at: Local::now().to_rfc3339(),
+            created_at: now_for_db(),
             updated_at: None,
             deleted_at: None,
         }
@@ -75,5 +75,20 @@ pub async fn init_books_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    // 読書記録の期間検索と、同じ本の直前のメモの検索用
+    sqlx::query("DROP INDEX IF EXISTS idx_reading_memos_date")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_reading_memos_created_at ON reading_memos(datetime(created_at))",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_reading_memos_book ON reading_memos(book_id, created_at)",
+    )
+    .execute(pool)
+    .await?;
+
     Ok(())
 }
@@ -1,8 +1,10 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 use tauri::Manager;
 
+use crate::commands::sandbox::resolve_within;
+
 pub fn get_books_dir(app_handle: &AppHandle) -> PathBuf {
     let app_dir = app_handle
         .path()
@@ -35,8 +37,16 @@ pub fn save_memo_content(
     Ok(path.to_string_lossy().to_string())
 }
 
-pub fn read_memo_content(path_str: &str) -> Result<String, String> {
-    let path = PathBuf::from(path_str);
+// content_path は絶対パスで保存されているため、メモのフォルダからの相対パスに直してから
+// フォルダの外を指していないか確かめる
+pub fn read_memo_content(app_handle: &AppHandle, path_str: &str) -> Result<String, String> {
+    let dir = get_memos_dir(app_handle);
+    let relative = Path::new(path_str)
+        .strip_prefix(&dir)
+        .unwrap_or(Path::new(path_str))
+        .to_string_lossy()
+        .to_string();
+    let path = resolve_within(&dir, &relative)?;
     if !path.exists() {
         return Ok("".to_string());
     }
@@ -1 +1,4 @@
+pub mod event;
+pub mod items;
+pub mod journal;
 pub mod memo;
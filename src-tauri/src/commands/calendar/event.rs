@@ -0,0 +1,379 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::commands::settings::timezone::to_db_datetime;
+use crate::commands::task::sql::task_query::to_sql_datetime;
+use crate::AppState;
+
+// タスクとは別の予定 (会議など)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Event {
+    pub id: Uuid,
+    pub title: String,
+    pub location: Option<String>,
+    pub all_day: bool,
+    pub start_datetime: DateTime<Local>,
+    pub end_datetime: DateTime<Local>,
+    pub color: Option<String>,
+    pub notes: Option<String>,
+    //メタ情報
+    pub created_at: DateTime<Local>,
+    pub updated_at: Option<DateTime<Local>>,
+    pub deleted_at: Option<DateTime<Local>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateEventDto {
+    pub title: String,
+    pub location: Option<String>,
+    #[serde(default)]
+    pub all_day: bool,
+    pub start_datetime: DateTime<Local>,
+    pub end_datetime: DateTime<Local>,
+    pub color: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl Event {
+    pub fn new(start_datetime: DateTime<Local>, end_datetime: DateTime<Local>) -> Self {
+        Event {
+            id: Uuid::new_v4(),
+            title: "".to_string(),
+            location: None,
+            all_day: false,
+            start_datetime,
+            end_datetime,
+            color: None,
+            notes: None,
+            created_at: Local::now(),
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    pub fn update_updated_at(&mut self) {
+        self.updated_at = Some(Local::now());
+    }
+
+    pub fn set_deleted(&mut self) {
+        self.deleted_at = Some(Local::now());
+    }
+}
+
+pub async fn init_event_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS events (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            location TEXT,
+            all_day BOOLEAN NOT NULL,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            color TEXT,
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            deleted_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_start_date ON events(datetime(start_date))")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_end_date ON events(datetime(end_date))")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn save_event(pool: &SqlitePool, event: &mut Event) -> Result<(), sqlx::Error> {
+    init_event_table(pool).await?;
+
+    event.update_updated_at();
+    let updated_at = event.updated_at.map(|dt| to_db_datetime(&dt));
+    let deleted_at = event.deleted_at.map(|dt| to_db_datetime(&dt));
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO events (id, title, location, all_day, start_date, end_date, color, notes, created_at, updated_at, deleted_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(event.id.to_string())
+    .bind(&event.title)
+    .bind(&event.location)
+    .bind(event.all_day)
+    .bind(to_db_datetime(&event.start_datetime))
+    .bind(to_db_datetime(&event.end_datetime))
+    .bind(&event.color)
+    .bind(&event.notes)
+    .bind(to_db_datetime(&event.created_at))
+    .bind(&updated_at)
+    .bind(&deleted_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 削除済みの予定は書き換えない。更新できたら true
+pub async fn update_saved_event(pool: &SqlitePool, event: &mut Event) -> Result<bool, sqlx::Error> {
+    init_event_table(pool).await?;
+
+    event.update_updated_at();
+    let updated_at = event.updated_at.map(|dt| to_db_datetime(&dt));
+
+    let result = sqlx::query(
+        "UPDATE events
+         SET title = ?, location = ?, all_day = ?, start_date = ?, end_date = ?, color = ?, notes = ?, updated_at = ?
+         WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(&event.title)
+    .bind(&event.location)
+    .bind(event.all_day)
+    .bind(to_db_datetime(&event.start_datetime))
+    .bind(to_db_datetime(&event.end_datetime))
+    .bind(&event.color)
+    .bind(&event.notes)
+    .bind(&updated_at)
+    .bind(event.id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+fn row_to_event(row: &SqliteRow) -> Result<Event, sqlx::Error> {
+    let parse = |s: String| {
+        DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&Local))
+            .ok()
+    };
+
+    let id: String = row.try_get("id")?;
+    let start: String = row.try_get("start_date")?;
+    let end: String = row.try_get("end_date")?;
+    let created_at: String = row.try_get("created_at")?;
+    let updated_at: Option<String> = row.try_get("updated_at")?;
+    let deleted_at: Option<String> = row.try_get("deleted_at")?;
+    let start_datetime = parse(start).unwrap_or_else(Local::now);
+
+    Ok(Event {
+        id: Uuid::parse_str(&id).unwrap_or(Uuid::new_v4()),
+        title: row.try_get("title")?,
+        location: row.try_get("location")?,
+        all_day: row.try_get("all_day")?,
+        start_datetime,
+        end_datetime: parse(end).unwrap_or(start_datetime),
+        color: row.try_get("color")?,
+        notes: row.try_get("notes")?,
+        created_at: parse(created_at).unwrap_or_else(Local::now),
+        updated_at: updated_at.and_then(parse),
+        deleted_at: deleted_at.and_then(parse),
+    })
+}
+
+// [start, end) と重なる予定を開始順に返す (end ちょうどに始まる予定・start ちょうどに終わる予定は含めない)
+// 長さ 0 の予定は start ちょうどでも含める
+pub async fn load_events_between(
+    pool: &SqlitePool,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Vec<Event>, sqlx::Error> {
+    init_event_table(pool).await?;
+
+    let rows = sqlx::query(
+        "SELECT * FROM events
+         WHERE deleted_at IS NULL AND datetime(start_date) < ?
+           AND (datetime(end_date) > ? OR datetime(start_date) >= ?)
+         ORDER BY datetime(start_date)",
+    )
+    .bind(to_sql_datetime(end))
+    .bind(to_sql_datetime(start))
+    .bind(to_sql_datetime(start))
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(row_to_event).collect()
+}
+
+pub async fn load_all_events(pool: &SqlitePool) -> Result<Vec<Event>, sqlx::Error> {
+    init_event_table(pool).await?;
+
+    let rows =
+        sqlx::query("SELECT * FROM events WHERE deleted_at IS NULL ORDER BY datetime(start_date)")
+            .fetch_all(pool)
+            .await?;
+    rows.iter().map(row_to_event).collect()
+}
+
+pub async fn load_event(pool: &SqlitePool, id: Uuid) -> Result<Option<Event>, sqlx::Error> {
+    init_event_table(pool).await?;
+
+    let row = sqlx::query("SELECT * FROM events WHERE id = ? AND deleted_at IS NULL")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    row.as_ref().map(row_to_event).transpose()
+}
+
+fn validate(event: &Event) -> Result<(), String> {
+    if event.title.trim().is_empty() {
+        return Err("Title is required".to_string());
+    }
+    if event.end_datetime < event.start_datetime {
+        return Err("End must not be before start".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_events(
+    state: tauri::State<'_, AppState>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<Vec<Event>, String> {
+    load_events_between(&state.pool, start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_event(
+    state: tauri::State<'_, AppState>,
+    event_dto: CreateEventDto,
+) -> Result<Event, String> {
+    let mut event = Event::new(event_dto.start_datetime, event_dto.end_datetime);
+    event.title = event_dto.title;
+    event.location = event_dto.location;
+    event.all_day = event_dto.all_day;
+    event.color = event_dto.color;
+    event.notes = event_dto.notes;
+    validate(&event)?;
+
+    save_event(&state.pool, &mut event)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(event)
+}
+
+#[tauri::command]
+pub async fn update_event(
+    state: tauri::State<'_, AppState>,
+    event: Event,
+) -> Result<Event, String> {
+    let mut event = event;
+    validate(&event)?;
+    let updated = update_saved_event(&state.pool, &mut event)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !updated {
+        return Err("Event not found".to_string());
+    }
+    // created_at などは保存されている値を返す
+    load_event(&state.pool, event.id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Event not found".to_string())
+}
+
+#[tauri::command]
+pub async fn delete_event(state: tauri::State<'_, AppState>, id: Uuid) -> Result<(), String> {
+    let Some(mut event) = load_event(&state.pool, id)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Err("Event not found".to_string());
+    };
+    event.set_deleted();
+    save_event(&state.pool, &mut event)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("Failed to connect to test database")
+    }
+
+    #[tokio::test]
+    async fn test_event_round_trip() {
+        let pool = setup_test_db().await;
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        let mut event = Event::new(start, start + Duration::hours(1));
+        event.title = "Meeting".to_string();
+        save_event(&pool, &mut event).await.unwrap();
+
+        let events = load_events_between(&pool, start, start + Duration::days(1))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Meeting");
+
+        event.title = "Review".to_string();
+        event.location = Some("Room A".to_string());
+        assert!(update_saved_event(&pool, &mut event).await.unwrap());
+        let loaded = load_event(&pool, event.id).await.unwrap().unwrap();
+        assert_eq!(loaded.title, "Review");
+        assert_eq!(loaded.location.as_deref(), Some("Room A"));
+        assert_eq!(loaded.start_datetime, start);
+
+        event.set_deleted();
+        save_event(&pool, &mut event).await.unwrap();
+        assert!(load_all_events(&pool).await.unwrap().is_empty());
+        assert!(load_event(&pool, event.id).await.unwrap().is_none());
+
+        // 削除済みの予定は更新されない
+        event.title = "Revived".to_string();
+        assert!(!update_saved_event(&pool, &mut event).await.unwrap());
+        let title: String = sqlx::query_scalar("SELECT title FROM events WHERE id = ?")
+            .bind(event.id.to_string())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(title, "Review");
+    }
+
+    #[tokio::test]
+    async fn test_events_between_excludes_boundaries() {
+        let pool = setup_test_db().await;
+        let from = Local.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+        let to = from + Duration::days(1);
+        for (title, start, end) in [
+            ("ends at from", from - Duration::hours(1), from),
+            ("zero length at from", from, from),
+            (
+                "inside",
+                from + Duration::hours(9),
+                from + Duration::hours(10),
+            ),
+            (
+                "spans the range",
+                from - Duration::hours(1),
+                to + Duration::hours(1),
+            ),
+            ("starts at to", to, to + Duration::hours(1)),
+        ] {
+            let mut event = Event::new(start, end);
+            event.title = title.to_string();
+            save_event(&pool, &mut event).await.unwrap();
+        }
+
+        let titles: Vec<String> = load_events_between(&pool, from, to)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.title)
+            .collect();
+        assert_eq!(titles, ["spans the range", "zero length at from", "inside"]);
+    }
+}
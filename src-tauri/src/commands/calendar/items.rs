@@ -0,0 +1,68 @@
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::commands::books::commands::{load_reading_activities, ReadingActivity};
+use crate::commands::calendar::event::{load_events_between, Event};
+use crate::commands::calendar::memo::{load_memos, CalendarMemo};
+use crate::commands::settings::timezone::{load_user_timezone, start_of_day};
+use crate::commands::task::sql::task_query::query_tasks_between;
+use crate::commands::task::task::Task;
+use crate::AppState;
+
+// カレンダーに表示する日付付きのデータ (src/type/calendar.ts の CalendarEvent と同じ形)
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum CalendarItem {
+    Task(Task),
+    Event(Event),
+    Memo(CalendarMemo),
+    Reading(ReadingActivity),
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date {}: {}", date, e))
+}
+
+// start_date から end_date まで (両端を含む, YYYY-MM-DD) のデータをまとめて返す
+#[tauri::command]
+pub async fn get_calendar_items(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<CalendarItem>, String> {
+    let pool = &state.pool;
+    let tz = load_user_timezone(pool).await.map_err(|e| e.to_string())?;
+    let from = start_of_day(parse_date(&start_date)?, tz);
+    let to = start_of_day(parse_date(&end_date)? + Duration::days(1), tz);
+
+    let mut items = Vec::new();
+    items.extend(
+        query_tasks_between(pool, from, to)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(CalendarItem::Task),
+    );
+    items.extend(
+        load_events_between(pool, from, to)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(CalendarItem::Event),
+    );
+    items.extend(
+        load_memos(pool, &start_date, &end_date)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(CalendarItem::Memo),
+    );
+    items.extend(
+        load_reading_activities(pool, from, to, tz)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(CalendarItem::Reading),
+    );
+    Ok(items)
+}
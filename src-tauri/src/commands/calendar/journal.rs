@@ -0,0 +1,315 @@
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::commands::calendar::memo::{load_memo_by_date, save_memo_content, CalendarMemo};
+use crate::commands::notes::fs::write_note_file;
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::sandbox::{check_relative, relative_to, resolve_within};
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::settings::timezone::{load_user_timezone, start_of_day};
+use crate::commands::task::sql::task_query::query_tasks;
+use crate::commands::task::task::Task;
+use crate::commands::task::task_query::{DueFilter, TaskQuery};
+use crate::AppState;
+
+pub const DAILY_TEMPLATE_KEY: &str = "daily_template";
+
+const DEFAULT_TEMPLATE: &str = "# {{date}} ({{weekday}})
+
+## Tasks due today
+{{tasks_due_today}}
+
+## Unfinished from yesterday
+{{unfinished_yesterday}}
+
+## Notes
+";
+
+// 日別メモのテンプレートと、ノートとして保存するかどうか
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DailyTemplate {
+    pub content: String,
+    pub store_as_note: bool,
+    // ノートのルートからの相対パス (空ならルート直下)
+    pub note_folder: String,
+}
+
+impl Default for DailyTemplate {
+    fn default() -> Self {
+        DailyTemplate {
+            content: DEFAULT_TEMPLATE.to_string(),
+            store_as_note: false,
+            note_folder: "Daily".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyMemo {
+    pub memo: CalendarMemo,
+    pub note_path: Option<String>,
+}
+
+// テンプレートに埋め込む値
+pub struct TemplateContext<'a> {
+    pub date: NaiveDate,
+    pub tasks_due_today: &'a [Task],
+    pub unfinished_yesterday: &'a [Task],
+}
+
+fn task_list(tasks: &[Task]) -> String {
+    if tasks.is_empty() {
+        return "- (none)".to_string();
+    }
+    tasks
+        .iter()
+        .map(|task| {
+            let mark = if task.completed { "x" } else { " " };
+            format!("- [{}] {}", mark, task.description)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// プレースホルダーは一度に置き換える (タスク名などに含まれる {{...}} は展開しない)
+pub fn render_template(template: &str, ctx: &TemplateContext) -> String {
+    let value = |key: &str| match key {
+        "date" => Some(ctx.date.format("%Y-%m-%d").to_string()),
+        "weekday" => Some(ctx.date.format("%A").to_string()),
+        "yesterday" => Some(
+            (ctx.date - Duration::days(1))
+                .format("%Y-%m-%d")
+                .to_string(),
+        ),
+        "tomorrow" => Some(
+            (ctx.date + Duration::days(1))
+                .format("%Y-%m-%d")
+                .to_string(),
+        ),
+        "tasks_due_today" => Some(task_list(ctx.tasks_due_today)),
+        "unfinished_yesterday" => Some(task_list(ctx.unfinished_yesterday)),
+        _ => None,
+    };
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after
+            .find("}}")
+            .and_then(|end| value(&after[..end]).map(|v| (end, v)))
+        {
+            Some((end, v)) => {
+                out.push_str(&v);
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub async fn load_daily_template(pool: &SqlitePool) -> Result<DailyTemplate, sqlx::Error> {
+    Ok(get_setting(pool, DAILY_TEMPLATE_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+async fn daily_note_path(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    template: &DailyTemplate,
+    date: &str,
+) -> Result<PathBuf, String> {
+    let root = ensure_notes_dir(app_handle, pool).await?;
+    let relative = Path::new(&template.note_folder).join(format!("{}.md", date));
+    resolve_within(&root, &relative.to_string_lossy())
+}
+
+// ノートの編集画面からの保存と同じく、版を残して索引も更新する
+async fn write_note(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    path: &Path,
+    content: &str,
+) -> Result<(), String> {
+    let root = ensure_notes_dir(app_handle, pool).await?;
+    write_note_file(pool, &root, path, content).await
+}
+
+async fn tasks_due_on(
+    pool: &SqlitePool,
+    date: NaiveDate,
+    completed: Option<bool>,
+) -> Result<Vec<Task>, sqlx::Error> {
+    let tz = load_user_timezone(pool).await?;
+    let query = TaskQuery {
+        completed,
+        due: Some(DueFilter::Range {
+            from: Some(start_of_day(date, tz)),
+            to: Some(start_of_day(date + Duration::days(1), tz)),
+        }),
+        ..Default::default()
+    };
+    query_tasks(pool, &query).await
+}
+
+// カレンダーのメモ欄で保存された内容を日別ノートにも反映する (ノートがある場合のみ)
+pub async fn update_daily_note(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    date: &str,
+    content: &str,
+) -> Result<(), String> {
+    let template = load_daily_template(pool).await.map_err(|e| e.to_string())?;
+    if !template.store_as_note {
+        return Ok(());
+    }
+    let path = daily_note_path(app_handle, pool, &template, date).await?;
+    if path.exists() {
+        write_note(app_handle, pool, &path, content).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_daily_template(
+    state: tauri::State<'_, AppState>,
+) -> Result<DailyTemplate, String> {
+    load_daily_template(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_daily_template(
+    state: tauri::State<'_, AppState>,
+    template: DailyTemplate,
+) -> Result<DailyTemplate, String> {
+    check_relative(&template.note_folder)?;
+    set_setting(&state.pool, DAILY_TEMPLATE_KEY, &template)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(template)
+}
+
+// その日のメモを開く
+// ノートとして保存している場合はノートの内容を優先し、まだメモがなければテンプレートから作る
+#[tauri::command]
+pub async fn open_daily_memo(
+    app_handle: AppHandle,
+    state: tauri::State<'_, AppState>,
+    date: String,
+) -> Result<DailyMemo, String> {
+    let pool = &state.pool;
+    let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date {}: {}", date, e))?;
+    let template = load_daily_template(pool).await.map_err(|e| e.to_string())?;
+    let note_path = if template.store_as_note {
+        Some(daily_note_path(&app_handle, pool, &template, &date).await?)
+    } else {
+        None
+    };
+
+    let existing = load_memo_by_date(pool, &date)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let memo = match (&note_path, existing) {
+        // ノート側で編集された内容をメモに取り込む
+        (Some(path), _) if path.exists() => {
+            let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            save_memo_content(pool, &date, &content)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        (_, Some(memo)) => memo,
+        (_, None) => {
+            let tasks_due_today = tasks_due_on(pool, day, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            let unfinished_yesterday = tasks_due_on(pool, day - Duration::days(1), Some(false))
+                .await
+                .map_err(|e| e.to_string())?;
+            let content = render_template(
+                &template.content,
+                &TemplateContext {
+                    date: day,
+                    tasks_due_today: &tasks_due_today,
+                    unfinished_yesterday: &unfinished_yesterday,
+                },
+            );
+            save_memo_content(pool, &date, &content)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    if let Some(path) = &note_path {
+        if !path.exists() {
+            write_note(&app_handle, pool, path, &memo.content).await?;
+        }
+    }
+
+    let note_path = match note_path {
+        Some(path) => Some(relative_to(
+            &ensure_notes_dir(&app_handle, pool).await?,
+            &path,
+        )?),
+        None => None,
+    };
+    Ok(DailyMemo { memo, note_path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let mut done = Task::new();
+        done.description = "Write report".to_string();
+        done.completed = true;
+        let mut open = Task::new();
+        open.description = "Call Bob".to_string();
+
+        let text = render_template(
+            "{{date}} {{weekday}}\n{{tasks_due_today}}\n{{unfinished_yesterday}}",
+            &TemplateContext {
+                date: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                tasks_due_today: &[done],
+                unfinished_yesterday: &[open],
+            },
+        );
+        assert_eq!(
+            text,
+            "2024-05-01 Wednesday\n- [x] Write report\n- [ ] Call Bob"
+        );
+
+        // タスク名の中のプレースホルダーや知らないプレースホルダーはそのまま
+        let mut odd = Task::new();
+        odd.description = "Fix {{date}} and {{unfinished_yesterday}}".to_string();
+        let text = render_template(
+            "{{tasks_due_today}} {{unknown}} {{date",
+            &TemplateContext {
+                date: NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                tasks_due_today: &[odd],
+                unfinished_yesterday: &[],
+            },
+        );
+        assert_eq!(
+            text,
+            "- [ ] Fix {{date}} and {{unfinished_yesterday}} {{unknown}} {{date"
+        );
+    }
+}
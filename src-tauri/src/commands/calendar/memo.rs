@@ -1,91 +1,443 @@
+use crate::commands::calendar::journal::update_daily_note;
+use crate::commands::migration::has_column;
+use crate::commands::search::db::{remove_document, SearchKind};
+use crate::commands::search::indexer::index_calendar_memo;
+use crate::commands::settings::timezone::{now_for_db, to_db_datetime};
 use crate::AppState;
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+// 1日あたりに残す過去の版の数
+const MAX_MEMO_VERSIONS: i64 = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CalendarMemo {
+    pub id: Uuid,
     pub date: String, // YYYY-MM-DD
     pub content: String,
+    //メタ情報
+    pub created_at: DateTime<Local>,
+    pub updated_at: Option<DateTime<Local>>,
+    pub deleted_at: Option<DateTime<Local>>,
+}
+
+// 上書き・削除される前のメモの内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalendarMemoVersion {
+    pub id: Uuid,
+    pub memo_id: Uuid,
+    pub date: String,
+    pub content: String,
+    pub created_at: DateTime<Local>,
+}
+
+impl CalendarMemo {
+    pub fn new(date: &str, content: &str) -> Self {
+        CalendarMemo {
+            id: Uuid::new_v4(),
+            date: date.to_string(),
+            content: content.to_string(),
+            created_at: Local::now(),
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
 }
 
 pub async fn init_memo_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // 旧スキーマ (date が主キーで id を持たない) からの移行
+    if has_column(pool, "calendar_memos", "date").await?
+        && !has_column(pool, "calendar_memos", "id").await?
+    {
+        migrate_legacy_memo_table(pool).await?;
+    }
+
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS calendar_memos (
-            date TEXT PRIMARY KEY,
-            content TEXT NOT NULL
+            id TEXT PRIMARY KEY,
+            date TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            deleted_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    // 削除されていないメモは1日に1件だけ
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_calendar_memos_date
+         ON calendar_memos(date) WHERE deleted_at IS NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS calendar_memo_versions (
+            id TEXT PRIMARY KEY,
+            memo_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL
         )",
     )
     .execute(pool)
     .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_calendar_memo_versions_date
+         ON calendar_memo_versions(date, created_at)",
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
+async fn migrate_legacy_memo_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("ALTER TABLE calendar_memos RENAME TO calendar_memos_legacy")
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "CREATE TABLE calendar_memos (
+            id TEXT PRIMARY KEY,
+            date TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            deleted_at TEXT
+        )",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let rows = sqlx::query("SELECT date, content FROM calendar_memos_legacy")
+        .fetch_all(&mut *tx)
+        .await?;
+    let now = now_for_db();
+    for row in rows {
+        sqlx::query(
+            "INSERT INTO calendar_memos (id, date, content, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(row.get::<String, _>("date"))
+        .bind(row.get::<String, _>("content"))
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("DROP TABLE calendar_memos_legacy")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+fn parse_datetime(s: String) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(&s)
+        .map(|dt| dt.with_timezone(&Local))
+        .ok()
+}
+
+fn row_to_memo(row: &SqliteRow) -> Result<CalendarMemo, sqlx::Error> {
+    let id: String = row.try_get("id")?;
+    let created_at: String = row.try_get("created_at")?;
+    let updated_at: Option<String> = row.try_get("updated_at")?;
+    let deleted_at: Option<String> = row.try_get("deleted_at")?;
+
+    Ok(CalendarMemo {
+        id: Uuid::parse_str(&id).unwrap_or(Uuid::new_v4()),
+        date: row.try_get("date")?,
+        content: row.try_get("content")?,
+        created_at: parse_datetime(created_at).unwrap_or_else(Local::now),
+        updated_at: updated_at.and_then(parse_datetime),
+        deleted_at: deleted_at.and_then(parse_datetime),
+    })
+}
+
+fn row_to_version(row: &SqliteRow) -> Result<CalendarMemoVersion, sqlx::Error> {
+    let id: String = row.try_get("id")?;
+    let memo_id: String = row.try_get("memo_id")?;
+    let created_at: String = row.try_get("created_at")?;
+
+    Ok(CalendarMemoVersion {
+        id: Uuid::parse_str(&id).unwrap_or(Uuid::new_v4()),
+        memo_id: Uuid::parse_str(&memo_id).unwrap_or(Uuid::new_v4()),
+        date: row.try_get("date")?,
+        content: row.try_get("content")?,
+        created_at: parse_datetime(created_at).unwrap_or_else(Local::now),
+    })
+}
+
+pub async fn load_memos(
+    pool: &SqlitePool,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CalendarMemo>, sqlx::Error> {
+    init_memo_table(pool).await?;
+
+    let rows = sqlx::query(
+        "SELECT * FROM calendar_memos
+         WHERE deleted_at IS NULL AND date >= ? AND date <= ?
+         ORDER BY date",
+    )
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter().map(row_to_memo).collect()
+}
+
+pub async fn load_all_memos(pool: &SqlitePool) -> Result<Vec<CalendarMemo>, sqlx::Error> {
+    init_memo_table(pool).await?;
+
+    let rows = sqlx::query("SELECT * FROM calendar_memos WHERE deleted_at IS NULL ORDER BY date")
+        .fetch_all(pool)
+        .await?;
+
+    rows.iter().map(row_to_memo).collect()
+}
+
+pub async fn load_memo_by_date(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<Option<CalendarMemo>, sqlx::Error> {
+    init_memo_table(pool).await?;
+
+    let row = sqlx::query("SELECT * FROM calendar_memos WHERE deleted_at IS NULL AND date = ?")
+        .bind(date)
+        .fetch_optional(pool)
+        .await?;
+    row.as_ref().map(row_to_memo).transpose()
+}
+
+async fn save_memo_version(pool: &SqlitePool, memo: &CalendarMemo) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO calendar_memo_versions (id, memo_id, date, content, created_at)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(memo.id.to_string())
+    .bind(&memo.date)
+    .bind(&memo.content)
+    .bind(now_for_db())
+    .execute(pool)
+    .await?;
+
+    // 古い版は MAX_MEMO_VERSIONS 件を超えた分から消す
+    sqlx::query(
+        "DELETE FROM calendar_memo_versions
+         WHERE date = ? AND id NOT IN (
+             SELECT id FROM calendar_memo_versions
+             WHERE date = ?
+             ORDER BY datetime(created_at) DESC, rowid DESC
+             LIMIT ?
+         )",
+    )
+    .bind(&memo.date)
+    .bind(&memo.date)
+    .bind(MAX_MEMO_VERSIONS)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 内容が変わる場合は、上書きする前の内容を版として残す
+pub async fn save_memo_content(
+    pool: &SqlitePool,
+    date: &str,
+    content: &str,
+) -> Result<CalendarMemo, sqlx::Error> {
+    init_memo_table(pool).await?;
+
+    let mut memo = match load_memo_by_date(pool, date).await? {
+        Some(memo) if memo.content == content => return Ok(memo),
+        Some(memo) => {
+            save_memo_version(pool, &memo).await?;
+            memo
+        }
+        None => CalendarMemo::new(date, content),
+    };
+    memo.content = content.to_string();
+    memo.updated_at = Some(Local::now());
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO calendar_memos (id, date, content, created_at, updated_at, deleted_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(memo.id.to_string())
+    .bind(&memo.date)
+    .bind(&memo.content)
+    .bind(to_db_datetime(&memo.created_at))
+    .bind(memo.updated_at.map(|dt| to_db_datetime(&dt)))
+    .bind(memo.deleted_at.map(|dt| to_db_datetime(&dt)))
+    .execute(pool)
+    .await?;
+
+    if let Err(e) = index_calendar_memo(pool, &memo).await {
+        println!("Failed to index the memo of {}: {}", memo.date, e);
+    }
+    Ok(memo)
+}
+
+pub async fn delete_memo_by_date(pool: &SqlitePool, date: &str) -> Result<(), sqlx::Error> {
+    let Some(memo) = load_memo_by_date(pool, date).await? else {
+        return Ok(());
+    };
+    save_memo_version(pool, &memo).await?;
+
+    let now = now_for_db();
+    sqlx::query("UPDATE calendar_memos SET deleted_at = ?, updated_at = ? WHERE id = ?")
+        .bind(&now)
+        .bind(&now)
+        .bind(memo.id.to_string())
+        .execute(pool)
+        .await?;
+
+    if let Err(e) = remove_document(pool, SearchKind::CalendarMemo, date).await {
+        println!(
+            "Failed to remove the memo of {} from the index: {}",
+            date, e
+        );
+    }
+    Ok(())
+}
+
+pub async fn load_memo_versions(
+    pool: &SqlitePool,
+    date: &str,
+) -> Result<Vec<CalendarMemoVersion>, sqlx::Error> {
+    init_memo_table(pool).await?;
+
+    let rows = sqlx::query(
+        "SELECT * FROM calendar_memo_versions
+         WHERE date = ?
+         ORDER BY datetime(created_at) DESC, rowid DESC",
+    )
+    .bind(date)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(row_to_version).collect()
+}
+
 #[tauri::command]
 pub async fn get_memos(
     state: tauri::State<'_, AppState>,
     start_date: String,
     end_date: String,
 ) -> Result<Vec<CalendarMemo>, String> {
-    init_memo_table(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let rows = sqlx::query("SELECT * FROM calendar_memos WHERE date >= ? AND date <= ?")
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(&state.pool)
+    load_memos(&state.pool, &start_date, &end_date)
         .await
-        .map_err(|e| e.to_string())?;
-
-    let memos = rows
-        .into_iter()
-        .map(|row| CalendarMemo {
-            date: row.get("date"),
-            content: row.get("content"),
-        })
-        .collect();
-
-    Ok(memos)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub async fn save_memo(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     date: String,
     content: String,
 ) -> Result<(), String> {
-    init_memo_table(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
     if content.trim().is_empty() {
-        // Option: Delete if empty? Or just save empty string.
-        // User might want to clear memo.
-        // If empty, let's delete it to keep DB clean.
+        // 空のメモは削除として扱う (内容は版として残る)
         return delete_memo(state, date).await;
     }
 
-    sqlx::query("INSERT OR REPLACE INTO calendar_memos (date, content) VALUES (?, ?)")
-        .bind(date)
-        .bind(content)
-        .execute(&state.pool)
+    save_memo_content(&state.pool, &date, &content)
         .await
         .map_err(|e| e.to_string())?;
-
-    Ok(())
+    update_daily_note(&app_handle, &state.pool, &date, &content).await
 }
 
 #[tauri::command]
 pub async fn delete_memo(state: tauri::State<'_, AppState>, date: String) -> Result<(), String> {
+    delete_memo_by_date(&state.pool, &date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_memo_versions(
+    state: tauri::State<'_, AppState>,
+    date: String,
+) -> Result<Vec<CalendarMemoVersion>, String> {
+    load_memo_versions(&state.pool, &date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 過去の版の内容でその日のメモを書き戻す (現在の内容も版として残る)
+#[tauri::command]
+pub async fn restore_memo_version(
+    state: tauri::State<'_, AppState>,
+    version_id: Uuid,
+) -> Result<CalendarMemo, String> {
     init_memo_table(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
 
-    sqlx::query("DELETE FROM calendar_memos WHERE date = ?")
-        .bind(date)
-        .execute(&state.pool)
+    let row = sqlx::query("SELECT * FROM calendar_memo_versions WHERE id = ?")
+        .bind(version_id.to_string())
+        .fetch_optional(&state.pool)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())?
+        .ok_or("Memo version not found")?;
+    let version = row_to_version(&row).map_err(|e| e.to_string())?;
 
-    Ok(())
+    save_memo_content(&state.pool, &version.date, &version.content)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("Failed to connect to test database")
+    }
+
+    #[tokio::test]
+    async fn test_migrate_and_keep_versions() {
+        let pool = setup_test_db().await;
+        sqlx::query("CREATE TABLE calendar_memos (date TEXT PRIMARY KEY, content TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO calendar_memos (date, content) VALUES ('2024-05-01', 'old')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let memos = load_all_memos(&pool).await.unwrap();
+        assert_eq!(memos.len(), 1);
+        assert_eq!(memos[0].content, "old");
+
+        let saved = save_memo_content(&pool, "2024-05-01", "new").await.unwrap();
+        assert_eq!(saved.id, memos[0].id);
+        delete_memo_by_date(&pool, "2024-05-01").await.unwrap();
+        assert!(load_memo_by_date(&pool, "2024-05-01")
+            .await
+            .unwrap()
+            .is_none());
+
+        let versions = load_memo_versions(&pool, "2024-05-01").await.unwrap();
+        let contents: Vec<_> = versions.iter().map(|v| v.content.as_str()).collect();
+        assert_eq!(contents, vec!["new", "old"]);
+
+        // 削除後も同じ日に新しいメモを作れる
+        let recreated = save_memo_content(&pool, "2024-05-01", "again")
+            .await
+            .unwrap();
+        assert_ne!(recreated.id, saved.id);
+    }
 }
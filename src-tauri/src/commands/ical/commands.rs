@@ -0,0 +1,78 @@
+use chrono::{Local, Utc};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::commands::calendar::event::load_all_events;
+use crate::commands::calendar::memo::load_all_memos;
+use crate::commands::ical::export::{build_calendar, IcsExportOptions};
+use crate::commands::ical::import::{apply_import, collect_items, IcsImportReport};
+use crate::commands::ical::parse::parse;
+use crate::commands::settings::timezone::load_user_timezone;
+use crate::commands::task::sql::task_query::query_tasks;
+use crate::commands::task::task_query::TaskQuery;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn export_ics(
+    state: State<'_, AppState>,
+    path: String,
+    options: Option<IcsExportOptions>,
+) -> Result<(), String> {
+    let options = options.unwrap_or_default();
+
+    let tasks = if options.include_tasks {
+        let query = TaskQuery {
+            group_id: options.group_id,
+            completed: if options.include_completed {
+                None
+            } else {
+                Some(false)
+            },
+            ..TaskQuery::default()
+        };
+        query_tasks(&state.pool, &query)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let events = if options.include_events {
+        load_all_events(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let memos = if options.include_memos {
+        load_all_memos(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let tz = load_user_timezone(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let ics = build_calendar(&tasks, &events, &memos, Some(tz), Utc::now());
+    std::fs::write(&path, ics).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_ics(
+    state: State<'_, AppState>,
+    path: String,
+    group_id: Uuid,
+) -> Result<IcsImportReport, String> {
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let calendars = parse(&String::from_utf8_lossy(&bytes))?;
+
+    let mut report = IcsImportReport::default();
+    let (tasks, memos) = collect_items(&calendars, Local::now(), &mut report);
+    apply_import(&state.pool, group_id, tasks, memos, &mut report)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report)
+}
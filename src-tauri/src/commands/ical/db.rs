@@ -0,0 +1,67 @@
+use crate::commands::settings::timezone::now_for_db;
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+// iCalendar の UID (と繰り返しの発生日時) と取り込んだタスクの対応
+// 同じファイルを再取り込みしたときに重複させず更新するために使う
+pub async fn init_rela_task_ical_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS rela_task_ical (
+            ical_uid TEXT NOT NULL,
+            occurrence TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            PRIMARY KEY (ical_uid, occurrence)
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_task_id_from_ical_uid(
+    pool: &SqlitePool,
+    ical_uid: &str,
+    occurrence: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    init_rela_task_ical_table(pool).await?;
+
+    let row =
+        sqlx::query("SELECT task_id FROM rela_task_ical WHERE ical_uid = ? AND occurrence = ?")
+            .bind(ical_uid)
+            .bind(occurrence)
+            .fetch_optional(pool)
+            .await?;
+    match row {
+        Some(row) => {
+            let task_id: String = row.try_get("task_id")?;
+            Ok(Uuid::parse_str(&task_id).ok())
+        }
+        None => Ok(None),
+    }
+}
+
+pub async fn save_rela_task_ical(
+    pool: &SqlitePool,
+    ical_uid: &str,
+    occurrence: &str,
+    task_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    init_rela_task_ical_table(pool).await?;
+
+    let now = now_for_db();
+    sqlx::query(
+        "INSERT INTO rela_task_ical (ical_uid, occurrence, task_id, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(ical_uid, occurrence) DO UPDATE SET task_id = excluded.task_id, updated_at = excluded.updated_at",
+    )
+    .bind(ical_uid)
+    .bind(occurrence)
+    .bind(task_id.to_string())
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
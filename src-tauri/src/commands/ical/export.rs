@@ -0,0 +1,231 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::commands::calendar::event::Event;
+use crate::commands::calendar::memo::CalendarMemo;
+use crate::commands::ical::format::{escape_text, format_date, format_utc, IcsWriter, IcsZone};
+use crate::commands::ical::timezone::write_vtimezone;
+use crate::commands::task::task::Task;
+
+pub const UID_DOMAIN: &str = "norunos";
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct IcsExportOptions {
+    pub include_tasks: bool,
+    pub include_completed: bool,
+    pub include_events: bool,
+    pub include_memos: bool,
+    pub group_id: Option<Uuid>,
+}
+
+impl Default for IcsExportOptions {
+    fn default() -> Self {
+        IcsExportOptions {
+            include_tasks: true,
+            include_completed: true,
+            include_events: true,
+            include_memos: true,
+            group_id: None,
+        }
+    }
+}
+
+pub fn task_uid(id: Uuid) -> String {
+    format!("{}@{}", id, UID_DOMAIN)
+}
+
+pub fn event_uid(id: Uuid) -> String {
+    format!("event-{}@{}", id, UID_DOMAIN)
+}
+
+// Norunos の優先度 (3: 高 .. 1: 低) を iCalendar の PRIORITY (1: 高 .. 9: 低) に変換
+fn ical_priority(priority: u32) -> Option<u32> {
+    match priority {
+        0 => None,
+        1 => Some(9),
+        2 => Some(5),
+        _ => Some(1),
+    }
+}
+
+// 期間付きのタスク (ガントチャートのバー) は VEVENT、開始日か期日だけのタスクは VTODO にする
+// 開始と終了が同じタスクはマイルストーンとして DTEND なしの VEVENT にする
+pub fn build_calendar(
+    tasks: &[Task],
+    events: &[Event],
+    memos: &[CalendarMemo],
+    tz: Option<Tz>,
+    now: DateTime<Utc>,
+) -> String {
+    let zone = tz.map(IcsZone::Named).unwrap_or(IcsZone::Utc);
+    let dtstamp = format_utc(now);
+    let mut writer = IcsWriter::new();
+
+    writer.line("BEGIN:VCALENDAR");
+    writer.line("VERSION:2.0");
+    writer.line("PRODID:-//Norunos//Norunos Platform//JA");
+    writer.line("CALSCALE:GREGORIAN");
+    writer.line("X-WR-CALNAME:Norunos");
+
+    let dated: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.start_datetime.is_some() || t.end_datetime.is_some())
+        .collect();
+
+    if let Some(tz) = tz {
+        let dates: Vec<NaiveDate> = dated
+            .iter()
+            .flat_map(|t| [t.start_datetime, t.end_datetime])
+            .flatten()
+            .chain(
+                events
+                    .iter()
+                    .filter(|e| !e.all_day)
+                    .flat_map(|e| [e.start_datetime, e.end_datetime]),
+            )
+            .map(|dt| dt.with_timezone(&tz).date_naive())
+            .collect();
+        if let (Some(from), Some(to)) = (dates.iter().min(), dates.iter().max()) {
+            write_vtimezone(
+                &mut writer,
+                tz,
+                *from - Duration::days(1),
+                *to + Duration::days(1),
+            );
+        }
+    }
+
+    for task in dated {
+        write_task(&mut writer, task, zone, &dtstamp);
+    }
+
+    for event in events {
+        write_event(&mut writer, event, zone, &dtstamp);
+    }
+
+    for memo in memos {
+        write_memo(&mut writer, memo, &dtstamp);
+    }
+
+    writer.line("END:VCALENDAR");
+    writer.finish()
+}
+
+fn write_task(writer: &mut IcsWriter, task: &Task, zone: IcsZone, dtstamp: &str) {
+    let is_event = task.start_datetime.is_some() && task.end_datetime.is_some();
+    let component = if is_event { "VEVENT" } else { "VTODO" };
+
+    writer.line(format!("BEGIN:{}", component));
+    writer.text("UID", &task_uid(task.id));
+    writer.line(format!("DTSTAMP:{}", dtstamp));
+    writer.text("SUMMARY", &task.description);
+    if let Some(details) = task.details.as_deref().filter(|d| !d.is_empty()) {
+        writer.text("DESCRIPTION", details);
+    }
+
+    if is_event {
+        let (start, end) = (task.start_datetime.unwrap(), task.end_datetime.unwrap());
+        writer.line(zone.datetime_property("DTSTART", start));
+        if end > start {
+            writer.line(zone.datetime_property("DTEND", end));
+        }
+    } else {
+        if let Some(start) = task.start_datetime {
+            writer.line(zone.datetime_property("DTSTART", start));
+        }
+        if let Some(due) = task.end_datetime {
+            writer.line(zone.datetime_property("DUE", due));
+        }
+        writer.line(if task.completed {
+            "STATUS:COMPLETED"
+        } else {
+            "STATUS:NEEDS-ACTION"
+        });
+        writer.line(format!("PERCENT-COMPLETE:{}", task.progress.min(100)));
+        if let Some(completed_at) = task.completed_at {
+            writer.line(format!(
+                "COMPLETED:{}",
+                format_utc(completed_at.with_timezone(&Utc))
+            ));
+        }
+    }
+
+    if let Some(priority) = ical_priority(task.priority) {
+        writer.line(format!("PRIORITY:{}", priority));
+    }
+    if !task.tags.is_empty() {
+        let categories: Vec<String> = task.tags.iter().map(|t| escape_text(t)).collect();
+        writer.line(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    writer.line(format!(
+        "CREATED:{}",
+        format_utc(task.created_at.with_timezone(&Utc))
+    ));
+    if let Some(updated_at) = task.updated_at {
+        writer.line(format!(
+            "LAST-MODIFIED:{}",
+            format_utc(updated_at.with_timezone(&Utc))
+        ));
+    }
+    writer.line(format!("END:{}", component));
+}
+
+// 終日の予定は DATE 値にし、DTEND は最終日の翌日 (排他的) にする
+fn write_event(writer: &mut IcsWriter, event: &Event, zone: IcsZone, dtstamp: &str) {
+    writer.line("BEGIN:VEVENT");
+    writer.text("UID", &event_uid(event.id));
+    writer.line(format!("DTSTAMP:{}", dtstamp));
+    writer.text("SUMMARY", &event.title);
+    if let Some(location) = event.location.as_deref().filter(|l| !l.is_empty()) {
+        writer.text("LOCATION", location);
+    }
+    if let Some(notes) = event.notes.as_deref().filter(|n| !n.is_empty()) {
+        writer.text("DESCRIPTION", notes);
+    }
+    if event.all_day {
+        let start = event.start_datetime.date_naive();
+        let end = event.end_datetime.date_naive().max(start) + Duration::days(1);
+        writer.line(format!("DTSTART;VALUE=DATE:{}", format_date(start)));
+        writer.line(format!("DTEND;VALUE=DATE:{}", format_date(end)));
+    } else {
+        writer.line(zone.datetime_property("DTSTART", event.start_datetime));
+        if event.end_datetime > event.start_datetime {
+            writer.line(zone.datetime_property("DTEND", event.end_datetime));
+        }
+    }
+    writer.line(format!(
+        "CREATED:{}",
+        format_utc(event.created_at.with_timezone(&Utc))
+    ));
+    if let Some(updated_at) = event.updated_at {
+        writer.line(format!(
+            "LAST-MODIFIED:{}",
+            format_utc(updated_at.with_timezone(&Utc))
+        ));
+    }
+    writer.line("END:VEVENT");
+}
+
+// 日別メモは終日の VJOURNAL にする
+fn write_memo(writer: &mut IcsWriter, memo: &CalendarMemo, dtstamp: &str) {
+    let Ok(date) = NaiveDate::parse_from_str(&memo.date, "%Y-%m-%d") else {
+        return;
+    };
+    let summary = memo
+        .content
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("Daily memo");
+
+    writer.line("BEGIN:VJOURNAL");
+    writer.text("UID", &format!("memo-{}@{}", memo.date, UID_DOMAIN));
+    writer.line(format!("DTSTAMP:{}", dtstamp));
+    writer.line(format!("DTSTART;VALUE=DATE:{}", format_date(date)));
+    writer.text("SUMMARY", summary);
+    writer.text("DESCRIPTION", &memo.content);
+    writer.line("END:VJOURNAL");
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+// RFC 5545 の TEXT 値のエスケープ
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 1行を75オクテット以内に折り返す (UTF-8 の文字の途中では切らない)
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 72 * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            folded.push_str("\r\n ");
+            // 継続行の先頭の空白も75オクテットに含まれる
+            width = 1;
+        }
+        folded.push(c);
+        width += len;
+    }
+    folded
+}
+
+pub fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+pub fn format_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    let (hours, minutes, rest) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if rest == 0 {
+        format!("{}{:02}{:02}", sign, hours, minutes)
+    } else {
+        format!("{}{:02}{:02}{:02}", sign, hours, minutes, rest)
+    }
+}
+
+// 日時プロパティの書き出し方 (TZID 付きのローカル時刻か UTC か)
+#[derive(Clone, Copy)]
+pub enum IcsZone {
+    Named(Tz),
+    Utc,
+}
+
+impl IcsZone {
+    pub fn datetime_property(&self, name: &str, dt: DateTime<Local>) -> String {
+        match self {
+            IcsZone::Named(tz) => format!(
+                "{};TZID={}:{}",
+                name,
+                tz.name(),
+                dt.with_timezone(tz).format("%Y%m%dT%H%M%S")
+            ),
+            IcsZone::Utc => format!("{}:{}", name, format_utc(dt.with_timezone(&Utc))),
+        }
+    }
+}
+
+// 折り返し前の論理行を溜めて、最後に CRLF 区切りで書き出す
+pub struct IcsWriter {
+    lines: Vec<String>,
+}
+
+impl IcsWriter {
+    pub fn new() -> Self {
+        IcsWriter { lines: Vec::new() }
+    }
+
+    pub fn line(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.lines.push(format!("{}:{}", name, escape_text(value)));
+    }
+
+    pub fn finish(self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            out.push_str(&fold_line(line));
+            out.push_str("\r\n");
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text("a,b;c\\d\r\ne"),
+            "a\\,b\\;c\\\\d\\ne".to_string()
+        );
+    }
+
+    #[test]
+    fn test_fold_line_respects_octets_and_chars() {
+        let line = format!("DESCRIPTION:{}", "あ".repeat(40));
+        let folded = fold_line(&line);
+        for part in folded.split("\r\n") {
+            assert!(part.len() <= 75);
+        }
+        let unfolded = folded.replace("\r\n ", "");
+        assert_eq!(unfolded, line);
+    }
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!(
+            format_offset(FixedOffset::east_opt(9 * 3600).unwrap()),
+            "+0900"
+        );
+        assert_eq!(
+            format_offset(FixedOffset::west_opt(3 * 3600 + 1800).unwrap()),
+            "-0330"
+        );
+    }
+}
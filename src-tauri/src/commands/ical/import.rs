@@ -0,0 +1,519 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::commands::calendar::memo::{load_memos, save_memo_content};
+use crate::commands::ical::db::{get_task_id_from_ical_uid, save_rela_task_ical};
+use crate::commands::ical::parse::{split_list, IcsComponent};
+use crate::commands::ical::recurrence::{expand, parse_rrule};
+use crate::commands::ical::timezone::TzResolver;
+use crate::commands::ical::value::{
+    parse_duration, parse_property_date_time, parse_property_date_times, IcsDateTime,
+};
+use crate::commands::task::sql::task::{load_by_id, save_task};
+use crate::commands::task::sql::task_task_group::save_rela_task_task_group;
+use crate::commands::task::task::Task;
+
+// 繰り返し予定は前後1年分だけ展開する
+const RECURRENCE_PAST_DAYS: i64 = 365;
+const RECURRENCE_FUTURE_DAYS: i64 = 365;
+const MAX_OCCURRENCES: usize = 1000;
+
+#[derive(Debug, Serialize, Default)]
+pub struct IcsImportReport {
+    pub created: u32,
+    pub updated: u32,
+    pub skipped: u32,
+    pub memos: u32,
+    pub warnings: Vec<String>,
+}
+
+// VEVENT/VTODO (の1回分) から読み取ったタスクの内容
+#[derive(Debug)]
+pub struct ImportedTask {
+    pub uid: String,
+    pub occurrence: String,
+    pub description: String,
+    pub details: Option<String>,
+    pub start_datetime: Option<DateTime<Local>>,
+    pub end_datetime: Option<DateTime<Local>>,
+    pub completed: bool,
+    pub completed_at: Option<DateTime<Local>>,
+    pub progress: u32,
+    pub priority: u32,
+    pub tags: Vec<String>,
+}
+
+impl ImportedTask {
+    fn apply_to(&self, task: &mut Task) {
+        task.description = self.description.clone();
+        task.details = self.details.clone();
+        task.start_datetime = self.start_datetime;
+        task.end_datetime = self.end_datetime;
+        task.completed = self.completed;
+        task.completed_at = self.completed_at;
+        task.progress = self.progress;
+        task.priority = self.priority;
+        task.tags = self.tags.clone();
+    }
+}
+
+#[derive(Debug)]
+pub struct ImportedMemo {
+    pub date: String,
+    pub content: String,
+}
+
+// iCalendar の PRIORITY (1: 高 .. 9: 低, 0: 未定義) を Norunos の優先度に変換
+fn norunos_priority(priority: u32) -> u32 {
+    match priority {
+        0 => 0,
+        1..=4 => 3,
+        5 => 2,
+        _ => 1,
+    }
+}
+
+// 繰り返しの各回を区別するキー (RECURRENCE-ID と突き合わせられるよう UTC に揃える)
+fn occurrence_key(resolver: &TzResolver, value: &IcsDateTime) -> String {
+    match value {
+        IcsDateTime::Date(date) => date.format("%Y%m%d").to_string(),
+        _ => resolver
+            .resolve(value)
+            .with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string(),
+    }
+}
+
+struct Collector<'a> {
+    resolver: TzResolver,
+    report: &'a mut IcsImportReport,
+    unknown_tzids: HashSet<String>,
+}
+
+impl Collector<'_> {
+    fn resolve(&mut self, value: &IcsDateTime) -> DateTime<Local> {
+        if let IcsDateTime::Local {
+            tzid: Some(tzid), ..
+        } = value
+        {
+            if !self.resolver.is_known(tzid) && self.unknown_tzids.insert(tzid.clone()) {
+                self.report.warnings.push(format!(
+                    "Unknown TZID \"{}\"; times were treated as local time",
+                    tzid
+                ));
+            }
+        }
+        self.resolver.resolve(value)
+    }
+
+    // 開始・終了を Task の start_datetime / end_datetime に変換する
+    // 終日の予定は開始日の 0:00 から最終日の 23:59:59 までにする
+    fn span(
+        &mut self,
+        component: &IcsComponent,
+        start: Option<&IcsDateTime>,
+    ) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        let is_event = component.name == "VEVENT";
+        let start_dt = start.map(|s| self.resolve(s));
+        let all_day = start.is_some_and(|s| s.is_date());
+        let end_prop = if is_event { "DTEND" } else { "DUE" };
+        let one_second = Duration::seconds(1);
+
+        let end = if let Some(end) = component
+            .property(end_prop)
+            .and_then(parse_property_date_time)
+        {
+            let resolved = self.resolve(&end);
+            if !end.is_date() {
+                Some(resolved)
+            } else if is_event {
+                Some(resolved - one_second)
+            } else {
+                Some(resolved + Duration::days(1) - one_second)
+            }
+        } else if let (Some(start_dt), Some(duration)) = (
+            start_dt,
+            component
+                .property("DURATION")
+                .and_then(|p| parse_duration(&p.value)),
+        ) {
+            if all_day && duration > Duration::zero() {
+                Some(start_dt + duration - one_second)
+            } else {
+                Some(start_dt + duration)
+            }
+        } else if is_event {
+            start_dt.map(|s| {
+                if all_day {
+                    s + Duration::days(1) - one_second
+                } else {
+                    s
+                }
+            })
+        } else {
+            None
+        };
+
+        let end = match (start_dt, end) {
+            (Some(s), Some(e)) if e < s => Some(s),
+            (_, e) => e,
+        };
+        (start_dt, end)
+    }
+
+    fn item(
+        &mut self,
+        component: &IcsComponent,
+        uid: &str,
+        occurrence: String,
+        span: (Option<DateTime<Local>>, Option<DateTime<Local>>),
+    ) -> ImportedTask {
+        let status = component
+            .property("STATUS")
+            .map(|p| p.value.trim().to_ascii_uppercase());
+        let completed_at = component
+            .property("COMPLETED")
+            .and_then(parse_property_date_time)
+            .map(|c| self.resolve(&c));
+        let completed = status.as_deref() == Some("COMPLETED") || completed_at.is_some();
+        let progress = component
+            .property("PERCENT-COMPLETE")
+            .and_then(|p| p.value.trim().parse::<u32>().ok())
+            .unwrap_or(if completed { 100 } else { 0 })
+            .min(100);
+        let priority = component
+            .property("PRIORITY")
+            .and_then(|p| p.value.trim().parse::<u32>().ok())
+            .map(norunos_priority)
+            .unwrap_or(0);
+        let tags = component
+            .properties_named("CATEGORIES")
+            .flat_map(|p| split_list(&p.value))
+            .collect();
+
+        ImportedTask {
+            uid: uid.to_string(),
+            occurrence,
+            description: component
+                .text("SUMMARY")
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "No description.".to_string()),
+            details: component.text("DESCRIPTION").filter(|d| !d.is_empty()),
+            start_datetime: span.0,
+            end_datetime: span.1,
+            completed,
+            completed_at,
+            progress,
+            priority,
+            tags,
+        }
+    }
+}
+
+fn component_uid(component: &IcsComponent) -> String {
+    component
+        .text("UID")
+        .filter(|u| !u.trim().is_empty())
+        // UID のないファイルでも再取り込みで重複しないよう、内容からキーを作る
+        .unwrap_or_else(|| {
+            format!(
+                "{}|{}",
+                component.text("SUMMARY").unwrap_or_default(),
+                component
+                    .property("DTSTART")
+                    .map(|p| p.value.clone())
+                    .unwrap_or_default()
+            )
+        })
+}
+
+pub fn collect_items(
+    calendars: &[IcsComponent],
+    now: DateTime<Local>,
+    report: &mut IcsImportReport,
+) -> (Vec<ImportedTask>, Vec<ImportedMemo>) {
+    let mut tasks = Vec::new();
+    let mut memos = Vec::new();
+    let window_start = (now - Duration::days(RECURRENCE_PAST_DAYS)).naive_local();
+    let window_end = (now + Duration::days(RECURRENCE_FUTURE_DAYS)).naive_local();
+
+    for calendar in calendars.iter().filter(|c| c.name == "VCALENDAR") {
+        let mut collector = Collector {
+            resolver: TzResolver::from_calendar(calendar),
+            report: &mut *report,
+            unknown_tzids: HashSet::new(),
+        };
+
+        // RECURRENCE-ID 付きの要素は繰り返しの特定の回を置き換える
+        let overrides: HashSet<(String, String)> = calendar
+            .children
+            .iter()
+            .filter_map(|c| {
+                let recurrence_id = c
+                    .property("RECURRENCE-ID")
+                    .and_then(parse_property_date_time)?;
+                Some((
+                    component_uid(c),
+                    occurrence_key(&collector.resolver, &recurrence_id),
+                ))
+            })
+            .collect();
+
+        for component in &calendar.children {
+            match component.name.as_str() {
+                "VEVENT" | "VTODO" => {}
+                "VJOURNAL" => {
+                    let date = component
+                        .property("DTSTART")
+                        .and_then(parse_property_date_time)
+                        .map(|d| collector.resolve(&d).format("%Y-%m-%d").to_string());
+                    let content = component
+                        .text("DESCRIPTION")
+                        .or_else(|| component.text("SUMMARY"))
+                        .filter(|c| !c.trim().is_empty());
+                    match (date, content) {
+                        (Some(date), Some(content)) => memos.push(ImportedMemo { date, content }),
+                        _ => collector.report.skipped += 1,
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let uid = component_uid(component);
+            let cancelled = component
+                .property("STATUS")
+                .is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED"));
+            if cancelled {
+                collector.report.skipped += 1;
+                continue;
+            }
+
+            let start = component
+                .property("DTSTART")
+                .and_then(parse_property_date_time);
+
+            if let Some(recurrence_id) = component
+                .property("RECURRENCE-ID")
+                .and_then(parse_property_date_time)
+            {
+                let key = occurrence_key(&collector.resolver, &recurrence_id);
+                let span = collector.span(component, start.as_ref());
+                tasks.push(collector.item(component, &uid, key, span));
+                continue;
+            }
+
+            let rule = match (component.property("RRULE"), &start) {
+                (Some(rrule), Some(_)) => match parse_rrule(&rrule.value) {
+                    Ok(rule) => {
+                        if !rule.unsupported.is_empty() {
+                            collector.report.warnings.push(format!(
+                                "{}: ignored unsupported RRULE parts {}",
+                                uid,
+                                rule.unsupported.join(";")
+                            ));
+                        }
+                        Some(rule)
+                    }
+                    Err(e) => {
+                        collector.report.warnings.push(format!(
+                            "{}: {}; imported only the first occurrence",
+                            uid, e
+                        ));
+                        None
+                    }
+                },
+                _ => None,
+            };
+
+            let (Some(rule), Some(start)) = (rule, start.clone()) else {
+                let span = collector.span(component, start.as_ref());
+                tasks.push(collector.item(component, &uid, String::new(), span));
+                continue;
+            };
+
+            let (first_start, first_end) = collector.span(component, Some(&start));
+            let length = match (first_start, first_end) {
+                (Some(s), Some(e)) => Some(e - s),
+                _ => None,
+            };
+            let until = rule.until.as_ref().map(|u| collector.resolve(u));
+            let exdates: Vec<IcsDateTime> = component
+                .properties_named("EXDATE")
+                .flat_map(parse_property_date_times)
+                .collect();
+
+            let occurrences = expand(
+                &rule,
+                start.naive(),
+                // 正確な比較は解決後に行うので、ここでは1日余裕を持たせる
+                until.map(|u| u.naive_local() + Duration::days(1)),
+                window_start,
+                window_end,
+                MAX_OCCURRENCES,
+            );
+            for naive in occurrences {
+                let value = start.with_naive(naive);
+                let occurrence_start = collector.resolve(&value);
+                if until.is_some_and(|u| occurrence_start > u) {
+                    continue;
+                }
+                let excluded = exdates.iter().any(|ex| match ex {
+                    IcsDateTime::Date(date) => *date == naive.date(),
+                    _ => collector.resolver.resolve(ex) == occurrence_start,
+                });
+                let key = occurrence_key(&collector.resolver, &value);
+                if excluded || overrides.contains(&(uid.clone(), key.clone())) {
+                    continue;
+                }
+                let span = (Some(occurrence_start), length.map(|l| occurrence_start + l));
+                tasks.push(collector.item(component, &uid, key, span));
+            }
+        }
+    }
+
+    (tasks, memos)
+}
+
+pub async fn apply_import(
+    pool: &SqlitePool,
+    group_id: Uuid,
+    tasks: Vec<ImportedTask>,
+    memos: Vec<ImportedMemo>,
+    report: &mut IcsImportReport,
+) -> Result<(), sqlx::Error> {
+    for item in tasks {
+        let existing = match get_task_id_from_ical_uid(pool, &item.uid, &item.occurrence).await? {
+            Some(task_id) => load_by_id(pool, task_id).await?,
+            None => None,
+        };
+
+        match existing {
+            // アプリ側で削除したタスクは再取り込みで復活させない
+            Some(task) if task.deleted_at.is_some() => report.skipped += 1,
+            Some(mut task) => {
+                item.apply_to(&mut task);
+                save_task(pool, &mut task).await?;
+                report.updated += 1;
+            }
+            None => {
+                let mut task = Task::new();
+                item.apply_to(&mut task);
+                save_task(pool, &mut task).await?;
+                save_rela_task_task_group(pool, group_id.to_string(), task.id.to_string()).await?;
+                save_rela_task_ical(pool, &item.uid, &item.occurrence, task.id).await?;
+                report.created += 1;
+            }
+        }
+    }
+
+    // 既存の日別メモは上書きしない
+    for memo in memos {
+        if !load_memos(pool, &memo.date, &memo.date).await?.is_empty() {
+            report.skipped += 1;
+            report
+                .warnings
+                .push(format!("A memo for {} already exists; skipped", memo.date));
+            continue;
+        }
+        save_memo_content(pool, &memo.date, &memo.content).await?;
+        report.memos += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ical::parse::parse;
+    use chrono::TimeZone;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VTIMEZONE\r
+TZID:Tokyo Standard Time\r
+BEGIN:STANDARD\r
+DTSTART:16010101T000000\r
+TZOFFSETFROM:+0900\r
+TZOFFSETTO:+0900\r
+END:STANDARD\r
+END:VTIMEZONE\r
+BEGIN:VEVENT\r
+UID:weekly-1\r
+SUMMARY:Weekly sync\\, team\r
+DTSTART;TZID=Tokyo Standard Time:20260105T100000\r
+DTEND;TZID=Tokyo Standard Time:20260105T110000\r
+RRULE:FREQ=WEEKLY;COUNT=4\r
+EXDATE;TZID=Tokyo Standard Time:20260112T100000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:weekly-1\r
+RECURRENCE-ID;TZID=Tokyo Standard Time:20260119T100000\r
+SUMMARY:Moved sync\r
+DTSTART;TZID=Tokyo Standard Time:20260120T100000\r
+DTEND;TZID=Tokyo Standard Time:20260120T110000\r
+END:VEVENT\r
+BEGIN:VTODO\r
+UID:todo-1\r
+SUMMARY:Write a long description that is folded across\r
+  two lines\r
+DUE;VALUE=DATE:20260110\r
+STATUS:COMPLETED\r
+PRIORITY:1\r
+CATEGORIES:work,urgent\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn test_collect_items_expands_recurrences() {
+        let calendars = parse(SAMPLE).expect("Failed to parse");
+        let now = Local.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let mut report = IcsImportReport::default();
+        let (tasks, memos) = collect_items(&calendars, now, &mut report);
+
+        assert!(memos.is_empty());
+        assert!(report.warnings.is_empty());
+
+        let weekly: Vec<&ImportedTask> = tasks.iter().filter(|t| t.uid == "weekly-1").collect();
+        // 4回のうち1回は EXDATE で除外、1回は RECURRENCE-ID で置き換え
+        assert_eq!(weekly.len(), 3);
+        assert_eq!(
+            weekly
+                .iter()
+                .filter(|t| t.description == "Weekly sync, team")
+                .count(),
+            2
+        );
+        let moved = weekly
+            .iter()
+            .find(|t| t.description == "Moved sync")
+            .unwrap();
+        assert_eq!(moved.occurrence, "20260119T010000Z");
+        assert_eq!(
+            moved.start_datetime.unwrap().with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2026, 1, 20, 1, 0, 0).unwrap()
+        );
+
+        let todo = tasks.iter().find(|t| t.uid == "todo-1").unwrap();
+        assert_eq!(
+            todo.description,
+            "Write a long description that is folded across two lines"
+        );
+        assert!(todo.completed);
+        assert_eq!(todo.priority, 3);
+        assert_eq!(todo.tags, vec!["work".to_string(), "urgent".to_string()]);
+        assert_eq!(
+            todo.end_datetime
+                .unwrap()
+                .date_naive()
+                .format("%Y-%m-%d")
+                .to_string(),
+            "2026-01-10"
+        );
+    }
+}
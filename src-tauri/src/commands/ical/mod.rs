@@ -0,0 +1,9 @@
+pub mod commands;
+pub mod db;
+pub mod export;
+pub mod format;
+pub mod import;
+pub mod parse;
+pub mod recurrence;
+pub mod timezone;
+pub mod value;
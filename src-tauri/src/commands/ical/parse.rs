@@ -0,0 +1,206 @@
+// iCalendar (RFC 5545) のコンテンツ行をコンポーネントの木に変換する
+
+#[derive(Debug, Clone)]
+pub struct IcsProperty {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl IcsProperty {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IcsComponent {
+    pub name: String,
+    pub properties: Vec<IcsProperty>,
+    pub children: Vec<IcsComponent>,
+}
+
+impl IcsComponent {
+    pub fn property(&self, name: &str) -> Option<&IcsProperty> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    pub fn properties_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a IcsProperty> + 'a {
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    pub fn text(&self, name: &str) -> Option<String> {
+        self.property(name).map(|p| unescape_text(&p.value))
+    }
+
+    pub fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a IcsComponent> + 'a {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+}
+
+// 折り返された行 (CRLF/LF の直後が空白かタブ) を1行に戻す
+pub fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in input.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(rest) = raw.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !raw.is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
+
+pub fn parse_line(line: &str) -> Option<IcsProperty> {
+    // 引用符の外にある最初の ':' で名前・パラメータと値に分ける
+    let mut in_quotes = false;
+    let mut split_at = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                split_at = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let split_at = split_at?;
+    let (head, value) = (&line[..split_at], &line[split_at + 1..]);
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    in_quotes = false;
+    for c in head.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ';' if !in_quotes => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((
+                key.trim().to_ascii_uppercase(),
+                value.trim().trim_matches('"').to_string(),
+            ))
+        })
+        .collect();
+
+    Some(IcsProperty {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+pub fn parse(input: &str) -> Result<Vec<IcsComponent>, String> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<IcsComponent> = Vec::new();
+
+    for line in unfold(input) {
+        let Some(property) = parse_line(&line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => stack.push(IcsComponent {
+                name: property.value.trim().to_ascii_uppercase(),
+                ..IcsComponent::default()
+            }),
+            "END" => {
+                let name = property.value.trim().to_ascii_uppercase();
+                let component = stack
+                    .pop()
+                    .ok_or_else(|| format!("Unexpected END:{}", name))?;
+                if component.name != name {
+                    return Err(format!(
+                        "Mismatched END:{} for BEGIN:{}",
+                        name, component.name
+                    ));
+                }
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => {
+                if let Some(current) = stack.last_mut() {
+                    current.properties.push(property);
+                }
+            }
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("Missing END:{}", open.name));
+    }
+    Ok(roots)
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+// TEXT のリスト値 (CATEGORIES など) をエスケープされていない ',' で分ける
+pub fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            items.push(unescape_text(&std::mem::take(&mut current)));
+        } else {
+            current.push(c);
+        }
+    }
+    items.push(unescape_text(&current));
+    items
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
@@ -0,0 +1,363 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+
+use crate::commands::ical::value::{parse_date_time_value, IcsDateTime};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// RRULE のうち取り込みで扱える部分 (扱えない部分は unsupported に残して警告する)
+#[derive(Debug, Clone)]
+pub struct Rrule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<IcsDateTime>,
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub by_month: Vec<u32>,
+    pub by_month_day: Vec<i32>,
+    pub unsupported: Vec<String>,
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// "MO" / "2TU" / "-1FR" (第 n 曜日は -53 から 53 まで)
+fn parse_by_day(day: &str) -> Option<(Option<i32>, Weekday)> {
+    // 曜日は末尾の2文字。マルチバイト文字の途中では分けない
+    let split = day.len().checked_sub(2)?;
+    let (nth, name) = (day.get(..split)?, day.get(split..)?);
+    let weekday = parse_weekday(name)?;
+    if nth.is_empty() {
+        return Some((None, weekday));
+    }
+    let nth = nth.strip_prefix('+').unwrap_or(nth).parse::<i32>().ok()?;
+    (nth != 0 && (-53..=53).contains(&nth)).then_some((Some(nth), weekday))
+}
+
+pub fn parse_rrule(value: &str) -> Result<Rrule, String> {
+    let mut freq = None;
+    let mut rule = Rrule {
+        freq: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month: Vec::new(),
+        by_month_day: Vec::new(),
+        unsupported: Vec::new(),
+    };
+
+    for part in value.split(';').filter(|p| !p.is_empty()) {
+        let (key, val) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid RRULE part: {}", part))?;
+        let key = key.trim().to_ascii_uppercase();
+        let val = val.trim().to_ascii_uppercase();
+        match key.as_str() {
+            "FREQ" => {
+                freq = Some(match val.as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    other => return Err(format!("Unsupported RRULE frequency: {}", other)),
+                })
+            }
+            "INTERVAL" => rule.interval = val.parse().unwrap_or(1).max(1),
+            "COUNT" => rule.count = val.parse().ok(),
+            "UNTIL" => rule.until = parse_date_time_value(&val, None, false),
+            "BYDAY" => {
+                for day in val.split(',') {
+                    rule.by_day
+                        .push(parse_by_day(day).ok_or_else(|| format!("Invalid BYDAY: {}", day))?);
+                }
+            }
+            // 範囲外の値は無視する
+            "BYMONTH" => {
+                rule.by_month = val
+                    .split(',')
+                    .filter_map(|m| m.parse().ok())
+                    .filter(|m| (1..=12).contains(m))
+                    .collect()
+            }
+            "BYMONTHDAY" => {
+                rule.by_month_day = val
+                    .split(',')
+                    .filter_map(|d| d.parse().ok())
+                    .filter(|d: &i32| *d != 0 && (-31..=31).contains(d))
+                    .collect()
+            }
+            "WKST" => {}
+            _ => rule.unsupported.push(part.to_string()),
+        }
+    }
+
+    rule.freq = freq.ok_or_else(|| "RRULE without FREQ".to_string())?;
+    Ok(rule)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        year.checked_add(1)
+            .and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1))
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    next.and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+// その月の第 n 曜日 (n が負なら月末から数える)
+pub fn nth_weekday_of_month(
+    year: i32,
+    month: u32,
+    weekday: Weekday,
+    nth: i32,
+) -> Option<NaiveDate> {
+    if nth > 0 {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let offset = (7 + weekday.num_days_from_monday() as i64
+            - first.weekday().num_days_from_monday() as i64)
+            % 7;
+        let date = first.checked_add_signed(Duration::try_days(offset + 7 * (nth as i64 - 1))?)?;
+        (date.month() == month).then_some(date)
+    } else if nth < 0 {
+        let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month))?;
+        let offset = (7 + last.weekday().num_days_from_monday() as i64
+            - weekday.num_days_from_monday() as i64)
+            % 7;
+        let date =
+            last.checked_sub_signed(Duration::try_days(offset + 7 * (-(nth as i64) - 1))?)?;
+        (date.month() == month).then_some(date)
+    } else {
+        None
+    }
+}
+
+fn dates_in_month(rule: &Rrule, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+    let last_day = days_in_month(year, month) as i32;
+    if !rule.by_month_day.is_empty() {
+        return rule
+            .by_month_day
+            .iter()
+            .filter_map(|&d| {
+                let day = if d < 0 { last_day + d + 1 } else { d };
+                NaiveDate::from_ymd_opt(year, month, u32::try_from(day).ok()?)
+            })
+            .filter(|date| {
+                rule.by_day.is_empty() || rule.by_day.iter().any(|(_, wd)| *wd == date.weekday())
+            })
+            .collect();
+    }
+    if !rule.by_day.is_empty() {
+        let mut dates = Vec::new();
+        for &(nth, weekday) in &rule.by_day {
+            match nth {
+                Some(n) => dates.extend(nth_weekday_of_month(year, month, weekday, n)),
+                None => dates.extend((1..=last_day as u32).filter_map(|d| {
+                    NaiveDate::from_ymd_opt(year, month, d).filter(|date| date.weekday() == weekday)
+                })),
+            }
+        }
+        return dates;
+    }
+    NaiveDate::from_ymd_opt(year, month, start.day())
+        .into_iter()
+        .collect()
+}
+
+fn add_months(date: NaiveDate, months: u32) -> Option<(i32, u32)> {
+    let index = date
+        .year()
+        .checked_mul(12)?
+        .checked_add(date.month0() as i32)?
+        .checked_add(i32::try_from(months).ok()?)?;
+    Some((index.div_euclid(12), index.rem_euclid(12) as u32 + 1))
+}
+
+// DTSTART からの発生日時のうち [window_start, window_end] に入るものを列挙する
+// COUNT は期間外の発生も数える。UNTIL と max 件数でも打ち切る
+// 取り込んだ .ics の値で日付が扱える範囲を超えたら、そこで打ち切る
+pub fn expand(
+    rule: &Rrule,
+    start: NaiveDateTime,
+    until: Option<NaiveDateTime>,
+    window_start: NaiveDateTime,
+    window_end: NaiveDateTime,
+    max: usize,
+) -> Vec<NaiveDateTime> {
+    const MAX_PERIODS: u32 = 100_000;
+    let start_date = start.date();
+    let limit = match until {
+        Some(until) => until.min(window_end),
+        None => window_end,
+    };
+    let mut occurrences = Vec::new();
+    let mut seen = 0;
+
+    for period in 0..MAX_PERIODS {
+        let Some(step) = period.checked_mul(rule.interval) else {
+            break;
+        };
+        let Some((anchor, mut dates)) = occurrences_in_period(rule, start_date, step) else {
+            break;
+        };
+
+        if anchor.and_time(start.time()) > limit && anchor > start_date {
+            break;
+        }
+
+        dates.sort();
+        dates.dedup();
+        for date in dates {
+            let occurrence = date.and_time(start.time());
+            if occurrence < start {
+                continue;
+            }
+            if occurrence > limit {
+                return occurrences;
+            }
+            seen += 1;
+            if occurrence >= window_start {
+                occurrences.push(occurrence);
+            }
+            if rule.count.is_some_and(|c| seen >= c) || occurrences.len() >= max {
+                return occurrences;
+            }
+        }
+    }
+    occurrences
+}
+
+// step 番目の期間 (日・週・月・年) の先頭と、その中の発生日
+// 日付が扱える範囲を超えたら None
+fn occurrences_in_period(
+    rule: &Rrule,
+    start_date: NaiveDate,
+    step: u32,
+) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+    Some(match rule.freq {
+        Frequency::Daily => {
+            let date = start_date.checked_add_signed(Duration::try_days(step as i64)?)?;
+            let matches = (rule.by_month.is_empty() || rule.by_month.contains(&date.month()))
+                && (rule.by_day.is_empty()
+                    || rule.by_day.iter().any(|(_, wd)| *wd == date.weekday()))
+                && (rule.by_month_day.is_empty()
+                    || rule.by_month_day.contains(&(date.day() as i32)));
+            (date, if matches { vec![date] } else { Vec::new() })
+        }
+        Frequency::Weekly => {
+            let week_start = start_date
+                .checked_sub_signed(Duration::days(
+                    start_date.weekday().num_days_from_monday() as i64
+                ))?
+                .checked_add_signed(Duration::try_weeks(step as i64)?)?;
+            let weekdays: Vec<Weekday> = if rule.by_day.is_empty() {
+                vec![start_date.weekday()]
+            } else {
+                rule.by_day.iter().map(|(_, wd)| *wd).collect()
+            };
+            let dates = weekdays
+                .iter()
+                .filter_map(|wd| {
+                    week_start.checked_add_signed(Duration::days(wd.num_days_from_monday() as i64))
+                })
+                .collect();
+            (week_start, dates)
+        }
+        Frequency::Monthly => {
+            let (year, month) = add_months(start_date, step)?;
+            let anchor = NaiveDate::from_ymd_opt(year, month, 1)?;
+            if !rule.by_month.is_empty() && !rule.by_month.contains(&month) {
+                (anchor, Vec::new())
+            } else {
+                (anchor, dates_in_month(rule, year, month, start_date))
+            }
+        }
+        Frequency::Yearly => {
+            let year = start_date.year().checked_add(i32::try_from(step).ok()?)?;
+            let anchor = NaiveDate::from_ymd_opt(year, 1, 1)?;
+            let months = if rule.by_month.is_empty() {
+                vec![start_date.month()]
+            } else {
+                rule.by_month.clone()
+            };
+            let dates = months
+                .iter()
+                .flat_map(|&m| dates_in_month(rule, year, m, start_date))
+                .collect();
+            (anchor, dates)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_rejects_hostile_byday() {
+        for value in [
+            "FREQ=WEEKLY;BYDAY=1月",
+            "FREQ=WEEKLY;BYDAY=é",
+            "FREQ=WEEKLY;BYDAY=月曜",
+            "FREQ=MONTHLY;BYDAY=2147483647MO",
+            "FREQ=MONTHLY;BYDAY=-2147483648MO",
+            "FREQ=MONTHLY;BYDAY=0MO",
+        ] {
+            assert!(parse_rrule(value).is_err(), "{}", value);
+        }
+        let rule = parse_rrule("FREQ=MONTHLY;BYDAY=+2TU,-1FR;BYMONTHDAY=-2147483648,15").unwrap();
+        assert_eq!(
+            rule.by_day,
+            vec![(Some(2), Weekday::Tue), (Some(-1), Weekday::Fri)]
+        );
+        assert_eq!(rule.by_month_day, vec![15]);
+    }
+
+    #[test]
+    fn test_expand_stops_at_the_end_of_the_date_range() {
+        let start = at(2024, 1, 31);
+        for value in [
+            "FREQ=DAILY;INTERVAL=4294967295;COUNT=4294967295",
+            "FREQ=WEEKLY;INTERVAL=4294967295",
+            "FREQ=MONTHLY;INTERVAL=4294967295;BYDAY=-1FR",
+            "FREQ=YEARLY;INTERVAL=4000000000;BYMONTH=2;BYMONTHDAY=-1",
+            "FREQ=DAILY;INTERVAL=100000000",
+        ] {
+            let rule = parse_rrule(value).unwrap();
+            let occurrences = expand(&rule, start, None, start, NaiveDateTime::MAX, 1000);
+            assert!(occurrences.len() <= 1, "{}", value);
+        }
+
+        // 通常のルールはこれまでどおり展開される
+        let rule = parse_rrule("FREQ=MONTHLY;BYDAY=-1FR;COUNT=3").unwrap();
+        assert_eq!(
+            expand(&rule, start, None, start, at(2025, 1, 1), 10),
+            vec![at(2024, 2, 23), at(2024, 3, 29), at(2024, 4, 26)]
+        );
+    }
+}
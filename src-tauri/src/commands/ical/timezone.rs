@@ -0,0 +1,210 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Utc,
+};
+use chrono_tz::{OffsetComponents, Tz};
+use std::collections::HashMap;
+
+use crate::commands::ical::format::{format_offset, IcsWriter};
+use crate::commands::ical::parse::IcsComponent;
+use crate::commands::ical::recurrence::{nth_weekday_of_month, parse_rrule, Rrule};
+use crate::commands::ical::value::IcsDateTime;
+
+// 指定期間中のオフセット切り替えを実際に調べて VTIMEZONE を書き出す
+// (RRULE は使わず、切り替えごとに STANDARD/DAYLIGHT を並べる)
+pub fn write_vtimezone(writer: &mut IcsWriter, tz: Tz, from: NaiveDate, to: NaiveDate) {
+    let offset_at = |t: DateTime<Utc>| tz.offset_from_utc_datetime(&t.naive_utc());
+    let start = from.and_time(NaiveTime::MIN).and_utc();
+    let end = to.and_time(NaiveTime::MIN).and_utc() + Duration::days(1);
+
+    writer.line("BEGIN:VTIMEZONE");
+    writer.line(format!("TZID:{}", tz.name()));
+
+    let mut current = offset_at(start);
+    write_observance(writer, current.fix(), &current, start);
+
+    let mut day = start;
+    while day < end {
+        let next = day + Duration::days(1);
+        if offset_at(next).fix() != current.fix() {
+            // 1日の中で切り替わった時刻を秒単位まで絞り込む
+            let (mut lo, mut hi) = (day, next);
+            while hi - lo > Duration::seconds(1) {
+                let mid = lo + (hi - lo) / 2;
+                if offset_at(mid).fix() == current.fix() {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            let changed = offset_at(hi);
+            write_observance(writer, current.fix(), &changed, hi);
+            current = changed;
+        }
+        day = next;
+    }
+
+    writer.line("END:VTIMEZONE");
+}
+
+fn write_observance(
+    writer: &mut IcsWriter,
+    from: FixedOffset,
+    to: &<Tz as TimeZone>::Offset,
+    at: DateTime<Utc>,
+) {
+    let kind = if to.dst_offset().is_zero() {
+        "STANDARD"
+    } else {
+        "DAYLIGHT"
+    };
+    // DTSTART は切り替え前のオフセットでのローカル時刻
+    let local_start = at.naive_utc() + Duration::seconds(from.local_minus_utc() as i64);
+
+    writer.line(format!("BEGIN:{}", kind));
+    writer.line(format!("DTSTART:{}", local_start.format("%Y%m%dT%H%M%S")));
+    writer.line(format!("TZOFFSETFROM:{}", format_offset(from)));
+    writer.line(format!("TZOFFSETTO:{}", format_offset(to.fix())));
+    writer.line(format!("TZNAME:{}", to));
+    writer.line(format!("END:{}", kind));
+}
+
+// VTIMEZONE の STANDARD/DAYLIGHT 1件分
+struct Observance {
+    start: NaiveDateTime,
+    offset_to: FixedOffset,
+    rule: Option<Rrule>,
+}
+
+impl Observance {
+    // local 以前で最も新しい切り替え時刻
+    fn last_onset(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        if self.start > local {
+            return None;
+        }
+        let Some(rule) = &self.rule else {
+            return Some(self.start);
+        };
+        let until = rule.until.as_ref().map(|u| u.naive());
+        let month = rule.by_month.first().copied().unwrap_or(self.start.month());
+        let (nth, weekday) = rule
+            .by_day
+            .first()
+            .map(|(nth, wd)| (nth.unwrap_or(1), *wd))
+            .unwrap_or((0, self.start.weekday()));
+
+        (self.start.year()..=local.year())
+            .rev()
+            .take(2)
+            .filter_map(|year| {
+                let date = if nth == 0 {
+                    NaiveDate::from_ymd_opt(year, month, self.start.day())
+                } else {
+                    nth_weekday_of_month(year, month, weekday, nth)
+                }?;
+                Some(date.and_time(self.start.time()))
+            })
+            .filter(|onset| *onset >= self.start && *onset <= local)
+            .find(|onset| until.is_none_or(|u| *onset <= u))
+            .or(Some(self.start))
+    }
+}
+
+// 取り込むファイル内の TZID を解決する
+// IANA 名ならそのまま chrono-tz で、それ以外はファイル内の VTIMEZONE 定義で変換する
+pub struct TzResolver {
+    custom: HashMap<String, Vec<Observance>>,
+}
+
+impl TzResolver {
+    pub fn from_calendar(calendar: &IcsComponent) -> Self {
+        let mut custom = HashMap::new();
+        for vtimezone in calendar.children_named("VTIMEZONE") {
+            let Some(tzid) = vtimezone.property("TZID").map(|p| p.value.clone()) else {
+                continue;
+            };
+            let observances = vtimezone
+                .children
+                .iter()
+                .filter(|c| c.name == "STANDARD" || c.name == "DAYLIGHT")
+                .filter_map(|c| {
+                    let start = c.property("DTSTART")?.value.clone();
+                    let start =
+                        NaiveDateTime::parse_from_str(start.trim(), "%Y%m%dT%H%M%S").ok()?;
+                    let offset_to = parse_offset(&c.property("TZOFFSETTO")?.value)?;
+                    let rule = c.property("RRULE").and_then(|p| parse_rrule(&p.value).ok());
+                    Some(Observance {
+                        start,
+                        offset_to,
+                        rule,
+                    })
+                })
+                .collect();
+            custom.insert(tzid, observances);
+        }
+        TzResolver { custom }
+    }
+
+    pub fn is_known(&self, tzid: &str) -> bool {
+        iana_timezone(tzid).is_some() || self.custom.contains_key(tzid)
+    }
+
+    // 解決できない TZID はフローティング時刻 (端末のローカル時刻) として扱う
+    pub fn resolve(&self, value: &IcsDateTime) -> DateTime<Local> {
+        let local_fallback = |naive: NaiveDateTime| {
+            Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+        };
+        match value {
+            IcsDateTime::Date(_) => local_fallback(value.naive()),
+            IcsDateTime::Utc(naive) => Local.from_utc_datetime(naive),
+            IcsDateTime::Local { value, tzid: None } => local_fallback(*value),
+            IcsDateTime::Local {
+                value,
+                tzid: Some(tzid),
+            } => {
+                if let Some(tz) = iana_timezone(tzid) {
+                    if let Some(dt) = tz.from_local_datetime(value).earliest() {
+                        return dt.with_timezone(&Local);
+                    }
+                }
+                if let Some(observances) = self.custom.get(tzid) {
+                    let offset = observances
+                        .iter()
+                        .filter_map(|o| o.last_onset(*value).map(|onset| (onset, o.offset_to)))
+                        .max_by_key(|(onset, _)| *onset)
+                        .map(|(_, offset)| offset)
+                        .or_else(|| observances.first().map(|o| o.offset_to));
+                    if let Some(offset) = offset {
+                        if let Some(dt) = offset.from_local_datetime(value).single() {
+                            return dt.with_timezone(&Local);
+                        }
+                    }
+                }
+                local_fallback(*value)
+            }
+        }
+    }
+}
+
+fn iana_timezone(tzid: &str) -> Option<Tz> {
+    tzid.trim_start_matches('/').parse().ok()
+}
+
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[0..2].parse().ok()?;
+    let minutes: i32 = digits[2..4].parse().ok()?;
+    let seconds: i32 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
@@ -0,0 +1,117 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::commands::ical::parse::IcsProperty;
+
+// DATE / DATE-TIME 値 (TZID の解決は timezone::TzResolver で行う)
+#[derive(Debug, Clone, PartialEq)]
+pub enum IcsDateTime {
+    Date(NaiveDate),
+    Utc(NaiveDateTime),
+    Local {
+        value: NaiveDateTime,
+        tzid: Option<String>,
+    },
+}
+
+impl IcsDateTime {
+    pub fn naive(&self) -> NaiveDateTime {
+        match self {
+            IcsDateTime::Date(date) => date.and_hms_opt(0, 0, 0).unwrap(),
+            IcsDateTime::Utc(value) | IcsDateTime::Local { value, .. } => *value,
+        }
+    }
+
+    // 繰り返しの展開で得た時刻を、元の値と同じ種類・タイムゾーンで作り直す
+    pub fn with_naive(&self, naive: NaiveDateTime) -> IcsDateTime {
+        match self {
+            IcsDateTime::Date(_) => IcsDateTime::Date(naive.date()),
+            IcsDateTime::Utc(_) => IcsDateTime::Utc(naive),
+            IcsDateTime::Local { tzid, .. } => IcsDateTime::Local {
+                value: naive,
+                tzid: tzid.clone(),
+            },
+        }
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches!(self, IcsDateTime::Date(_))
+    }
+}
+
+pub fn parse_date_time_value(
+    value: &str,
+    tzid: Option<&str>,
+    date_only: bool,
+) -> Option<IcsDateTime> {
+    let value = value.trim();
+    if date_only || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(IcsDateTime::Date);
+    }
+    if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(IcsDateTime::Utc);
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .map(|value| IcsDateTime::Local {
+            value,
+            tzid: tzid.map(|t| t.to_string()),
+        })
+}
+
+// EXDATE のようにカンマ区切りで複数の値を持つプロパティにも対応する
+pub fn parse_property_date_times(property: &IcsProperty) -> Vec<IcsDateTime> {
+    let date_only = property
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"));
+    let tzid = property.param("TZID");
+    property
+        .value
+        .split(',')
+        .filter_map(|v| parse_date_time_value(v, tzid, date_only))
+        .collect()
+}
+
+pub fn parse_property_date_time(property: &IcsProperty) -> Option<IcsDateTime> {
+    parse_property_date_times(property).into_iter().next()
+}
+
+// DURATION 値 (例: P1D, PT1H30M, -P2W)
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, rest) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let rest = rest.strip_prefix(['P', 'p'])?;
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match (unit, in_time) {
+                    ('W', false) => Duration::weeks(n),
+                    ('D', false) => Duration::days(n),
+                    ('H', true) => Duration::hours(n),
+                    ('M', true) => Duration::minutes(n),
+                    ('S', true) => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total * sign)
+}
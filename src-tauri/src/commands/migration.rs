@@ -0,0 +1,184 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::commands::settings::db::{get_setting, set_setting};
+
+pub async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> Result<bool, sqlx::Error> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().any(|row| {
+        row.try_get::<String, _>("name")
+            .map(|name| name == column)
+            .unwrap_or(false)
+    }))
+}
+
+// CREATE TABLE IF NOT EXISTS は既存テーブルに新しいカラムを追加しないため、
+// 後から追加したカラムはここで補う
+pub async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    if !has_column(pool, table, column).await? {
+        sqlx::query(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+const UTC_STORAGE_KEY: &str = "utc_datetime_storage";
+
+// オフセット付きで保存していた日時のカラム
+// 説明文やメモの本文などユーザーが書いた文字列は、日時に見えても書き換えない
+const DATETIME_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "tasks",
+        &[
+            "start_date",
+            "end_date",
+            "completed_at",
+            "archived_at",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+    ),
+    ("subtasks", &["created_at", "updated_at", "deleted_at"]),
+    ("task_groups", &["created_at", "updated_at", "deleted_at"]),
+    ("smart_lists", &["created_at", "updated_at", "deleted_at"]),
+    ("rela_task_ical", &["created_at", "updated_at"]),
+    (
+        "events",
+        &[
+            "start_date",
+            "end_date",
+            "created_at",
+            "updated_at",
+            "deleted_at",
+        ],
+    ),
+    (
+        "calendar_memos",
+        &["created_at", "updated_at", "deleted_at"],
+    ),
+    ("calendar_memo_versions", &["created_at"]),
+    ("holidays", &["created_at", "updated_at", "deleted_at"]),
+    ("books", &["created_at", "updated_at", "deleted_at"]),
+    ("reading_memos", &["created_at", "updated_at", "deleted_at"]),
+    ("mind_maps", &["created_at", "updated_at", "deleted_at"]),
+    ("settings", &["created_at", "updated_at"]),
+];
+
+// 以前は Local::now().to_rfc3339() のオフセット付き時刻で保存していたため、
+// DATETIME_COLUMNS のオフセット付きの日時を UTC (ミリ秒まで, 末尾 Z) に書き換える
+// 日付だけの値 (YYYY-MM-DD) や UTC のものは対象外。一度だけ実行する
+pub async fn normalize_datetimes_to_utc(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    if get_setting::<bool>(pool, UTC_STORAGE_KEY).await? == Some(true) {
+        return Ok(());
+    }
+
+    let mut targets = Vec::new();
+    for (table, columns) in DATETIME_COLUMNS {
+        for column in *columns {
+            // まだ作られていないテーブル・カラムは飛ばす
+            if has_column(pool, table, column).await? {
+                targets.push((table, column));
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    for (table, column) in targets {
+        sqlx::query(&format!(
+            "UPDATE \"{table}\" SET \"{column}\" = strftime('%Y-%m-%dT%H:%M:%fZ', \"{column}\")
+             WHERE typeof(\"{column}\") = 'text'
+               AND \"{column}\" GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]T[0-9][0-9]:[0-9][0-9]*[+-][0-9][0-9]:[0-9][0-9]'
+               AND strftime('%Y-%m-%dT%H:%M:%fZ', \"{column}\") IS NOT NULL"
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    set_setting(pool, UTC_STORAGE_KEY, &true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_normalize_datetimes_to_utc() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE tasks (id TEXT, description TEXT, start_date TEXT, created_at TEXT)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO tasks VALUES
+                ('a', '2024-05-01T10:00:00+09:00', '2024-05-01', '2024-05-01T08:30:00.123456+09:00'),
+                ('b', 'review', '2024-05-01', '2024-05-01T00:00:00.000Z')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        normalize_datetimes_to_utc(&pool).await.unwrap();
+
+        let rows = sqlx::query("SELECT description, start_date, created_at FROM tasks ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let values: Vec<(String, String, String)> = rows
+            .iter()
+            .map(|r| {
+                (
+                    r.get("description"),
+                    r.get("start_date"),
+                    r.get("created_at"),
+                )
+            })
+            .collect();
+        // 日時に見える説明文はそのまま
+        assert_eq!(
+            values,
+            vec![
+                (
+                    "2024-05-01T10:00:00+09:00".to_string(),
+                    "2024-05-01".to_string(),
+                    "2024-04-30T23:30:00.123Z".to_string()
+                ),
+                (
+                    "review".to_string(),
+                    "2024-05-01".to_string(),
+                    "2024-05-01T00:00:00.000Z".to_string()
+                ),
+            ]
+        );
+
+        // 2回目以降は何もしない
+        sqlx::query("UPDATE tasks SET created_at = '2024-05-01T09:00:00+09:00' WHERE id = 'b'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        normalize_datetimes_to_utc(&pool).await.unwrap();
+        let created_at: String = sqlx::query_scalar("SELECT created_at FROM tasks WHERE id = 'b'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(created_at, "2024-05-01T09:00:00+09:00");
+    }
+}
@@ -1,6 +1,8 @@
 use crate::commands::mindmap::db::MindMap;
+use crate::commands::search::db::{remove_document, SearchKind};
+use crate::commands::search::indexer::index_mind_map;
+use crate::commands::settings::timezone::now_for_db;
 use crate::AppState;
-use chrono::Local;
 use tauri::State;
 
 #[tauri::command]
@@ -35,6 +37,9 @@ pub async fn create_mind_map(
     .await
     .map_err(|e| e.to_string())?;
 
+    if let Err(e) = index_mind_map(&state.pool, &mind_map).await {
+        println!("Failed to index mind map {}: {}", mind_map.id, e);
+    }
     Ok(mind_map)
 }
 
@@ -45,7 +50,7 @@ pub async fn update_mind_map(
     title: String,
     content: String,
 ) -> Result<MindMap, String> {
-    let updated_at = Local::now().to_rfc3339();
+    let updated_at = now_for_db();
 
     sqlx::query("UPDATE mind_maps SET title = ?, content = ?, updated_at = ? WHERE id = ?")
         .bind(&title)
@@ -62,17 +67,23 @@ pub async fn update_mind_map(
         .await
         .map_err(|e| e.to_string())?;
 
+    if let Err(e) = index_mind_map(&state.pool, &updated_map).await {
+        println!("Failed to index mind map {}: {}", updated_map.id, e);
+    }
     Ok(updated_map)
 }
 
 #[tauri::command]
 pub async fn delete_mind_map(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let deleted_at = Local::now().to_rfc3339();
+    let deleted_at = now_for_db();
     sqlx::query("UPDATE mind_maps SET deleted_at = ? WHERE id = ?")
         .bind(deleted_at)
-        .bind(id)
+        .bind(&id)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
+    if let Err(e) = remove_document(&state.pool, SearchKind::MindMap, &id).await {
+        println!("Failed to remove mind map {} from the index: {}", id, e);
+    }
     Ok(())
 }
@@ -1,4 +1,4 @@
-use chrono::Local;
+use crate::commands::settings::timezone::now_for_db;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use uuid::Uuid;
@@ -19,7 +19,7 @@ impl MindMap {
             id: Uuid::new_v4().to_string(),
             title,
             content,
-            created_at: Local::now().to_rfc3339(),
+            created_at: now_for_db(),
             updated_at: None,
             deleted_at: None,
         }
@@ -1 +1,11 @@
+pub mod attachments;
+pub mod export;
 pub mod fs;
+pub mod import;
+pub mod links;
+pub mod meta;
+pub mod root;
+pub mod tasks;
+pub mod trash;
+pub mod versions;
+pub mod watcher;
@@ -0,0 +1,329 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::ipc::{InvokeBody, Request};
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::links::{
+    list_notes, normalize, parent_dir, parse_all_links, percent_decode, relative_link, LinkKind,
+};
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::notes::trash::move_to_trash;
+use crate::commands::sandbox::{check_name, relative_to, resolve_within};
+use crate::AppState;
+
+// ノートと同じフォルダの attachments/ に置く
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+// 取り込むファイルの上限 (100MB)
+const MAX_ATTACHMENT_SIZE: u64 = 100 * 1024 * 1024;
+
+pub const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "webp", "svg", "bmp"];
+
+#[derive(Debug, Serialize)]
+pub struct Attachment {
+    // ルートからの相対パス
+    pub path: String,
+    // ノートに貼り付ける Markdown (ノートからの相対パス)
+    pub markdown: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttachmentInfo {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttachmentCleanup {
+    // どのノートからも参照されていない添付ファイル
+    pub unused: Vec<AttachmentInfo>,
+    pub total_size: u64,
+    // ゴミ箱に移した場合は true
+    pub trashed: bool,
+}
+
+// 貼り付けられた画像には名前がないので、先頭のバイト列から拡張子を決める
+fn sniff_extension(data: &[u8]) -> Option<&'static str> {
+    let signatures: [(&[u8], &str); 5] = [
+        (b"\x89PNG\r\n\x1a\n", "png"),
+        (b"\xff\xd8\xff", "jpg"),
+        (b"GIF8", "gif"),
+        (b"%PDF", "pdf"),
+        (b"BM", "bmp"),
+    ];
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some("webp");
+    }
+    signatures
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+        .map(|(_, ext)| *ext)
+}
+
+fn extension_of(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .filter(|e| !e.is_empty() && e.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+// Markdown のリンク先として書けるよう空白などをエスケープする
+pub fn encode_link(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+// 同じ内容のファイルは同じ名前になるので、二重に保存しない
+pub fn store_attachment(
+    root: &Path,
+    note_path: &str,
+    data: &[u8],
+    file_name: Option<&str>,
+) -> Result<Attachment, String> {
+    let note = resolve_within(root, note_path)?;
+    let note_relative = relative_to(root, &note)?;
+    let extension = file_name
+        .and_then(extension_of)
+        .or_else(|| sniff_extension(data).map(|e| e.to_string()))
+        .unwrap_or_else(|| "bin".to_string());
+
+    let hash = format!("{:x}", Sha256::digest(data));
+    let name = format!("{}.{}", &hash[..16], extension);
+    let relative = match parent_dir(&note_relative) {
+        "" => format!("{}/{}", ATTACHMENTS_DIR, name),
+        dir => format!("{}/{}/{}", dir, ATTACHMENTS_DIR, name),
+    };
+    let path = resolve_within(root, &relative)?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, data).map_err(|e| e.to_string())?;
+    }
+
+    let label = file_name
+        .and_then(|n| Path::new(n).file_stem())
+        .map(|s| s.to_string_lossy().replace(['[', ']'], ""))
+        .unwrap_or_default();
+    let link = encode_link(&relative_link(parent_dir(&note_relative), &relative));
+    let markdown = if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        format!("![{}]({})", label, link)
+    } else {
+        format!(
+            "[{}]({})",
+            if label.is_empty() { &name } else { &label },
+            link
+        )
+    };
+    Ok(Attachment {
+        path: relative,
+        markdown,
+    })
+}
+
+fn collect_attachments(dir: &Path, in_attachments: bool, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => {
+                collect_attachments(&path, in_attachments || name == ATTACHMENTS_DIR, found)
+            }
+            Ok(t) if t.is_file() && in_attachments && !name.ends_with(".md") => found.push(path),
+            _ => {}
+        }
+    }
+}
+
+// ノートから参照されている添付ファイル (ルートからの相対パス・ファイル名)
+fn referenced(root: &Path) -> Result<(HashSet<String>, HashSet<String>), String> {
+    let mut paths = HashSet::new();
+    let mut names = HashSet::new();
+    for note in list_notes(root)? {
+        let Ok(content) = fs::read_to_string(root.join(&note)) else {
+            continue;
+        };
+        for link in parse_all_links(&content) {
+            match link.kind {
+                // ![[image.png]] はファイル名だけで書かれることが多い
+                LinkKind::Wiki => {
+                    let target = link.target.trim_start_matches('/');
+                    paths.insert(target.to_string());
+                    names.insert(target.rsplit('/').next().unwrap_or(target).to_string());
+                }
+                LinkKind::Markdown => {
+                    let joined = match link.target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("{}/{}", parent_dir(&note), link.target),
+                    };
+                    if let Some(path) = normalize(&joined) {
+                        paths.insert(path);
+                    }
+                }
+            }
+        }
+    }
+    Ok((paths, names))
+}
+
+pub fn find_unused_attachments(root: &Path) -> Result<Vec<AttachmentInfo>, String> {
+    let (paths, names) = referenced(root)?;
+    let mut found = Vec::new();
+    collect_attachments(root, false, &mut found);
+
+    let mut unused = Vec::new();
+    for path in found {
+        let relative = relative_to(root, &path)?;
+        let name = relative.rsplit('/').next().unwrap_or(&relative);
+        if paths.contains(&relative) || names.contains(name) {
+            continue;
+        }
+        unused.push(AttachmentInfo {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: relative,
+        });
+    }
+    unused.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(unused)
+}
+
+// ウィンドウにドロップされたファイル。パスは Rust 側のドロップイベントからだけ受け取る
+#[derive(Default)]
+pub struct DroppedFiles(Mutex<Vec<PathBuf>>);
+
+impl DroppedFiles {
+    pub fn set(&self, paths: Vec<PathBuf>) {
+        if let Ok(mut dropped) = self.0.lock() {
+            *dropped = paths;
+        }
+    }
+
+    fn take(&self) -> Vec<PathBuf> {
+        self.0
+            .lock()
+            .map(|mut dropped| std::mem::take(&mut *dropped))
+            .unwrap_or_default()
+    }
+}
+
+fn header(request: &Request<'_>, key: &str) -> Option<String> {
+    request
+        .headers()
+        .get(key)
+        .and_then(|value| value.to_str().ok())
+        .map(percent_decode)
+}
+
+// 貼り付けたデータを保存する。本文は生のバイト列、ノートのパスとファイル名はヘッダー (URL エンコード) で受け取る
+#[tauri::command]
+pub async fn save_attachment(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    request: Request<'_>,
+) -> Result<Attachment, String> {
+    let InvokeBody::Raw(data) = request.body() else {
+        return Err("Attachment data must be sent as raw bytes".to_string());
+    };
+    let note_path = header(&request, "note-path").ok_or("Missing note path")?;
+    let file_name = header(&request, "file-name").filter(|name| !name.is_empty());
+    if let Some(name) = &file_name {
+        check_name(name)?;
+    }
+    if data.len() as u64 > MAX_ATTACHMENT_SIZE {
+        return Err("Attachment is too large".to_string());
+    }
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    store_attachment(&root, &note_path, data, file_name.as_deref())
+}
+
+// 直前にドロップされたファイルを取り込む。webview からはパスを受け取らない
+#[tauri::command]
+pub async fn attach_dropped_files(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    dropped: State<'_, DroppedFiles>,
+    note_path: String,
+) -> Result<Vec<Attachment>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let mut attachments = Vec::new();
+    for source in dropped.take() {
+        let metadata = fs::metadata(&source).map_err(|e| e.to_string())?;
+        if !metadata.is_file() {
+            return Err("Attachment source is not a file".to_string());
+        }
+        if metadata.len() > MAX_ATTACHMENT_SIZE {
+            return Err("Attachment is too large".to_string());
+        }
+        let data = fs::read(&source).map_err(|e| e.to_string())?;
+        let name = source.file_name().map(|n| n.to_string_lossy().to_string());
+        attachments.push(store_attachment(&root, &note_path, &data, name.as_deref())?);
+    }
+    Ok(attachments)
+}
+
+// 参照されていない添付ファイルを一覧にする (trash なら一緒にゴミ箱へ移す)
+#[tauri::command]
+pub async fn cleanup_attachments(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    trash: Option<bool>,
+) -> Result<AttachmentCleanup, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let unused = find_unused_attachments(&root)?;
+    let trashed = trash.unwrap_or(false);
+    if trashed {
+        for attachment in &unused {
+            move_to_trash(&state.pool, &root, &root.join(&attachment.path)).await?;
+        }
+    }
+    Ok(AttachmentCleanup {
+        total_size: unused.iter().map(|a| a.size).sum(),
+        unused,
+        trashed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_and_find_unused_attachments() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        let root = root.canonicalize().unwrap();
+        let png = b"\x89PNG\r\n\x1a\nfake image";
+
+        let pasted = store_attachment(&root, "Projects/Plan.md", png, None).unwrap();
+        assert!(pasted.path.starts_with("Projects/attachments/"));
+        assert!(pasted.path.ends_with(".png"));
+        assert!(pasted.markdown.starts_with("![](attachments/"));
+
+        // 同じ内容は同じファイルになる
+        let again = store_attachment(&root, "Projects/Plan.md", png, Some("shot.png")).unwrap();
+        assert_eq!(again.path, pasted.path);
+
+        let doc = store_attachment(&root, "Index.md", b"%PDF-1.4", Some("My Doc.pdf")).unwrap();
+        assert_eq!(doc.path.rsplit('.').next(), Some("pdf"));
+        assert!(doc.markdown.starts_with("[My Doc](attachments/"));
+
+        fs::write(root.join("Projects/Plan.md"), &pasted.markdown).unwrap();
+        fs::write(root.join("Index.md"), "no links").unwrap();
+        let unused = find_unused_attachments(&root).unwrap();
+        let paths: Vec<&str> = unused.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, vec![doc.path.as_str()]);
+    }
+}
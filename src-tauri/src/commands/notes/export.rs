@@ -0,0 +1,607 @@
+use base64::Engine;
+use pulldown_cmark::{html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::links::{
+    list_notes, normalize, parent_dir, percent_decode, relative_link, resolve_link, LinkKind,
+};
+use crate::commands::notes::meta::parse_front_matter;
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::sandbox::{relative_to, resolve_within};
+use crate::AppState;
+
+const STYLE_FILE: &str = "style.css";
+
+const STYLE: &str = "body { margin: 0; font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", \"Hiragino Sans\", \"Noto Sans JP\", sans-serif; line-height: 1.7; color: #222; }
+nav { padding: 0.75rem 1.5rem; border-bottom: 1px solid #ddd; }
+main { max-width: 48rem; margin: 0 auto; padding: 1.5rem; }
+img { max-width: 100%; }
+pre { padding: 0.75rem; overflow-x: auto; background: #f5f5f5; }
+code { font-family: ui-monospace, Menlo, Consolas, monospace; }
+table { border-collapse: collapse; }
+th, td { padding: 0.25rem 0.75rem; border: 1px solid #ccc; }
+blockquote { margin-left: 0; padding-left: 1rem; border-left: 4px solid #ddd; color: #555; }
+li:has(> input[type=checkbox]) { list-style: none; }
+.broken-link { color: #b00; text-decoration: underline dotted; }
+";
+
+#[derive(Debug, Serialize)]
+pub struct HtmlExport {
+    pub output_dir: String,
+    // 書き出したページ (出力先からの相対パス)
+    pub pages: Vec<String>,
+    // コピーした画像・添付ファイル (出力先からの相対パス)
+    pub assets: Vec<String>,
+    // 書き出せなかったリンク先・画像
+    pub missing: Vec<String>,
+}
+
+struct Renderer<'a> {
+    root: &'a Path,
+    notes: &'a [String],
+    // 今回書き出すノート (ここにないノートへのリンクは切れたリンクとして表示する)
+    exported: &'a HashSet<String>,
+    inline_images: bool,
+    assets: BTreeSet<String>,
+    missing: BTreeSet<String>,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn encode_href(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn is_external(url: &str) -> bool {
+    url.contains("://") || url.starts_with("mailto:") || url.starts_with("data:")
+}
+
+fn html_path(note: &str) -> String {
+    format!("{}.html", note.strip_suffix(".md").unwrap_or(note))
+}
+
+// 目次のページ名。ルートの Index.md などと大文字小文字だけ違う名前になると
+// macOS・Windows では上書きしてしまうので、重ならない名前にする
+fn index_page(pages: &[String]) -> String {
+    let mut name = "index.html".to_string();
+    while pages.iter().any(|page| page.eq_ignore_ascii_case(&name)) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn title_of(note: &str, content: &str) -> String {
+    parse_front_matter(content)
+        .and_then(|front| front.title)
+        .unwrap_or_else(|| {
+            let name = note.rsplit('/').next().unwrap_or(note);
+            name.strip_suffix(".md").unwrap_or(name).to_string()
+        })
+}
+
+// 見出しのアンカー。[[Note#見出し]] のリンクにも同じ変換をかける
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn mime_type(path: &str) -> &'static str {
+    match path.rsplit('.').next().map(|e| e.to_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn find_by_name(dir: &Path, name: &str, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        if entry_name.starts_with('.') {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => find_by_name(&entry.path(), name, found),
+            Ok(t) if t.is_file() && entry_name == name => found.push(entry.path()),
+            _ => {}
+        }
+    }
+}
+
+impl Renderer<'_> {
+    // ノート以外のファイル (画像・添付) をルートからの相対パスにする
+    fn find_file(&self, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+        let exists = |path: &str| {
+            resolve_within(self.root, path)
+                .map(|p| p.is_file())
+                .unwrap_or(false)
+        };
+        let mut candidates = Vec::new();
+        if let Some(absolute) = target.strip_prefix('/') {
+            candidates.push(normalize(absolute)?);
+        } else {
+            candidates.extend(normalize(&format!("{}/{}", parent_dir(source), target)));
+            // ![[image.png]] はルートからのパスかファイル名だけで書かれる
+            if kind == LinkKind::Wiki {
+                candidates.extend(normalize(target));
+            }
+        }
+        if let Some(found) = candidates.into_iter().find(|c| exists(c)) {
+            return Some(found);
+        }
+        if kind == LinkKind::Wiki && !target.contains('/') {
+            let mut found = Vec::new();
+            find_by_name(self.root, target, &mut found);
+            return found
+                .iter()
+                .filter_map(|path| relative_to(self.root, path).ok())
+                .min_by_key(|path| path.matches('/').count());
+        }
+        None
+    }
+
+    fn image_src(&mut self, source: &str, kind: LinkKind, target: &str) -> Option<String> {
+        let file = self.find_file(source, kind, target)?;
+        if self.inline_images {
+            let data = fs::read(self.root.join(&file)).ok()?;
+            return Some(format!(
+                "data:{};base64,{}",
+                mime_type(&file),
+                base64::engine::general_purpose::STANDARD.encode(data)
+            ));
+        }
+        let href = encode_href(&relative_link(parent_dir(source), &file));
+        self.assets.insert(file);
+        Some(href)
+    }
+
+    // リンク先の href。None なら切れたリンク
+    fn link_href(&mut self, source: &str, kind: LinkKind, dest: &str) -> Option<String> {
+        let (target, fragment) = match dest.split_once('#') {
+            Some((target, fragment)) => (target, Some(fragment)),
+            None => (dest, None),
+        };
+        let anchor = fragment
+            .map(|f| format!("#{}", encode_href(&slugify(&percent_decode(f)))))
+            .unwrap_or_default();
+        let target = match kind {
+            LinkKind::Wiki => target.trim().to_string(),
+            LinkKind::Markdown => percent_decode(target),
+        };
+        if target.is_empty() {
+            return Some(anchor);
+        }
+
+        let is_note = kind == LinkKind::Wiki || target.ends_with(".md");
+        if let Some(note) = resolve_link(source, kind, &target, self.notes).filter(|_| is_note) {
+            if !self.exported.contains(&note) {
+                return None;
+            }
+            let href = relative_link(parent_dir(source), &html_path(&note));
+            return Some(format!("{}{}", encode_href(&href), anchor));
+        }
+        // PDF などの添付ファイルは画像と同じようにコピーする
+        let file = self
+            .find_file(source, kind, &target)
+            .filter(|file| !file.ends_with(".md"))?;
+        let href = encode_href(&relative_link(parent_dir(source), &file));
+        self.assets.insert(file);
+        Some(href)
+    }
+
+    fn render(&mut self, source: &str, content: &str) -> String {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_TASKLISTS
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_FOOTNOTES
+            | Options::ENABLE_WIKILINKS
+            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS;
+        let mut events: Vec<Event> = Vec::new();
+        let mut in_metadata = false;
+        let mut broken_link = false;
+
+        for event in Parser::new_ext(content, options) {
+            match event {
+                // front matter はページに出さない
+                Event::Start(Tag::MetadataBlock(_)) => in_metadata = true,
+                Event::End(TagEnd::MetadataBlock(_)) => in_metadata = false,
+                _ if in_metadata => {}
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    let kind = match link_type {
+                        LinkType::WikiLink { .. } => LinkKind::Wiki,
+                        _ => LinkKind::Markdown,
+                    };
+                    if is_external(&dest_url) || matches!(link_type, LinkType::Email) {
+                        events.push(Event::Start(Tag::Link {
+                            link_type,
+                            dest_url,
+                            title,
+                            id,
+                        }));
+                        continue;
+                    }
+                    match self.link_href(source, kind, &dest_url) {
+                        Some(href) => events.push(Event::Start(Tag::Link {
+                            link_type,
+                            dest_url: href.into(),
+                            title,
+                            id,
+                        })),
+                        None => {
+                            self.missing.insert(format!("{}: {}", source, dest_url));
+                            broken_link = true;
+                            events.push(Event::Html("<span class=\"broken-link\">".into()));
+                        }
+                    }
+                }
+                Event::End(TagEnd::Link) if broken_link => {
+                    broken_link = false;
+                    events.push(Event::Html("</span>".into()));
+                }
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    let src = if is_external(&dest_url) {
+                        Some(dest_url.to_string())
+                    } else {
+                        let (kind, target) = match link_type {
+                            LinkType::WikiLink { .. } => {
+                                (LinkKind::Wiki, dest_url.trim().to_string())
+                            }
+                            _ => (LinkKind::Markdown, percent_decode(&dest_url)),
+                        };
+                        self.image_src(source, kind, &target)
+                    };
+                    if src.is_none() {
+                        self.missing.insert(format!("{}: {}", source, dest_url));
+                    }
+                    events.push(Event::Start(Tag::Image {
+                        link_type,
+                        dest_url: src.map(CowStr::from).unwrap_or(dest_url),
+                        title,
+                        id,
+                    }));
+                }
+                // 共有するサイトなので、ノートに書かれた HTML はそのまま出さずに文字として表示する
+                Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+                event => events.push(event),
+            }
+        }
+
+        add_heading_ids(&mut events);
+        let mut body = String::new();
+        html::push_html(&mut body, events.into_iter());
+        body
+    }
+}
+
+fn add_heading_ids(events: &mut [Event]) {
+    let mut used: HashSet<String> = HashSet::new();
+    for i in 0..events.len() {
+        let Event::Start(Tag::Heading { id: None, .. }) = &events[i] else {
+            continue;
+        };
+        let mut text = String::new();
+        for event in &events[i + 1..] {
+            match event {
+                Event::End(TagEnd::Heading(_)) => break,
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                _ => {}
+            }
+        }
+        let base = slugify(&text);
+        if base.is_empty() {
+            continue;
+        }
+        let mut slug = base.clone();
+        let mut n = 1;
+        while !used.insert(slug.clone()) {
+            slug = format!("{}-{}", base, n);
+            n += 1;
+        }
+        if let Event::Start(Tag::Heading {
+            level,
+            classes,
+            attrs,
+            ..
+        }) = &events[i]
+        {
+            events[i] = Event::Start(Tag::Heading {
+                level: *level,
+                id: Some(slug.into()),
+                classes: classes.clone(),
+                attrs: attrs.clone(),
+            });
+        }
+    }
+}
+
+// ページの置き場所から出力先のルートへ戻る相対パス ("" か "../" の繰り返し)
+fn to_top(page: &str) -> String {
+    "../".repeat(page.matches('/').count())
+}
+
+fn page_html(page: &str, index: &str, title: &str, body: &str) -> String {
+    let top = to_top(page);
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title}</title>
+<link rel=\"stylesheet\" href=\"{top}{STYLE_FILE}\">
+</head>
+<body>
+<nav><a href=\"{top}{index}\">Index</a></nav>
+<main>
+<article>
+{body}</article>
+</main>
+</body>
+</html>
+",
+        title = escape_html(title),
+        index = encode_href(index),
+    )
+}
+
+// フォルダごとにページを並べた目次
+fn index_html(index: &str, pages: &[(String, String)]) -> String {
+    let mut folders: BTreeMap<&str, Vec<&(String, String)>> = BTreeMap::new();
+    for page in pages {
+        folders.entry(parent_dir(&page.0)).or_default().push(page);
+    }
+    let mut body = String::from("<h1>Index</h1>\n");
+    for (folder, pages) in folders {
+        if !folder.is_empty() {
+            body.push_str(&format!("<h2>{}</h2>\n", escape_html(folder)));
+        }
+        body.push_str("<ul>\n");
+        for (note, title) in pages {
+            body.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                encode_href(&html_path(note)),
+                escape_html(title)
+            ));
+        }
+        body.push_str("</ul>\n");
+    }
+    page_html(index, index, "Index", &body)
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+// path (ノートかフォルダ、"" ならルート全体) を output_dir に静的サイトとして書き出す
+// 出力先ではルートからの相対パスをそのまま使う (Projects/Plan.md -> Projects/Plan.html)
+// 空でない output_dir には overwrite が true のときだけ書き出す
+pub fn export_html(
+    root: &Path,
+    path: &str,
+    output_dir: &Path,
+    inline_images: bool,
+    overwrite: bool,
+) -> Result<HtmlExport, String> {
+    let target = resolve_within(root, path)?;
+    let relative = relative_to(root, &target)?;
+    let notes = list_notes(root)?;
+    let selected: Vec<String> = if target.is_file() {
+        if !relative.ends_with(".md") {
+            return Err("Only Markdown notes can be exported".to_string());
+        }
+        vec![relative]
+    } else {
+        let prefix = format!("{}/", relative);
+        notes
+            .iter()
+            .filter(|note| relative.is_empty() || note.starts_with(&prefix))
+            .cloned()
+            .collect()
+    };
+    if selected.is_empty() {
+        return Err("No notes to export".to_string());
+    }
+
+    let inside_root = "Cannot export into the notes folder".to_string();
+    if output_dir.starts_with(root) {
+        return Err(inside_root);
+    }
+    let in_use = fs::read_dir(output_dir)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if in_use && !overwrite {
+        return Err("Output folder is not empty".to_string());
+    }
+    fs::create_dir_all(output_dir).map_err(|e| e.to_string())?;
+    let output_dir = output_dir.canonicalize().map_err(|e| e.to_string())?;
+    if output_dir.starts_with(root) {
+        return Err(inside_root);
+    }
+
+    let exported: HashSet<String> = selected.iter().cloned().collect();
+    let mut renderer = Renderer {
+        root,
+        notes: &notes,
+        exported: &exported,
+        inline_images,
+        assets: BTreeSet::new(),
+        missing: BTreeSet::new(),
+    };
+    let mut pages: Vec<String> = selected.iter().map(|note| html_path(note)).collect();
+    let index = index_page(&pages);
+    let mut titles = Vec::new();
+    for (note, page) in selected.iter().zip(&pages) {
+        let content = fs::read_to_string(root.join(note)).map_err(|e| e.to_string())?;
+        let title = title_of(note, &content);
+        let body = renderer.render(note, &content);
+        write_file(
+            &output_dir.join(page),
+            page_html(page, &index, &title, &body).as_bytes(),
+        )?;
+        titles.push((note.clone(), title));
+    }
+
+    let mut assets = Vec::new();
+    for asset in &renderer.assets {
+        let source = resolve_within(root, asset)?;
+        let destination = output_dir.join(asset);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::copy(&source, &destination).map_err(|e| e.to_string())?;
+        assets.push(asset.clone());
+    }
+
+    write_file(&output_dir.join(STYLE_FILE), STYLE.as_bytes())?;
+    write_file(
+        &output_dir.join(&index),
+        index_html(&index, &titles).as_bytes(),
+    )?;
+    pages.push(index);
+
+    Ok(HtmlExport {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        pages,
+        assets,
+        missing: renderer.missing.into_iter().collect(),
+    })
+}
+
+// output_dir は書き出し先の絶対パス (ノートのフォルダの外)
+#[tauri::command]
+pub async fn export_notes_html(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    output_dir: String,
+    inline_images: Option<bool>,
+    overwrite: Option<bool>,
+) -> Result<HtmlExport, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let output_dir = PathBuf::from(output_dir);
+    if !output_dir.is_absolute() {
+        return Err("Output folder must be an absolute path".to_string());
+    }
+    export_html(
+        &root,
+        &path,
+        &output_dir,
+        inline_images.unwrap_or(false),
+        overwrite.unwrap_or(false),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_html() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let root = base.join("notes");
+        fs::create_dir_all(root.join("Projects/attachments")).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(root.join("Projects/attachments/shot.png"), b"\x89PNG").unwrap();
+        fs::write(
+            root.join("Projects/Plan.md"),
+            "---\ntitle: The Plan\n---\n# Goals\n\n- [x] done\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n\
+             See [[Index#Top Notes]] and [[Missing]].\n\n![](attachments/shot.png)\n\n\
+             <script>alert(1)</script>\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Index.md"),
+            "## Top Notes\n\n[plan](Projects/Plan.md)\n",
+        )
+        .unwrap();
+        let output = base.join("site");
+
+        let result = export_html(&root, "", &output, false, false).unwrap();
+        // Index.html と重ならないよう、目次は _index.html になる
+        assert_eq!(
+            result.pages,
+            vec!["Index.html", "Projects/Plan.html", "_index.html"]
+        );
+        assert_eq!(result.assets, vec!["Projects/attachments/shot.png"]);
+        assert_eq!(result.missing, vec!["Projects/Plan.md: Missing"]);
+        assert!(output.join("Projects/attachments/shot.png").exists());
+
+        let plan = fs::read_to_string(output.join("Projects/Plan.html")).unwrap();
+        assert!(plan.contains("<title>The Plan</title>"));
+        assert!(!plan.contains("title: The Plan"));
+        assert!(plan.contains("<h1 id=\"goals\">"));
+        assert!(plan.contains("type=\"checkbox\""));
+        assert!(plan.contains("<table>"));
+        assert!(plan.contains("href=\"../Index.html#top-notes\""));
+        assert!(plan.contains("<span class=\"broken-link\">Missing</span>"));
+        assert!(plan.contains("src=\"attachments/shot.png\""));
+        assert!(plan.contains("href=\"../_index.html\""));
+        assert!(!plan.contains("<script>"));
+        assert!(plan.contains("&lt;script&gt;"));
+        let index = fs::read_to_string(output.join("Index.html")).unwrap();
+        assert!(index.contains("<h2 id=\"top-notes\">"));
+        assert!(index.contains("href=\"Projects/Plan.html\""));
+
+        // フォルダだけを書き出すと、その外へのリンクは切れたリンクになる
+        let inline = base.join("inline");
+        let result = export_html(&root, "Projects", &inline, true, false).unwrap();
+        assert_eq!(result.pages, vec!["Projects/Plan.html", "index.html"]);
+        assert!(result.assets.is_empty());
+        assert!(result
+            .missing
+            .contains(&"Projects/Plan.md: Index#Top Notes".to_string()));
+        let plan = fs::read_to_string(inline.join("Projects/Plan.html")).unwrap();
+        assert!(plan.contains("src=\"data:image/png;base64,"));
+
+        // 空でないフォルダには overwrite を指定したときだけ書き出す
+        assert!(export_html(&root, "", &output, false, false).is_err());
+        assert!(export_html(&root, "", &output, false, true).is_ok());
+
+        assert!(export_html(&root, "", &root.join("site"), false, false).is_err());
+    }
+}
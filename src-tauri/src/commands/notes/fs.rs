@@ -1,59 +1,47 @@
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::links::{list_notes, rewrite_links};
+use crate::commands::notes::meta::move_meta;
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::notes::trash::{move_to_trash, TRASH_DIR};
+use crate::commands::notes::versions::snapshot_note;
+use crate::commands::sandbox::{check_name, check_relative, relative_to, resolve_within};
+use crate::commands::search::indexer::{index_note, sync_notes};
+use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileNode {
     pub name: String,
-    pub path: String,
+    pub path: String, // ノートのルートからの相対パス ("/" 区切り)
     pub is_dir: bool,
     pub children: Option<Vec<FileNode>>,
 }
 
-const NOTES_DIR_NAME: &str = "NorunosNotes";
-
-fn get_notes_dir() -> PathBuf {
-    // For simplicity, let's use the App Data directory or Document directory.
-    // However, Rust side getting "Documents" folder might require `directories` crate or tauri API.
-    // Let's rely on a base path relative to the app or a fixed logic.
-    // Or better, let's assume we store it in the AppData directory for now to avoid permission headaches
-    // unless configured otherwise. User said "Documents/NorunosNotes".
-    // Getting standard directories in Rust:
-    // We can use `tauri::path::BaseDirectory`.
-    // For now, let's use the simple approach of storing it alongside the database in AppData,
-    // or try to resolve user's Document folder if possible.
-    // Given the constraints and setup, let's stick to AppData/NorunosNotes for guaranteed access,
-    // or try to use a hardcoded path if user really wants "Documents".
-
-    // Changing strategy: Let's pass the AppHandle or resolve it via `dirs` crate if available?
-    // `dirs` is not in Cargo.toml.
-    // We can use `app_handle.path().document_dir()`.
-
-    // Since we are in a command, we can accept `app_handle` but `files` logic might be cleaner if standalone.
-    // Let's implement helper to get path via tauri context if possible, or just default to AppData for V1.
-    // User specifically mentioned "Documents/NorunosNotes".
-
-    // We will attempt to use the standard directories.
-    // But since adding crates is annoying, let's try to infer from environment or just use a safe place.
-    // Actually, `dirs` crate is very standard. Check Cargo.toml?
-    // If not, we'll punt to AppData for safety unless we want to try `std::env::home_dir` (deprecated) or `env::var("HOME")`.
-
-    if let Ok(home) = std::env::var("HOME") {
-        PathBuf::from(home).join("Documents").join(NOTES_DIR_NAME)
-    } else {
-        PathBuf::from(NOTES_DIR_NAME) // Fallback
-    }
-}
-
-fn ensure_notes_dir() -> Result<PathBuf, String> {
-    let path = get_notes_dir();
+// 同じ名前のものがあれば "name (2).md" のように番号を付けた空いているパスを返す
+pub fn available_path(path: &Path) -> PathBuf {
     if !path.exists() {
-        fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+        return path.to_path_buf();
     }
-    Ok(path)
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
-fn read_dir_recursive(path: &Path) -> Result<Vec<FileNode>, String> {
+fn read_dir_recursive(root: &Path, path: &Path) -> Result<Vec<FileNode>, String> {
     let mut nodes = Vec::new();
     let entries = fs::read_dir(path).map_err(|e| e.to_string())?;
 
@@ -63,16 +51,19 @@ fn read_dir_recursive(path: &Path) -> Result<Vec<FileNode>, String> {
         let metadata = entry.metadata().map_err(|e| e.to_string())?;
         let name = entry.file_name().to_string_lossy().to_string();
         let is_dir = metadata.is_dir();
+        if is_dir && path.parent() == Some(root) && name == TRASH_DIR {
+            continue;
+        }
 
         let children = if is_dir {
-            Some(read_dir_recursive(&path)?)
+            Some(read_dir_recursive(root, &path)?)
         } else {
             None
         };
 
         nodes.push(FileNode {
             name,
-            path: path.to_string_lossy().to_string(),
+            path: relative_to(root, &path)?,
             is_dir,
             children,
         });
@@ -95,29 +86,75 @@ fn read_dir_recursive(path: &Path) -> Result<Vec<FileNode>, String> {
 }
 
 #[tauri::command]
-pub async fn get_notes_tree() -> Result<Vec<FileNode>, String> {
-    let root = ensure_notes_dir()?;
-    read_dir_recursive(&root)
+pub async fn get_notes_tree(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileNode>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    read_dir_recursive(&root, &root)
 }
 
 #[tauri::command]
-pub async fn read_note(path: String) -> Result<String, String> {
-    fs::read_to_string(path).map_err(|e| e.to_string())
+pub async fn read_note(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    fs::read_to_string(resolve_within(&root, &path)?).map_err(|e| e.to_string())
+}
+
+// アプリからノートを書き換えるときはここを通す
+// 上書きする前の内容を版として残してから書き込み、索引を更新する
+pub async fn write_note_file(
+    pool: &SqlitePool,
+    root: &Path,
+    file_path: &Path,
+    content: &str,
+) -> Result<(), String> {
+    if let Ok(previous) = fs::read_to_string(file_path) {
+        snapshot_note(
+            pool,
+            root,
+            &relative_to(root, file_path)?,
+            &previous,
+            content,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    } else if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(file_path, content).map_err(|e| e.to_string())?;
+    index_note(pool, root, file_path).await
 }
 
 #[tauri::command]
-pub async fn save_note(path: String, content: String) -> Result<(), String> {
-    fs::write(path, content).map_err(|e| e.to_string())
+pub async fn save_note(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let file_path = resolve_within(&root, &path)?;
+    if file_path.is_dir() {
+        return Err("Cannot write to a folder".to_string());
+    }
+    write_note_file(&state.pool, &root, &file_path, &content).await
 }
 
 #[tauri::command]
-pub async fn create_note(parent_path: String, name: String) -> Result<String, String> {
-    // If parent_path is empty, use root.
-    let dir = if parent_path.is_empty() {
-        ensure_notes_dir()?
-    } else {
-        PathBuf::from(parent_path)
-    };
+pub async fn create_note(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    parent_path: String,
+    name: String,
+) -> Result<String, String> {
+    check_name(&name)?;
+    // parent_path が空ならルート直下
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let dir = resolve_within(&root, &parent_path)?;
 
     let mut file_path = dir.join(&name);
     if !name.ends_with(".md") {
@@ -129,16 +166,20 @@ pub async fn create_note(parent_path: String, name: String) -> Result<String, St
     }
 
     fs::write(&file_path, "").map_err(|e| e.to_string())?;
-    Ok(file_path.to_string_lossy().to_string())
+    index_note(&state.pool, &root, &file_path).await?;
+    relative_to(&root, &file_path)
 }
 
 #[tauri::command]
-pub async fn create_folder(parent_path: String, name: String) -> Result<String, String> {
-    let dir = if parent_path.is_empty() {
-        ensure_notes_dir()?
-    } else {
-        PathBuf::from(parent_path)
-    };
+pub async fn create_folder(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    parent_path: String,
+    name: String,
+) -> Result<String, String> {
+    check_name(&name)?;
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let dir = resolve_within(&root, &parent_path)?;
 
     let folder_path = dir.join(name);
     if folder_path.exists() {
@@ -146,25 +187,249 @@ pub async fn create_folder(parent_path: String, name: String) -> Result<String,
     }
 
     fs::create_dir(&folder_path).map_err(|e| e.to_string())?;
-    Ok(folder_path.to_string_lossy().to_string())
+    relative_to(&root, &folder_path)
 }
 
 #[tauri::command]
-pub async fn delete_item(path: String) -> Result<(), String> {
-    let p = PathBuf::from(&path);
-    if p.is_dir() {
-        fs::remove_dir_all(p).map_err(|e| e.to_string())
+pub async fn delete_item(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<(), String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let p = resolve_within(&root, &path)?;
+    if p == root {
+        return Err("Cannot delete the notes root".to_string());
+    }
+    // 完全には消さずにゴミ箱へ移す
+    move_to_trash(&state.pool, &root, &p).await?;
+    sync_notes(&state.pool, &root).await
+}
+
+// 同じ名前のものがあるときの扱い
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnConflict {
+    #[default]
+    Error,
+    // "name (2).md" のように番号を付ける
+    Rename,
+}
+
+fn destination(path: PathBuf, on_conflict: OnConflict) -> Result<PathBuf, String> {
+    if !path.exists() {
+        return Ok(path);
+    }
+    match on_conflict {
+        OnConflict::Error => Err("An item with that name already exists".to_string()),
+        OnConflict::Rename => Ok(available_path(&path)),
+    }
+}
+
+// from (フォルダなら中のノートも) が to に移ったときの (元, 後) の相対パス
+fn relocated_notes(notes: &[String], from: &str, to: &str) -> HashMap<String, String> {
+    notes
+        .iter()
+        .filter_map(|note| {
+            let moved = if note == from {
+                to.to_string()
+            } else {
+                format!("{}/{}", to, note.strip_prefix(&format!("{}/", from))?)
+            };
+            Some((note.clone(), moved))
+        })
+        .collect()
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    if from.is_dir() {
+        fs::create_dir(to).map_err(|e| e.to_string())?;
+        for entry in fs::read_dir(from).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
     } else {
-        fs::remove_file(p).map_err(|e| e.to_string())
+        fs::copy(from, to).map(|_| ()).map_err(|e| e.to_string())
     }
 }
 
+// 名前変更・移動の共通処理
+// メタ情報を付け替え、update_links ならリンクも書き換えてから索引を更新する
+async fn relocate_item(
+    state: &State<'_, AppState>,
+    root: &Path,
+    old_path: &Path,
+    new_path: &Path,
+    update_links: bool,
+) -> Result<String, String> {
+    let notes_before = list_notes(root)?;
+    fs::rename(old_path, new_path).map_err(|e| e.to_string())?;
+
+    let old_relative = relative_to(root, old_path)?;
+    let new_relative = relative_to(root, new_path)?;
+    move_meta(&state.pool, &old_relative, &new_relative)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // このノート (フォルダなら中のノート) へのリンクと、中の相対リンクを書き換える
+    if update_links {
+        let moves = relocated_notes(&notes_before, &old_relative, &new_relative);
+        rewrite_links(root, &notes_before, &moves, false)?;
+    }
+
+    sync_notes(&state.pool, root).await?;
+    Ok(new_relative)
+}
+
+fn movable(root: &Path, path: &Path) -> Result<(), String> {
+    if path == root {
+        return Err("Cannot move the notes root".to_string());
+    }
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn rename_item(path: String, new_name: String) -> Result<String, String> {
-    let old_path = PathBuf::from(&path);
+pub async fn rename_item(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    new_name: String,
+    update_links: Option<bool>,
+) -> Result<String, String> {
+    check_name(&new_name)?;
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let old_path = resolve_within(&root, &path)?;
+    if old_path == root {
+        return Err("Cannot rename the notes root".to_string());
+    }
     let parent = old_path.parent().ok_or("Invalid path")?;
     let new_path = parent.join(new_name);
+    check_relative(&relative_to(&root, &new_path)?)?;
+    if new_path.exists() {
+        return Err("An item with that name already exists".to_string());
+    }
+
+    relocate_item(
+        &state,
+        &root,
+        &old_path,
+        &new_path,
+        update_links.unwrap_or(false),
+    )
+    .await
+}
 
-    fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+// 別のフォルダへ移動する (new_parent が空ならルート直下)
+// 移動したノートの中の相対リンクが切れないよう、update_links は既定で有効
+#[tauri::command]
+pub async fn move_item(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    new_parent: String,
+    on_conflict: Option<OnConflict>,
+    update_links: Option<bool>,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let old_path = resolve_within(&root, &path)?;
+    movable(&root, &old_path)?;
+    let parent = resolve_within(&root, &new_parent)?;
+    if !parent.is_dir() {
+        return Err("Destination is not a folder".to_string());
+    }
+    if parent.starts_with(&old_path) {
+        return Err("Cannot move a folder into itself".to_string());
+    }
+    if old_path.parent() == Some(parent.as_path()) {
+        return relative_to(&root, &old_path);
+    }
+
+    let name = old_path.file_name().ok_or("Invalid path")?;
+    let new_path = destination(parent.join(name), on_conflict.unwrap_or_default())?;
+    relocate_item(
+        &state,
+        &root,
+        &old_path,
+        &new_path,
+        update_links.unwrap_or(true),
+    )
+    .await
+}
+
+// ファイル・フォルダを複製する (new_parent を省略すると同じフォルダに番号付きで作る)
+// 複製したノートの中のリンクは、複製先から同じノートを指すよう書き換える
+#[tauri::command]
+pub async fn duplicate_item(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    new_parent: Option<String>,
+    on_conflict: Option<OnConflict>,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let source = resolve_within(&root, &path)?;
+    movable(&root, &source)?;
+    let (parent, on_conflict) = match &new_parent {
+        Some(parent) => (
+            resolve_within(&root, parent)?,
+            on_conflict.unwrap_or_default(),
+        ),
+        None => (
+            source.parent().ok_or("Invalid path")?.to_path_buf(),
+            OnConflict::Rename,
+        ),
+    };
+    if !parent.is_dir() {
+        return Err("Destination is not a folder".to_string());
+    }
+    if parent.starts_with(&source) {
+        return Err("Cannot copy a folder into itself".to_string());
+    }
+
+    let name = source.file_name().ok_or("Invalid path")?;
+    let target = destination(parent.join(name), on_conflict)?;
+    let notes_before = list_notes(&root)?;
+    copy_recursive(&source, &target)?;
+
+    let copies = relocated_notes(
+        &notes_before,
+        &relative_to(&root, &source)?,
+        &relative_to(&root, &target)?,
+    );
+    rewrite_links(&root, &notes_before, &copies, true)?;
+    sync_notes(&state.pool, &root).await?;
+    relative_to(&root, &target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocated_notes_and_available_path() {
+        let notes = vec![
+            "Inbox.md".to_string(),
+            "Projects/Plan.md".to_string(),
+            "Projects/Sub/Todo.md".to_string(),
+            "ProjectsOld/Plan.md".to_string(),
+        ];
+        let moves = relocated_notes(&notes, "Projects", "Archive/Projects");
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves["Projects/Plan.md"], "Archive/Projects/Plan.md");
+        assert_eq!(
+            moves["Projects/Sub/Todo.md"],
+            "Archive/Projects/Sub/Todo.md"
+        );
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().to_path_buf();
+        fs::write(dir.join("Plan.md"), "").unwrap();
+        fs::write(dir.join("Plan (2).md"), "").unwrap();
+        assert_eq!(
+            available_path(&dir.join("Plan.md")),
+            dir.join("Plan (3).md")
+        );
+        assert_eq!(available_path(&dir.join("New.md")), dir.join("New.md"));
+    }
 }
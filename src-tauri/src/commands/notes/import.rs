@@ -0,0 +1,122 @@
+pub mod notion;
+pub mod obsidian;
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::notes::fs::available_path;
+use crate::commands::sandbox::{relative_to, resolve_within};
+
+// 取り込めなかった・形を変えて取り込んだもの
+#[derive(Debug, Serialize)]
+pub struct ImportIssue {
+    // 取り込んだ先のフォルダからの相対パス
+    pub path: String,
+    pub line: Option<u32>,
+    pub kind: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportReport {
+    // 取り込んだ先のフォルダ (ルートからの相対パス)
+    pub folder: String,
+    pub notes: usize,
+    pub attachments: usize,
+    // 書き換えたリンク・埋め込みの数
+    pub converted: usize,
+    // 作成したタスクの数
+    pub tasks: usize,
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    pub fn issue(&mut self, path: &str, line: Option<u32>, kind: &str, detail: &str) {
+        self.issues.push(ImportIssue {
+            path: path.to_string(),
+            line,
+            kind: kind.to_string(),
+            detail: detail.to_string(),
+        });
+    }
+}
+
+// 取り込み元のフォルダ。ノートのルートと重なっているものは取り込まない
+pub fn check_source(root: &Path, source: &str) -> Result<PathBuf, String> {
+    let source = PathBuf::from(source)
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    if !source.is_dir() {
+        return Err("Import source must be a folder".to_string());
+    }
+    if source.starts_with(root) || root.starts_with(&source) {
+        return Err("Import source overlaps the notes folder".to_string());
+    }
+    Ok(source)
+}
+
+// 取り込み先のフォルダを作る。空でないフォルダがあれば "name (2)" にする
+pub fn prepare_folder(root: &Path, folder: &str) -> Result<(PathBuf, String), String> {
+    let path = resolve_within(root, folder)?;
+    if relative_to(root, &path)?.is_empty() {
+        return Err("Import folder must not be the notes root".to_string());
+    }
+    let in_use = fs::read_dir(&path)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(path.exists());
+    let path = if in_use { available_path(&path) } else { path };
+    fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+    let relative = relative_to(root, &path)?;
+    Ok((path, relative))
+}
+
+fn collect(base: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // .obsidian などの設定フォルダは取り込まない
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => collect(base, &path, files),
+            Ok(t) if t.is_file() => {
+                if let Ok(relative) = path.strip_prefix(base) {
+                    files.push(
+                        relative
+                            .components()
+                            .map(|c| c.as_os_str().to_string_lossy().to_string())
+                            .collect::<Vec<_>>()
+                            .join("/"),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// dir 以下のファイルの相対パス ("/" 区切り、ソート済み)
+pub fn collect_files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    collect(dir, dir, &mut files);
+    files.sort();
+    files
+}
+
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+pub fn copy_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::copy(from, to).map(|_| ()).map_err(|e| e.to_string())
+}
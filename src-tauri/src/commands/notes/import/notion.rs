@@ -0,0 +1,531 @@
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::attachments::encode_link;
+use crate::commands::notes::import::{
+    check_source, collect_files, copy_file, prepare_folder, write_file, ImportReport,
+};
+use crate::commands::notes::links::{
+    normalize, parent_dir, parse_all_links, relative_link, LinkKind,
+};
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::notes::tasks::promote_checkbox;
+use crate::commands::search::indexer::sync_notes;
+use crate::AppState;
+
+// CSV で書き出されたデータベースの取り込み方
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseMode {
+    // Markdown の表にする
+    #[default]
+    Table,
+    // チェックボックスの一覧にして、それぞれをタスクにする
+    Tasks,
+}
+
+// Notion のチェックボックス列の値
+const CHECKED: [&str; 2] = ["Yes", "No"];
+// ステータス列で完了とみなす値
+const DONE_STATUSES: [&str; 4] = ["Done", "Completed", "Complete", "完了"];
+
+fn is_notion_id(text: &str) -> bool {
+    text.len() == 32 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// "Page 1a2b...(32桁).md" -> "Page.md"、"Table 1a2b..._all.csv" -> "Table.md"
+fn clean_name(name: &str, is_dir: bool) -> String {
+    let (stem, extension) = match name.rfind('.') {
+        Some(i) if !is_dir && i > 0 => (&name[..i], &name[i..]),
+        _ => (name, ""),
+    };
+    let extension = if extension.eq_ignore_ascii_case(".csv") {
+        ".md"
+    } else {
+        extension
+    };
+    let stem = if extension == ".md" {
+        stem.strip_suffix("_all").unwrap_or(stem)
+    } else {
+        stem
+    };
+    let stem = match stem.rsplit_once(' ') {
+        Some((title, id)) if is_notion_id(id) && !title.trim().is_empty() => title.trim(),
+        _ => stem,
+    };
+    format!("{}{}", stem, extension)
+}
+
+// 書き出し元のパスから取り込み先のパスを決める (ID を外して同じ名前になったものには番号を付ける)
+#[derive(Default)]
+struct Renamer {
+    dirs: HashMap<String, String>,
+    taken: HashSet<String>,
+}
+
+impl Renamer {
+    fn unique(&mut self, parent: &str, name: &str, is_dir: bool) -> (String, bool) {
+        let join = |name: &str| match parent {
+            "" => name.to_string(),
+            _ => format!("{}/{}", parent, name),
+        };
+        let (stem, extension) = match name.rfind('.') {
+            Some(i) if !is_dir && i > 0 => (&name[..i], &name[i..]),
+            _ => (name, ""),
+        };
+        let mut candidate = join(name);
+        let mut n = 2;
+        while self.taken.contains(&candidate.to_lowercase()) {
+            candidate = join(&format!("{} ({}){}", stem, n, extension));
+            n += 1;
+        }
+        self.taken.insert(candidate.to_lowercase());
+        (candidate, n > 2)
+    }
+
+    fn dir(&mut self, old: &str) -> String {
+        if old.is_empty() {
+            return String::new();
+        }
+        if let Some(new) = self.dirs.get(old) {
+            return new.clone();
+        }
+        let parent = self.dir(parent_dir(old));
+        let name = old.rsplit('/').next().unwrap_or(old);
+        let (new, _) = self.unique(&parent, &clean_name(name, true), true);
+        self.dirs.insert(old.to_string(), new.clone());
+        new
+    }
+
+    // 番号を付けた場合は true も返す
+    fn file(&mut self, old: &str) -> (String, bool) {
+        let parent = self.dir(parent_dir(old));
+        let name = old.rsplit('/').next().unwrap_or(old);
+        self.unique(&parent, &clean_name(name, false), false)
+    }
+}
+
+// 引用符 ("..."、"" は " ) とセル内の改行に対応した CSV の読み込み
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c != '"' {
+                field.push(c);
+            } else if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+// 完了を表す列
+enum DoneColumn {
+    // チェックボックス列 ("Yes" / "No")
+    Checkbox(usize),
+    // ステータス列
+    Status(usize),
+}
+
+impl DoneColumn {
+    // チェックボックス列、なければステータス列
+    fn find(header: &[String], rows: &[Vec<String>]) -> Option<Self> {
+        let values = |column: usize| rows.iter().filter_map(move |row| row.get(column));
+        let checkbox = (1..header.len()).find(|&column| {
+            values(column).all(|v| v.is_empty() || CHECKED.contains(&v.as_str()))
+                && values(column).any(|v| !v.is_empty())
+        });
+        if let Some(column) = checkbox {
+            return Some(DoneColumn::Checkbox(column));
+        }
+        header
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case("status") || h == "ステータス")
+            .map(DoneColumn::Status)
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            DoneColumn::Checkbox(column) | DoneColumn::Status(column) => *column,
+        }
+    }
+
+    fn is_done(&self, value: &str) -> bool {
+        match self {
+            DoneColumn::Checkbox(_) => value == "Yes",
+            DoneColumn::Status(_) => DONE_STATUSES.contains(&value),
+        }
+    }
+}
+
+// タスクにするチェックボックス
+#[derive(Debug, PartialEq)]
+pub struct TaskCheckboxes {
+    // ルートからの相対パス
+    pub path: String,
+    pub lines: Vec<u32>,
+}
+
+struct Database<'a> {
+    // 取り込み先のパス (ルートではなく取り込み先のフォルダからの相対パス)
+    path: &'a str,
+    title: &'a str,
+    rows: Vec<Vec<String>>,
+    // 行のページ (タイトル -> 取り込み先のパス)
+    pages: HashMap<String, String>,
+}
+
+impl Database<'_> {
+    fn page_link(&self, title: &str) -> Option<String> {
+        self.pages
+            .get(title)
+            .map(|page| encode_link(&relative_link(parent_dir(self.path), page)))
+    }
+
+    fn to_table(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title);
+        let Some(header) = self.rows.first() else {
+            return out;
+        };
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        out.push_str(&line(header.iter().map(|h| table_cell(h)).collect()));
+        out.push_str(&line(vec!["---".to_string(); header.len()]));
+        for row in &self.rows[1..] {
+            let cells = (0..header.len())
+                .map(|column| {
+                    let value = row.get(column).map(String::as_str).unwrap_or("");
+                    match (column == 0).then(|| self.page_link(value)).flatten() {
+                        Some(link) => format!("[{}]({})", table_cell(value), link),
+                        None => table_cell(value),
+                    }
+                })
+                .collect();
+            out.push_str(&line(cells));
+        }
+        out
+    }
+
+    // チェックボックスの一覧と、それぞれの行番号を返す
+    fn to_tasks(&self) -> Option<(String, Vec<u32>)> {
+        let header = self.rows.first()?;
+        let rows = &self.rows[1..];
+        let done_column = DoneColumn::find(header, rows)?;
+        let done = done_column.index();
+        let mut out = format!("# {}\n\n", self.title);
+        let mut lines = Vec::new();
+        for row in rows {
+            let title = row.first().map(|t| t.trim()).unwrap_or("");
+            if title.is_empty() {
+                continue;
+            }
+            let checked = row.get(done).is_some_and(|v| done_column.is_done(v));
+            lines.push(out.lines().count() as u32 + 1);
+            out.push_str(&format!(
+                "- [{}] {}\n",
+                if checked { "x" } else { " " },
+                title.replace('\n', " ")
+            ));
+            // 他の列は子の項目にする
+            for (column, value) in row.iter().enumerate() {
+                if column == 0 || column == done || value.is_empty() {
+                    continue;
+                }
+                let name = header.get(column).map(String::as_str).unwrap_or("");
+                out.push_str(&format!("  - {}: {}\n", name, value.replace('\n', " ")));
+            }
+            if let Some(link) = self.page_link(title) {
+                out.push_str(&format!("  - [Page]({})\n", link));
+            }
+        }
+        Some((out, lines))
+    }
+}
+
+// Markdown のリンク先を取り込み先のパスに書き換える
+fn convert_page(
+    old: &str,
+    new: &str,
+    content: &str,
+    paths: &HashMap<String, String>,
+    report: &mut ImportReport,
+) -> String {
+    let mut replacements = Vec::new();
+    for link in parse_all_links(content) {
+        if link.kind != LinkKind::Markdown {
+            continue;
+        }
+        let target = normalize(&format!("{}/{}", parent_dir(old), link.target));
+        let Some(target) = target.and_then(|t| paths.get(&t)) else {
+            if link.target.ends_with(".md") || link.target.ends_with(".csv") {
+                report.issue(new, Some(link.line), "missing_link", &link.target);
+            }
+            continue;
+        };
+        let text = encode_link(&relative_link(parent_dir(new), target));
+        if content[link.range.clone()] != text {
+            replacements.push((link.range, text));
+        }
+    }
+    report.converted += replacements.len();
+    let mut converted = content.to_string();
+    for (range, text) in replacements.into_iter().rev() {
+        converted.replace_range(range, &text);
+    }
+    converted
+}
+
+// root は ensure_notes_dir で得た実体のパス、folder は取り込み先 (ルートからの相対パス)
+// タスクにするチェックボックスも返す
+pub fn import_export(
+    root: &Path,
+    source: &Path,
+    folder: &str,
+    mode: DatabaseMode,
+) -> Result<(ImportReport, Vec<TaskCheckboxes>), String> {
+    let (target, folder) = prepare_folder(root, folder)?;
+    let mut files = collect_files(source);
+    // "_all.csv" (すべての列) があれば、表示中のビューだけの CSV は使わない
+    let all: HashSet<String> = files
+        .iter()
+        .filter_map(|f| f.strip_suffix("_all.csv").map(|s| format!("{}.csv", s)))
+        .collect();
+    files.retain(|f| !all.contains(f));
+
+    let mut report = ImportReport {
+        folder: folder.clone(),
+        ..Default::default()
+    };
+    let mut renamer = Renamer::default();
+    let mut paths = HashMap::new();
+    for file in &files {
+        let (new, renamed) = renamer.file(file);
+        if renamed {
+            report.issue(&new, None, "renamed", file);
+        }
+        paths.insert(file.clone(), new);
+    }
+    // ページからのリンクは "_all" の付かない CSV を指している
+    for csv in &all {
+        let with_all = format!("{}_all.csv", csv.trim_end_matches(".csv"));
+        if let Some(new) = paths.get(&with_all).cloned() {
+            paths.insert(csv.clone(), new);
+        }
+    }
+
+    let mut tasks = Vec::new();
+    for file in &files {
+        let new = &paths[file];
+        let source_path = source.join(file);
+        let destination = target.join(new);
+        let lower = file.to_lowercase();
+        if lower.ends_with(".md") {
+            let content = fs::read_to_string(&source_path).map_err(|e| e.to_string())?;
+            let converted = convert_page(file, new, &content, &paths, &mut report);
+            write_file(&destination, converted.as_bytes())?;
+            report.notes += 1;
+        } else if lower.ends_with(".csv") {
+            let text = fs::read_to_string(&source_path).map_err(|e| e.to_string())?;
+            // 行のページは CSV と同じ名前のフォルダに書き出されている
+            let stem = &file[..file.len() - 4];
+            let folder_old = stem.strip_suffix("_all").unwrap_or(stem);
+            let pages = files
+                .iter()
+                .filter(|f| parent_dir(f) == folder_old && f.ends_with(".md"))
+                .map(|f| {
+                    let page = &paths[f];
+                    let name = page.rsplit('/').next().unwrap_or(page);
+                    (name.trim_end_matches(".md").to_string(), page.clone())
+                })
+                .collect();
+            let title = new
+                .rsplit('/')
+                .next()
+                .unwrap_or(new)
+                .trim_end_matches(".md");
+            let database = Database {
+                path: new,
+                title,
+                rows: parse_csv(&text),
+                pages,
+            };
+            let converted = match mode {
+                DatabaseMode::Table => database.to_table(),
+                DatabaseMode::Tasks => match database.to_tasks() {
+                    Some((content, lines)) => {
+                        tasks.push(TaskCheckboxes {
+                            path: format!("{}/{}", folder, new),
+                            lines,
+                        });
+                        content
+                    }
+                    None => {
+                        report.issue(new, None, "not_a_task_list", "Imported as a table");
+                        database.to_table()
+                    }
+                },
+            };
+            write_file(&destination, converted.as_bytes())?;
+            report.notes += 1;
+        } else {
+            copy_file(&source_path, &destination)?;
+            report.attachments += 1;
+        }
+    }
+    Ok((report, tasks))
+}
+
+// source_path は Notion の書き出し (Markdown & CSV) を展開したフォルダ
+#[tauri::command]
+pub async fn import_notion_export(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    source_path: String,
+    folder: Option<String>,
+    databases: Option<DatabaseMode>,
+) -> Result<ImportReport, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let source = check_source(&root, &source_path)?;
+    let folder = folder.unwrap_or_else(|| "Notion".to_string());
+    let (mut report, tasks) =
+        import_export(&root, &source, &folder, databases.unwrap_or_default())?;
+    sync_notes(&state.pool, &root).await?;
+    for checkboxes in tasks {
+        for line in checkboxes.lines {
+            promote_checkbox(&state.pool, &root, &checkboxes.path, line).await?;
+            report.tasks += 1;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID1: &str = "0123456789abcdef0123456789abcdef";
+    const ID2: &str = "fedcba9876543210fedcba9876543210";
+    const ID3: &str = "00112233445566778899aabbccddeeff";
+
+    #[test]
+    fn test_clean_name() {
+        assert_eq!(clean_name(&format!("Page {}.md", ID1), false), "Page.md");
+        assert_eq!(
+            clean_name(&format!("Tasks {}_all.csv", ID1), false),
+            "Tasks.md"
+        );
+        assert_eq!(clean_name(&format!("v1.2 {}", ID1), true), "v1.2");
+        assert_eq!(clean_name("image.png", false), "image.png");
+        assert_eq!(clean_name(ID1, true), ID1);
+    }
+
+    #[test]
+    fn test_import_export() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let export = base.join("Export");
+        let home = format!("Home {}", ID1);
+        let tasks = format!("Tasks {}", ID2);
+        fs::create_dir_all(export.join(&home).join(&tasks)).unwrap();
+        let root = base.join("notes");
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        fs::write(
+            export.join(format!("{}.md", home)),
+            format!(
+                "# Home\n\n[Tasks](Home%20{ID1}/Tasks%20{ID2}.csv)\n\
+                 [Report](Home%20{ID1}/Tasks%20{ID2}/Report%20{ID3}.md)\n\
+                 ![](Home%20{ID1}/chart.png)\n[Gone](Gone%20{ID3}.md)\n"
+            ),
+        )
+        .unwrap();
+        fs::write(export.join(&home).join("chart.png"), b"\x89PNG").unwrap();
+        fs::write(
+            export.join(&home).join(format!("{}.csv", tasks)),
+            "Name,Done\nonly visible,No\n",
+        )
+        .unwrap();
+        fs::write(
+            export.join(&home).join(format!("{}_all.csv", tasks)),
+            "\u{feff}Name,Done,Notes\nReport,Yes,\"with, comma\"\n\"a|b\",No,\"two\nlines\"\n",
+        )
+        .unwrap();
+        fs::write(
+            export
+                .join(&home)
+                .join(&tasks)
+                .join(format!("Report {}.md", ID3)),
+            "# Report\n",
+        )
+        .unwrap();
+
+        let (report, promote) =
+            import_export(&root, &export, "Notion", DatabaseMode::Table).unwrap();
+        assert_eq!(report.folder, "Notion");
+        assert_eq!(report.notes, 3);
+        assert_eq!(report.attachments, 1);
+        assert_eq!(report.converted, 3);
+        assert!(promote.is_empty());
+        let kinds: Vec<&str> = report.issues.iter().map(|i| i.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["missing_link"]);
+
+        assert_eq!(
+            fs::read_to_string(root.join("Notion/Home.md")).unwrap(),
+            format!(
+                "# Home\n\n[Tasks](Home/Tasks.md)\n[Report](Home/Tasks/Report.md)\n\
+                 ![](Home/chart.png)\n[Gone](Gone%20{ID3}.md)\n"
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("Notion/Home/Tasks.md")).unwrap(),
+            "# Tasks\n\n| Name | Done | Notes |\n| --- | --- | --- |\n\
+             | [Report](Tasks/Report.md) | Yes | with, comma |\n| a\\|b | No | two<br>lines |\n"
+        );
+        assert!(root.join("Notion/Home/chart.png").exists());
+
+        let (_, promote) = import_export(&root, &export, "Notion", DatabaseMode::Tasks).unwrap();
+        assert_eq!(
+            promote,
+            vec![TaskCheckboxes {
+                path: "Notion (2)/Home/Tasks.md".to_string(),
+                lines: vec![3, 6],
+            }]
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("Notion (2)/Home/Tasks.md")).unwrap(),
+            "# Tasks\n\n- [x] Report\n  - Notes: with, comma\n  - [Page](Tasks/Report.md)\n\
+             - [ ] a|b\n  - Notes: two lines\n"
+        );
+    }
+}
@@ -0,0 +1,553 @@
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::attachments::{encode_link, IMAGE_EXTENSIONS};
+use crate::commands::notes::import::{
+    check_source, collect_files, copy_file, prepare_folder, write_file, ImportReport,
+};
+use crate::commands::notes::links::{
+    list_notes, normalize, parent_dir, percent_decode, relative_link, resolve_link, LinkKind,
+};
+use crate::commands::notes::meta::parse_front_matter;
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::search::indexer::sync_notes;
+use crate::AppState;
+
+// プラグインが描画するコードブロック (コードブロックのまま残る)
+const PLUGIN_BLOCKS: [&str; 4] = ["dataview", "dataviewjs", "query", "tasks"];
+
+// 取り込む Vault と取り込み先
+pub struct Vault<'a> {
+    // Vault 内のファイル一覧 (Vault からの相対パス、ソート済み)
+    pub files: &'a [String],
+    // 取り込み先のフォルダ (ルートからの相対パス)
+    pub folder: &'a str,
+    // 取り込んだ後のルートのノート一覧 (ソート済み)
+    pub notes: &'a [String],
+}
+
+impl Vault<'_> {
+    fn destination(&self, file: &str) -> String {
+        format!("{}/{}", self.folder, file)
+    }
+
+    // [[name]] のままだと取り込み先で別のノート (ルートにある同じ名前のノートなど) を指す場合は、
+    // フォルダからのパスに書き換える
+    fn note_link(
+        &self,
+        path: &str,
+        name: &str,
+        anchor: Option<&str>,
+        alias: &str,
+        file: &str,
+    ) -> Option<String> {
+        let destination = self.destination(file);
+        let resolved = resolve_link(
+            &self.destination(path),
+            LinkKind::Wiki,
+            name.trim(),
+            self.notes,
+        );
+        if resolved.as_deref() == Some(destination.as_str()) {
+            return None;
+        }
+        let qualified = destination.strip_suffix(".md").unwrap_or(&destination);
+        Some(match anchor {
+            Some(anchor) => format!("[[{}#{}|{}]]", qualified, anchor, alias),
+            None => format!("[[{}|{}]]", qualified, alias),
+        })
+    }
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// ![[image.png|300]] や ![[image.png|300x200]] の幅・高さ指定
+fn is_size(alias: &str) -> bool {
+    alias.starts_with(|c: char| c.is_ascii_digit())
+        && alias.chars().all(|c| c.is_ascii_digit() || c == 'x')
+}
+
+// Obsidian と同じく、パスか名前で Vault 内のファイルを探す
+// 同じ名前のものが複数あれば同じフォルダのもの、次に浅い場所のものを選ぶ
+fn resolve_file(name: &str, source: &str, files: &[String]) -> Option<String> {
+    let name = name.trim().trim_start_matches('/');
+    if let Some(path) = normalize(&format!("{}/{}", parent_dir(source), name)) {
+        if files.binary_search(&path).is_ok() {
+            return Some(path);
+        }
+    }
+    let mut wanted = vec![name.to_lowercase()];
+    if !name.ends_with(".md") {
+        wanted.push(format!("{}.md", name.to_lowercase()));
+    }
+    files
+        .iter()
+        .filter(|file| {
+            let file = file.to_lowercase();
+            wanted
+                .iter()
+                .any(|w| file == *w || file.ends_with(&format!("/{}", w)))
+        })
+        .min_by_key(|file| {
+            (
+                parent_dir(file) != parent_dir(source),
+                file.matches('/').count(),
+            )
+        })
+        .cloned()
+}
+
+// front matter (区切りの行を含む) と本文に分ける
+fn split_front_matter(content: &str) -> (&str, &str) {
+    if parse_front_matter(content).is_none() {
+        return ("", content);
+    }
+    let mut offset = 0;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        offset += line.len();
+        let text = line.trim_end();
+        if index > 0 && (text == "---" || text == "...") {
+            break;
+        }
+    }
+    content.split_at(offset)
+}
+
+// tags はいろいろな書き方ができるので "tags: [a, b]" にそろえる ("#" も外す)
+fn normalize_tags(front: &str) -> String {
+    let Some(parsed) = parse_front_matter(front) else {
+        return front.to_string();
+    };
+    let mut out = String::with_capacity(front.len());
+    let mut in_tags = false;
+    let mut written = false;
+    for (index, line) in front.split_inclusive('\n').enumerate() {
+        let text = line.trim_end();
+        let delimiter = text == "---" || text == "...";
+        let top_level = index > 0 && !delimiter && !line.starts_with([' ', '\t', '-']);
+        if top_level {
+            let key = text.split(':').next().unwrap_or("").trim().to_lowercase();
+            in_tags = key == "tags" || key == "tag";
+            if in_tags {
+                if !written {
+                    out.push_str(&format!("tags: [{}]\n", parsed.tags.join(", ")));
+                    written = true;
+                }
+                continue;
+            }
+        } else if in_tags && !delimiter {
+            continue;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+fn has_block_id(line: &str) -> bool {
+    line.trim_end()
+        .rsplit(' ')
+        .next()
+        .and_then(|word| word.strip_prefix('^'))
+        .is_some_and(|id| {
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+// このアプリでは表示が変わるもの
+fn check_line(path: &str, line: &str, line_no: u32, report: &mut ImportReport) {
+    let detail = line.trim();
+    if line.contains("%%") {
+        report.issue(path, Some(line_no), "comment", detail);
+    }
+    if line.contains("<%") {
+        report.issue(path, Some(line_no), "templater", detail);
+    }
+    if line.trim_start().starts_with("> [!") {
+        report.issue(path, Some(line_no), "callout", detail);
+    }
+    if has_block_id(line) {
+        report.issue(path, Some(line_no), "block_id", detail);
+    }
+}
+
+// [[...]] / ![[...]] の書き換え
+fn convert_wiki(
+    path: &str,
+    inner: &str,
+    embed: bool,
+    line_no: u32,
+    vault: &Vault,
+    report: &mut ImportReport,
+) -> String {
+    let original = format!("{}[[{}]]", if embed { "!" } else { "" }, inner);
+    let (target, alias) = match inner.split_once('|') {
+        Some((target, alias)) => (target, Some(alias)),
+        None => (inner, None),
+    };
+    let (name, anchor) = match target.split_once('#') {
+        Some((name, anchor)) => (name, Some(anchor)),
+        None => (target, None),
+    };
+    if anchor.is_some_and(|a| a.starts_with('^')) {
+        report.issue(path, Some(line_no), "block_reference", &original);
+    }
+    if name.trim().is_empty() {
+        return original;
+    }
+
+    match resolve_file(name, path, vault.files) {
+        // 画像などの添付ファイルは Markdown のリンクにする
+        Some(file) if !file.ends_with(".md") => {
+            report.converted += 1;
+            let link = encode_link(&relative_link(parent_dir(path), &file));
+            let file_name = file.rsplit('/').next().unwrap_or(&file);
+            let label = |default: &str| {
+                alias
+                    .filter(|a| !is_size(a))
+                    .unwrap_or(default)
+                    .replace(['[', ']'], "")
+            };
+            if embed && IMAGE_EXTENSIONS.contains(&extension(&file).as_str()) {
+                format!("![{}]({})", label(""), link)
+            } else {
+                format!("[{}]({})", label(file_name), link)
+            }
+        }
+        None if !extension(name).is_empty() && extension(name) != "md" => {
+            report.issue(path, Some(line_no), "missing_file", &original);
+            original
+        }
+        file => {
+            let qualified = file.and_then(|file| {
+                vault.note_link(path, name, anchor, alias.unwrap_or(target), &file)
+            });
+            // ノートの埋め込み (中身の表示) はできないのでリンクにする
+            if embed {
+                report.issue(path, Some(line_no), "note_embed", &original);
+            }
+            match qualified {
+                Some(link) => {
+                    report.converted += 1;
+                    link
+                }
+                None if embed => {
+                    report.converted += 1;
+                    format!("[[{}]]", inner)
+                }
+                None => original,
+            }
+        }
+    }
+}
+
+// [text](dest) の dest。Vault のルートからのパスで書かれていれば、ノートからの相対パスにする
+fn convert_destination(
+    path: &str,
+    inner: &str,
+    files: &[String],
+    report: &mut ImportReport,
+) -> String {
+    let (dest, title) = match inner.split_once(' ') {
+        Some((dest, title)) => (dest, Some(title)),
+        None => (inner, None),
+    };
+    if dest.is_empty() || dest.contains("://") || dest.starts_with(['#', '<']) {
+        return inner.to_string();
+    }
+    let (file, anchor) = match dest.split_once('#') {
+        Some((file, anchor)) => (file, Some(anchor)),
+        None => (dest, None),
+    };
+    let decoded = percent_decode(file);
+    let exists = |p: &String| files.binary_search(p).is_ok();
+    if normalize(&format!("{}/{}", parent_dir(path), decoded)).is_some_and(|p| exists(&p)) {
+        return inner.to_string();
+    }
+    let Some(file) = normalize(&decoded).filter(exists) else {
+        return inner.to_string();
+    };
+
+    report.converted += 1;
+    let mut link = encode_link(&relative_link(parent_dir(path), &file));
+    if let Some(anchor) = anchor {
+        link.push('#');
+        link.push_str(anchor);
+    }
+    if let Some(title) = title {
+        link.push(' ');
+        link.push_str(title);
+    }
+    link
+}
+
+fn convert_line(
+    path: &str,
+    line: &str,
+    line_no: u32,
+    vault: &Vault,
+    report: &mut ImportReport,
+) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        // インラインコードの中はそのまま
+        if c == '`' {
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            let end = rest[ticks..]
+                .find(&rest[..ticks])
+                .map(|end| ticks * 2 + end)
+                .unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        let embed = rest.starts_with("![[");
+        if embed || rest.starts_with("[[") {
+            let start = if embed { 3 } else { 2 };
+            if let Some(end) = rest[start..].find("]]") {
+                let inner = &rest[start..start + end];
+                out.push_str(&convert_wiki(path, inner, embed, line_no, vault, report));
+                rest = &rest[start + end + 2..];
+                continue;
+            }
+        }
+        if rest.starts_with("](") {
+            if let Some(end) = rest.find(')') {
+                out.push_str("](");
+                out.push_str(&convert_destination(
+                    path,
+                    &rest[2..end],
+                    vault.files,
+                    report,
+                ));
+                out.push(')');
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// path は Vault からの相対パス
+pub fn convert_note(path: &str, content: &str, vault: &Vault, report: &mut ImportReport) -> String {
+    let (front, body) = split_front_matter(content);
+    let mut out = normalize_tags(front);
+    let offset = out.lines().count() as u32;
+    let mut fence: Option<char> = None;
+    for (index, raw) in body.split_inclusive('\n').enumerate() {
+        let line_no = offset + index as u32 + 1;
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+        if let Some(c) = fence {
+            if trimmed.starts_with(&c.to_string().repeat(3)) {
+                fence = None;
+            }
+            out.push_str(raw);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = trimmed.chars().next();
+            let info = trimmed.trim_start_matches(['`', '~']).trim();
+            if let Some(lang) = info
+                .split_whitespace()
+                .next()
+                .filter(|lang| PLUGIN_BLOCKS.contains(lang))
+            {
+                report.issue(path, Some(line_no), "plugin_block", lang);
+            }
+            out.push_str(raw);
+            continue;
+        }
+        check_line(path, line, line_no, report);
+        out.push_str(&convert_line(path, line, line_no, vault, report));
+        out.push_str(&raw[line.len()..]);
+    }
+    out
+}
+
+// root は ensure_notes_dir で得た実体のパス、folder は取り込み先 (ルートからの相対パス)
+pub fn import_vault(root: &Path, vault: &Path, folder: &str) -> Result<ImportReport, String> {
+    let (target, folder) = prepare_folder(root, folder)?;
+    let files = collect_files(vault);
+    // 取り込んだ後のノート一覧で、リンクが取り込んだノートを指すか確かめる
+    let mut notes = list_notes(root)?;
+    notes.extend(
+        files
+            .iter()
+            .filter(|file| file.ends_with(".md"))
+            .map(|file| format!("{}/{}", folder, file)),
+    );
+    notes.sort();
+    let context = Vault {
+        files: &files,
+        folder: &folder,
+        notes: &notes,
+    };
+    let mut report = ImportReport::default();
+    for file in &files {
+        let source = vault.join(file);
+        let destination = target.join(file);
+        let unsupported = extension(file) == "canvas" || file.ends_with(".excalidraw.md");
+        if unsupported {
+            report.issue(file, None, "unsupported_file", "Copied without conversion");
+        } else if file.ends_with(".md") {
+            if let Ok(content) = fs::read_to_string(&source) {
+                let converted = convert_note(file, &content, &context, &mut report);
+                write_file(&destination, converted.as_bytes())?;
+                report.notes += 1;
+                continue;
+            }
+            report.issue(file, None, "unreadable", "Copied without conversion");
+        }
+        copy_file(&source, &destination)?;
+        report.attachments += 1;
+    }
+    report.folder = folder;
+    Ok(report)
+}
+
+// folder を省略すると Vault のフォルダ名で取り込む
+#[tauri::command]
+pub async fn import_obsidian_vault(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    vault_path: String,
+    folder: Option<String>,
+) -> Result<ImportReport, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let vault = check_source(&root, &vault_path)?;
+    let folder = folder.unwrap_or_else(|| {
+        vault
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Obsidian".to_string())
+    });
+    let report = import_vault(&root, &vault, &folder)?;
+    sync_notes(&state.pool, &root).await?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_vault() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let vault = base.join("Vault");
+        for dir in [".obsidian", "Daily", "Projects", "attachments", "assets"] {
+            fs::create_dir_all(vault.join(dir)).unwrap();
+        }
+        let root = base.join("notes");
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(vault.join(".obsidian/app.json"), "{}").unwrap();
+        fs::write(vault.join("Daily/2024-01-01.md"), "daily").unwrap();
+        fs::write(vault.join("attachments/pic one.png"), b"\x89PNG").unwrap();
+        fs::write(vault.join("assets/doc.pdf"), b"%PDF").unwrap();
+        fs::write(vault.join("Board.canvas"), "{}").unwrap();
+        fs::write(
+            vault.join("Projects/Plan.md"),
+            "---\ntitle: Plan\ntags:\n  - \"#project\"\n  - work\n---\n# Plan\n\
+             ![[pic one.png|300]]\n\
+             See [[doc.pdf]] and ![[2024-01-01]].\n\
+             ![](attachments/pic%20one.png)\n\
+             Ref [[2024-01-01#^abc123]]\n\
+             > [!note] Callout\n\
+             `[[not a link]]`\n\
+             ```dataview\nLIST\n```\n\
+             Last line ^blk1\n",
+        )
+        .unwrap();
+
+        let vault = vault.canonicalize().unwrap();
+        let report = import_vault(&root, &vault, "Vault").unwrap();
+        assert_eq!(report.folder, "Vault");
+        assert_eq!(report.notes, 2);
+        assert_eq!(report.attachments, 3);
+        assert_eq!(report.converted, 4);
+        let kinds: Vec<(&str, Option<u32>)> = report
+            .issues
+            .iter()
+            .map(|i| (i.kind.as_str(), i.line))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("unsupported_file", None),
+                ("note_embed", Some(7)),
+                ("block_reference", Some(9)),
+                ("callout", Some(10)),
+                ("plugin_block", Some(12)),
+                ("block_id", Some(15)),
+            ]
+        );
+
+        let plan = fs::read_to_string(root.join("Vault/Projects/Plan.md")).unwrap();
+        assert_eq!(
+            plan,
+            "---\ntitle: Plan\ntags: [project, work]\n---\n# Plan\n\
+             ![](../attachments/pic%20one.png)\n\
+             See [doc.pdf](../assets/doc.pdf) and [[2024-01-01]].\n\
+             ![](../attachments/pic%20one.png)\n\
+             Ref [[2024-01-01#^abc123]]\n\
+             > [!note] Callout\n\
+             `[[not a link]]`\n\
+             ```dataview\nLIST\n```\n\
+             Last line ^blk1\n"
+        );
+        assert!(root.join("Vault/attachments/pic one.png").exists());
+        assert!(!root.join("Vault/.obsidian").exists());
+
+        // 同じ名前のフォルダがあれば別の名前で取り込む
+        let again = import_vault(&root, &vault, "Vault").unwrap();
+        assert_eq!(again.folder, "Vault (2)");
+        assert!(check_source(&root, root.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_import_vault_qualifies_links_shadowed_by_root_notes() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let vault = base.join("Vault");
+        fs::create_dir_all(vault.join("Projects")).unwrap();
+        let root = base.join("notes");
+        fs::create_dir_all(&root).unwrap();
+        let root = root.canonicalize().unwrap();
+        // ルートに同じ名前のノートがある
+        fs::write(root.join("Index.md"), "root index").unwrap();
+        fs::write(vault.join("Index.md"), "vault index").unwrap();
+        fs::write(vault.join("Plan.md"), "plan").unwrap();
+        fs::write(
+            vault.join("Projects/Task.md"),
+            "[[Index]] [[Index#Goals|goals]] ![[Index]] [[Plan]]\n",
+        )
+        .unwrap();
+
+        let vault = vault.canonicalize().unwrap();
+        let report = import_vault(&root, &vault, "Vault").unwrap();
+        assert_eq!(report.converted, 3);
+        let task = fs::read_to_string(root.join("Vault/Projects/Task.md")).unwrap();
+        assert_eq!(
+            task,
+            "[[Vault/Index|Index]] [[Vault/Index#Goals|goals]] [[Vault/Index|Index]] [[Plan]]\n"
+        );
+
+        // 書き換えたリンクは取り込んだノートを指す
+        let notes = list_notes(&root).unwrap();
+        let resolve =
+            |target| resolve_link("Vault/Projects/Task.md", LinkKind::Wiki, target, &notes);
+        assert_eq!(resolve("Vault/Index").as_deref(), Some("Vault/Index.md"));
+        assert_eq!(resolve("Index").as_deref(), Some("Index.md"));
+    }
+}
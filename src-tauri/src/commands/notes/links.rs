@@ -0,0 +1,566 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::notes::meta::load_note_paths;
+use crate::commands::sandbox::{check_relative, relative_to};
+use crate::commands::search::indexer::collect_notes;
+use crate::AppState;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    // [[Note Name]]
+    Wiki,
+    // [text](relative/path.md)
+    Markdown,
+}
+
+impl LinkKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::Wiki => "wiki",
+            LinkKind::Markdown => "markdown",
+        }
+    }
+
+    fn parse(kind: &str) -> Self {
+        if kind == "markdown" {
+            LinkKind::Markdown
+        } else {
+            LinkKind::Wiki
+        }
+    }
+}
+
+// ノート本文中のリンク
+#[derive(Debug, PartialEq)]
+pub struct ParsedLink {
+    pub kind: LinkKind,
+    // 書かれているリンク先 (見出し・別名を除き、%XX はデコード済み)
+    pub target: String,
+    pub line: u32,
+    // 本文中でリンク先が書かれている範囲 (書き換え用)
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteLink {
+    pub source: String,
+    pub target: String,
+    pub kind: LinkKind,
+    pub line: u32,
+    // リンク先のノート (ルートからの相対パス)。見つからなければ None
+    pub resolved: Option<String>,
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
+
+fn is_external(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with('#')
+}
+
+// 拡張子なし、または .md のものだけをノートへのリンクとみなす (画像などは除く)
+fn is_note_target(target: &str) -> bool {
+    match Path::new(target).extension().and_then(|e| e.to_str()) {
+        None => true,
+        Some(ext) => ext.eq_ignore_ascii_case("md"),
+    }
+}
+
+fn parse_wiki_links(
+    line: &str,
+    offset: usize,
+    line_no: u32,
+    notes_only: bool,
+    links: &mut Vec<ParsedLink>,
+) {
+    let mut pos = 0;
+    while let Some(start) = line[pos..].find("[[") {
+        let inner_start = pos + start + 2;
+        let Some(len) = line[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &line[inner_start..inner_start + len];
+        // [[名前#見出し|別名]] の「名前」だけを対象にする
+        let name_len = inner.find(['#', '|']).unwrap_or(inner.len());
+        let name = inner[..name_len].trim();
+        if !name.is_empty() && (!notes_only || is_note_target(name)) {
+            let lead = inner[..name_len].len() - inner[..name_len].trim_start().len();
+            let begin = offset + inner_start + lead;
+            links.push(ParsedLink {
+                kind: LinkKind::Wiki,
+                target: name.to_string(),
+                line: line_no,
+                range: begin..begin + name.len(),
+            });
+        }
+        pos = inner_start + len + 2;
+    }
+}
+
+fn parse_markdown_links(
+    line: &str,
+    offset: usize,
+    line_no: u32,
+    notes_only: bool,
+    links: &mut Vec<ParsedLink>,
+) {
+    let mut pos = 0;
+    while let Some(found) = line[pos..].find("](") {
+        let open = pos + found + 2;
+        pos = open;
+        let rest = &line[open..];
+        // <path with spaces> の形式にも対応する
+        let (begin, raw) = if let Some(stripped) = rest.strip_prefix('<') {
+            match stripped.find('>') {
+                Some(end) => (open + 1, &stripped[..end]),
+                None => continue,
+            }
+        } else {
+            let end = rest.find([')', ' ']).unwrap_or(rest.len());
+            (open, &rest[..end])
+        };
+        // #見出し は範囲に含めない
+        let path = &raw[..raw.find('#').unwrap_or(raw.len())];
+        if path.is_empty() || is_external(raw) {
+            continue;
+        }
+        let target = percent_decode(path);
+        if notes_only && Path::new(&target).extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        links.push(ParsedLink {
+            kind: LinkKind::Markdown,
+            target,
+            line: line_no,
+            range: offset + begin..offset + begin + path.len(),
+        });
+    }
+}
+
+// コードブロック (```) の中は対象外
+pub fn parse_links(content: &str) -> Vec<ParsedLink> {
+    scan_links(content, true)
+}
+
+// 画像・添付ファイルへのリンク (![[image.png]] や ![](attachments/a.png)) も含めて返す
+pub fn parse_all_links(content: &str) -> Vec<ParsedLink> {
+    scan_links(content, false)
+}
+
+fn scan_links(content: &str, notes_only: bool) -> Vec<ParsedLink> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    let mut in_code = false;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        } else if !in_code {
+            let line_no = index as u32 + 1;
+            parse_wiki_links(line, offset, line_no, notes_only, &mut links);
+            parse_markdown_links(line, offset, line_no, notes_only, &mut links);
+        }
+        offset += line.len();
+    }
+    links
+}
+
+pub fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map(|i| &path[..i]).unwrap_or("")
+}
+
+fn stem(path: &str) -> &str {
+    let name = &path[path.rfind('/').map(|i| i + 1).unwrap_or(0)..];
+    name.strip_suffix(".md").unwrap_or(name)
+}
+
+// "a/./b/../c.md" -> "a/c.md" (ルートより上に出る場合は None)
+pub fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+// from_dir から to へのリンク用の相対パス
+pub fn relative_link(from_dir: &str, to: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|p| !p.is_empty()).collect();
+    let to: Vec<&str> = to.split('/').collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+// notes はルートからの相対パスの一覧 (ソート済み)
+pub fn resolve_link(
+    source: &str,
+    kind: LinkKind,
+    target: &str,
+    notes: &[String],
+) -> Option<String> {
+    match kind {
+        LinkKind::Markdown => {
+            let joined = if let Some(absolute) = target.strip_prefix('/') {
+                absolute.to_string()
+            } else {
+                format!("{}/{}", parent_dir(source), target)
+            };
+            let path = normalize(&joined)?;
+            notes.iter().find(|note| **note == path).cloned()
+        }
+        LinkKind::Wiki => {
+            let name = target.strip_suffix(".md").unwrap_or(target);
+            // [[Folder/Name]] はルートからのパス、なければ末尾が一致するノート
+            if name.contains('/') {
+                let path = format!("{}.md", normalize(name)?).to_lowercase();
+                let suffix = format!("/{}", path);
+                return notes
+                    .iter()
+                    .filter(|note| {
+                        let note = note.to_lowercase();
+                        note == path || note.ends_with(&suffix)
+                    })
+                    .min_by_key(|note| note.len())
+                    .cloned();
+            }
+            // 同じ名前のノートが複数あれば、同じフォルダのもの、次に浅い場所のものを選ぶ
+            let name = name.to_lowercase();
+            notes
+                .iter()
+                .filter(|note| stem(note).to_lowercase() == name)
+                .min_by_key(|note| {
+                    (
+                        parent_dir(note) != parent_dir(source),
+                        note.matches('/').count(),
+                    )
+                })
+                .cloned()
+        }
+    }
+}
+
+async fn init_link_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS note_links (
+            source TEXT NOT NULL,
+            target TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            line INTEGER NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_note_links_source ON note_links(source)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// リンク先の解決は他のノートの増減で変わるため、書かれたままの形で保存し、引くときに解決する
+pub async fn index_links(
+    pool: &SqlitePool,
+    source: &str,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    init_link_table(pool).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM note_links WHERE source = ?")
+        .bind(source)
+        .execute(&mut *tx)
+        .await?;
+    for link in parse_links(content) {
+        sqlx::query("INSERT INTO note_links (source, target, kind, line) VALUES (?, ?, ?, ?)")
+            .bind(source)
+            .bind(&link.target)
+            .bind(link.kind.as_str())
+            .bind(link.line)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await
+}
+
+pub async fn remove_links(pool: &SqlitePool, source: &str) -> Result<(), sqlx::Error> {
+    init_link_table(pool).await?;
+    sqlx::query("DELETE FROM note_links WHERE source = ?")
+        .bind(source)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn clear_links(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    init_link_table(pool).await?;
+    sqlx::query("DELETE FROM note_links").execute(pool).await?;
+    Ok(())
+}
+
+// 索引済みの全リンクを、現在のノート一覧で解決して返す
+pub async fn load_links(pool: &SqlitePool, notes: &[String]) -> Result<Vec<NoteLink>, sqlx::Error> {
+    init_link_table(pool).await?;
+    let rows =
+        sqlx::query("SELECT source, target, kind, line FROM note_links ORDER BY source, line")
+            .fetch_all(pool)
+            .await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let source: String = row.get("source");
+            let target: String = row.get("target");
+            let kind = LinkKind::parse(row.get("kind"));
+            let resolved = resolve_link(&source, kind, &target, notes);
+            NoteLink {
+                source,
+                target,
+                kind,
+                line: row.get("line"),
+                resolved,
+            }
+        })
+        .collect())
+}
+
+pub fn list_notes(root: &Path) -> Result<Vec<String>, String> {
+    let mut paths = Vec::new();
+    collect_notes(root, &mut paths);
+    let mut notes = paths
+        .iter()
+        .map(|path| relative_to(root, path))
+        .collect::<Result<Vec<_>, _>>()?;
+    notes.sort();
+    Ok(notes)
+}
+
+// ノートの移動・名前変更・複製に合わせてリンクを書き換える
+// moves は (元, 移動・複製後) の相対パス、notes_before は操作前のノート一覧
+// copied の場合は複製したノートの中のリンクだけを書き換える (元のノートへのリンクはそのまま)
+// 書き換えたノートの (移動・複製後の) パスを返す
+pub fn rewrite_links(
+    root: &Path,
+    notes_before: &[String],
+    moves: &HashMap<String, String>,
+    copied: bool,
+) -> Result<Vec<String>, String> {
+    let moved_back: HashMap<&str, &str> = moves
+        .iter()
+        .map(|(old, new)| (new.as_str(), old.as_str()))
+        .collect();
+    let mut rewritten = Vec::new();
+
+    let sources = if copied {
+        let mut sources: Vec<String> = moves.values().cloned().collect();
+        sources.sort();
+        sources
+    } else {
+        list_notes(root)?
+    };
+    for source in sources {
+        let old_source = moved_back.get(source.as_str()).copied().unwrap_or(&source);
+        let source_moved = old_source != source;
+        let path = root.join(&source);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let mut replacements = Vec::new();
+        for link in parse_links(&content) {
+            let Some(old_target) = resolve_link(old_source, link.kind, &link.target, notes_before)
+            else {
+                continue;
+            };
+            let new_target = moves.get(&old_target).unwrap_or(&old_target);
+            let text = match link.kind {
+                LinkKind::Wiki if new_target == &old_target => continue,
+                LinkKind::Wiki if link.target.contains('/') => new_target
+                    .strip_suffix(".md")
+                    .unwrap_or(new_target)
+                    .to_string(),
+                LinkKind::Wiki => stem(new_target).to_string(),
+                LinkKind::Markdown if new_target == &old_target && !source_moved => continue,
+                LinkKind::Markdown => {
+                    let relative = relative_link(parent_dir(&source), new_target);
+                    // <...> で囲まれていないリンクでは空白をエスケープする
+                    let bracketed = content[..link.range.start].ends_with('<');
+                    if bracketed {
+                        relative
+                    } else {
+                        relative.replace(' ', "%20")
+                    }
+                }
+            };
+            if content[link.range.clone()] != text {
+                replacements.push((link.range, text));
+            }
+        }
+
+        if replacements.is_empty() {
+            continue;
+        }
+        let mut updated = content.clone();
+        for (range, text) in replacements.into_iter().rev() {
+            updated.replace_range(range, &text);
+        }
+        fs::write(&path, updated).map_err(|e| e.to_string())?;
+        rewritten.push(source);
+    }
+    Ok(rewritten)
+}
+
+// 索引は監視・保存のたびに更新されるので、ここではフォルダを走査しない
+async fn links_for(state: &State<'_, AppState>) -> Result<Vec<NoteLink>, String> {
+    let notes = load_note_paths(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    load_links(&state.pool, &notes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_backlinks(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<NoteLink>, String> {
+    check_relative(&path)?;
+    Ok(links_for(&state)
+        .await?
+        .into_iter()
+        .filter(|link| link.resolved.as_deref() == Some(path.as_str()) && link.source != path)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_outgoing_links(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<NoteLink>, String> {
+    check_relative(&path)?;
+    Ok(links_for(&state)
+        .await?
+        .into_iter()
+        .filter(|link| link.source == path)
+        .collect())
+}
+
+// リンク先のノートが見つからないリンクの一覧
+#[tauri::command]
+pub async fn get_unresolved_links(state: State<'_, AppState>) -> Result<Vec<NoteLink>, String> {
+    Ok(links_for(&state)
+        .await?
+        .into_iter()
+        .filter(|link| link.resolved.is_none())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_resolve_links() {
+        let content = "See [[Plan#Goals|the plan]] and [notes](../Archive/Old%20Notes.md#top).\n\
+                       ```\n[[Ignored]]\n```\n![image](pic.png) [site](https://example.com/a.md)\n";
+        let links = parse_links(content);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target, "Plan");
+        assert_eq!(&content[links[0].range.clone()], "Plan");
+        assert_eq!(links[1].target, "../Archive/Old Notes.md");
+        assert_eq!(
+            &content[links[1].range.clone()],
+            "../Archive/Old%20Notes.md"
+        );
+
+        let notes = vec![
+            "Archive/Old Notes.md".to_string(),
+            "Projects/Plan.md".to_string(),
+            "Projects/Sub/Plan.md".to_string(),
+        ];
+        let source = "Projects/Today.md";
+        assert_eq!(
+            resolve_link(source, LinkKind::Wiki, "plan", &notes).as_deref(),
+            Some("Projects/Plan.md")
+        );
+        assert_eq!(
+            resolve_link(source, LinkKind::Wiki, "Sub/Plan", &notes).as_deref(),
+            Some("Projects/Sub/Plan.md")
+        );
+        assert_eq!(
+            resolve_link(source, LinkKind::Markdown, &links[1].target, &notes).as_deref(),
+            Some("Archive/Old Notes.md")
+        );
+        assert_eq!(
+            resolve_link(source, LinkKind::Wiki, "Missing", &notes),
+            None
+        );
+        assert_eq!(
+            relative_link("Projects", "Archive/New.md"),
+            "../Archive/New.md"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_links() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        fs::write(
+            root.join("Index.md"),
+            "[[Plan|plan]] [doc](Projects/Plan.md#goals) [[Other]]",
+        )
+        .unwrap();
+        fs::write(root.join("Projects/Plan.md"), "[home](../Index.md)").unwrap();
+        let root = root.canonicalize().unwrap();
+        let notes_before = list_notes(&root).unwrap();
+
+        fs::rename(
+            root.join("Projects/Plan.md"),
+            root.join("Projects/Road map.md"),
+        )
+        .unwrap();
+        let moves = HashMap::from([(
+            "Projects/Plan.md".to_string(),
+            "Projects/Road map.md".to_string(),
+        )]);
+        let rewritten = rewrite_links(&root, &notes_before, &moves, false).unwrap();
+
+        assert_eq!(rewritten, vec!["Index.md".to_string()]);
+        assert_eq!(
+            fs::read_to_string(root.join("Index.md")).unwrap(),
+            "[[Road map|plan]] [doc](Projects/Road%20map.md#goals) [[Other]]"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("Projects/Road map.md")).unwrap(),
+            "[home](../Index.md)"
+        );
+    }
+}
@@ -0,0 +1,608 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+
+use crate::commands::sandbox::check_relative;
+use crate::commands::settings::timezone::now_for_db;
+use crate::AppState;
+
+// ノート先頭の YAML front matter のうちアプリが使う項目
+#[derive(Debug, Default, PartialEq)]
+pub struct FrontMatter {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub created: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+// ノートのメタ情報 (本文はファイル、メタ情報は DB)
+#[derive(Debug, Serialize)]
+pub struct NoteMeta {
+    pub id: String,
+    pub path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    // front matter の created (書かれたまま)
+    pub created: Option<String>,
+    pub content_hash: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+    value.to_string()
+}
+
+// [a, b] / "a, b" / "a b" のどれでも受け付ける
+fn inline_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+    let separator = if inner.contains(',') { ',' } else { ' ' };
+    inner
+        .split(separator)
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim_start_matches('#').to_string();
+        if !tag.is_empty() && !cleaned.contains(&tag) {
+            cleaned.push(tag);
+        }
+    }
+    cleaned
+}
+
+// "key: value" と、値が空のキーに続く "- item" の形の一覧だけを読む簡易パーサー
+// front matter がなければ None
+pub fn parse_front_matter(content: &str) -> Option<FrontMatter> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.lines();
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut entries: Vec<(String, String, Vec<String>)> = Vec::new();
+    let mut closed = false;
+    for line in lines {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            closed = true;
+            break;
+        }
+        let stripped = trimmed.trim_start();
+        if stripped.is_empty() || stripped.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = stripped.strip_prefix('-') {
+            if item.is_empty() || item.starts_with(' ') {
+                if let Some((_, _, items)) = entries.last_mut() {
+                    let item = unquote(item);
+                    if !item.is_empty() {
+                        items.push(item);
+                    }
+                }
+                continue;
+            }
+        }
+        // インデントされた行は入れ子の値なので読み飛ばす
+        if line.starts_with([' ', '\t']) {
+            continue;
+        }
+        if let Some((key, value)) = trimmed.split_once(':') {
+            entries.push((
+                key.trim().to_lowercase(),
+                value.trim().to_string(),
+                Vec::new(),
+            ));
+        }
+    }
+    if !closed {
+        return None;
+    }
+
+    let mut front = FrontMatter::default();
+    for (key, value, items) in entries {
+        let list = || {
+            if items.is_empty() {
+                inline_list(&value)
+            } else {
+                items.clone()
+            }
+        };
+        let scalar = Some(unquote(&value)).filter(|v| !v.is_empty());
+        match key.as_str() {
+            "id" | "uuid" => front.id = scalar,
+            "title" => front.title = scalar,
+            "created" | "date" => front.created = front.created.or(scalar),
+            "tags" | "tag" => front.tags.extend(list()),
+            "aliases" | "alias" => front.aliases.extend(list()),
+            _ => {}
+        }
+    }
+    front.tags = clean_tags(front.tags);
+    Some(front)
+}
+
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+pub async fn init_notes_meta_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS notes_meta (
+            id TEXT PRIMARY KEY,
+            path TEXT NOT NULL,
+            title TEXT NOT NULL,
+            tags TEXT NOT NULL DEFAULT '[]',
+            aliases TEXT NOT NULL DEFAULT '[]',
+            created TEXT,
+            content_hash TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            deleted_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_notes_meta_path ON notes_meta(path) WHERE deleted_at IS NULL",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn json_list(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+fn row_to_meta(row: &SqliteRow) -> Result<NoteMeta, sqlx::Error> {
+    Ok(NoteMeta {
+        id: row.try_get("id")?,
+        path: row.try_get("path")?,
+        title: row.try_get("title")?,
+        tags: json_list(row.try_get("tags")?),
+        aliases: json_list(row.try_get("aliases")?),
+        created: row.try_get("created")?,
+        content_hash: row.try_get("content_hash")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        deleted_at: row.try_get("deleted_at")?,
+    })
+}
+
+pub async fn load_meta_by_path(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Option<NoteMeta>, sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    sqlx::query("SELECT * FROM notes_meta WHERE path = ? AND deleted_at IS NULL")
+        .bind(path)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row_to_meta(&row))
+        .transpose()
+}
+
+// このノートが以前どの id だったかを探す
+// front matter の id、次に同じ内容で同じ同期の中でなくなったノート (外部での名前変更・移動) の順
+// vanished はその同期でなくなったノートのパス
+async fn find_previous_id(
+    pool: &SqlitePool,
+    root: &Path,
+    front: &FrontMatter,
+    hash: &str,
+    vanished: &HashSet<String>,
+) -> Result<Option<String>, sqlx::Error> {
+    let is_gone = |row: &SqliteRow| {
+        let path: String = row.get("path");
+        let deleted_at: Option<String> = row.get("deleted_at");
+        deleted_at.is_some() || !root.join(path).exists()
+    };
+
+    if let Some(id) = &front.id {
+        let row = sqlx::query("SELECT path, deleted_at FROM notes_meta WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        // 複製されたノートが同じ id を持っている場合は新しい id にする
+        return Ok(match row {
+            Some(row) if !is_gone(&row) => None,
+            _ => Some(id.clone()),
+        });
+    }
+
+    if vanished.is_empty() {
+        return Ok(None);
+    }
+    let rows = sqlx::query(
+        "SELECT id, path FROM notes_meta WHERE content_hash = ? AND deleted_at IS NULL
+         ORDER BY datetime(updated_at) DESC",
+    )
+    .bind(hash)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .iter()
+        .find(|row| vanished.contains(&row.get::<String, _>("path")))
+        .map(|row| row.get("id")))
+}
+
+// ノートの内容からメタ情報を更新する (root は ensure_notes_dir で得た実体のパス)
+// vanished は sync_notes でなくなったノートのパス (それ以外から呼ぶときは空)
+pub async fn index_meta(
+    pool: &SqlitePool,
+    root: &Path,
+    path: &str,
+    content: &str,
+    vanished: &HashSet<String>,
+) -> Result<(), sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    let front = parse_front_matter(content).unwrap_or_default();
+    let hash = content_hash(content);
+    let title = front.title.clone().unwrap_or_else(|| {
+        Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let tags = serde_json::to_string(&front.tags).unwrap_or_else(|_| "[]".to_string());
+    let aliases = serde_json::to_string(&front.aliases).unwrap_or_else(|_| "[]".to_string());
+    let now = now_for_db();
+
+    let existing = sqlx::query("SELECT id FROM notes_meta WHERE path = ? AND deleted_at IS NULL")
+        .bind(path)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.get::<String, _>("id"));
+    // 空のノートは内容では見分けられないので、なくなったノートの id を引き継がない
+    let none = HashSet::new();
+    let vanished = if content.trim().is_empty() {
+        &none
+    } else {
+        vanished
+    };
+    let id = match existing {
+        Some(id) => Some(id),
+        None => find_previous_id(pool, root, &front, &hash, vanished).await?,
+    };
+
+    match id {
+        Some(id) => {
+            // 内容か場所が変わったときだけ updated_at を進める
+            sqlx::query(
+                "INSERT INTO notes_meta (id, path, title, tags, aliases, created, content_hash, created_at, updated_at, deleted_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, NULL)
+                 ON CONFLICT(id) DO UPDATE SET
+                    updated_at = CASE
+                        WHEN notes_meta.content_hash = excluded.content_hash
+                             AND notes_meta.path = excluded.path
+                             AND notes_meta.deleted_at IS NULL
+                        THEN notes_meta.updated_at ELSE excluded.updated_at END,
+                    path = excluded.path,
+                    title = excluded.title,
+                    tags = excluded.tags,
+                    aliases = excluded.aliases,
+                    created = excluded.created,
+                    content_hash = excluded.content_hash,
+                    deleted_at = NULL",
+            )
+            .bind(&id)
+            .bind(path)
+            .bind(&title)
+            .bind(&tags)
+            .bind(&aliases)
+            .bind(&front.created)
+            .bind(&hash)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query(
+                "INSERT INTO notes_meta (id, path, title, tags, aliases, created, content_hash, created_at, updated_at, deleted_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, NULL)",
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(path)
+            .bind(&title)
+            .bind(&tags)
+            .bind(&aliases)
+            .bind(&front.created)
+            .bind(&hash)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// ノートがなくなったら論理削除する (同期のため行は残す)
+pub async fn mark_meta_deleted(pool: &SqlitePool, path: &str) -> Result<(), sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    sqlx::query("UPDATE notes_meta SET deleted_at = ?, updated_at = ? WHERE path = ? AND deleted_at IS NULL")
+        .bind(now_for_db())
+        .bind(now_for_db())
+        .bind(path)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// アプリ内での名前変更・移動では id をそのまま新しいパスに付け替える
+// (フォルダの場合は中のノートも)
+pub async fn move_meta(pool: &SqlitePool, from: &str, to: &str) -> Result<(), sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    sqlx::query(
+        "UPDATE notes_meta
+         SET path = ? || substr(path, length(?) + 1), updated_at = ?
+         WHERE deleted_at IS NULL AND (path = ? OR substr(path, 1, length(?) + 1) = ? || '/')",
+    )
+    .bind(to)
+    .bind(from)
+    .bind(now_for_db())
+    .bind(from)
+    .bind(from)
+    .bind(from)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 索引済みのノートのパス (ルートからの相対パス、ソート済み)
+pub async fn load_note_paths(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    let rows = sqlx::query("SELECT path FROM notes_meta WHERE deleted_at IS NULL ORDER BY path")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(|row| row.get("path")).collect())
+}
+
+// 索引の作り直し後、実在しないノートのメタ情報を論理削除する
+pub async fn prune_missing_meta(pool: &SqlitePool, root: &Path) -> Result<(), sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    let rows = sqlx::query("SELECT path FROM notes_meta WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await?;
+    for row in rows {
+        let path: String = row.get("path");
+        if !root.join(&path).is_file() {
+            mark_meta_deleted(pool, &path).await?;
+        }
+    }
+    Ok(())
+}
+
+// tag で絞り込み、日付 (front matter の created、なければ登録日) の範囲で絞り込む
+pub async fn query_notes_meta(
+    pool: &SqlitePool,
+    tag: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<NoteMeta>, sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    let mut builder: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT * FROM notes_meta WHERE deleted_at IS NULL");
+    if let Some(tag) = tag {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM json_each(notes_meta.tags) WHERE value = ")
+            .push_bind(tag.trim_start_matches('#').to_string())
+            .push(")");
+    }
+    // 日付は YYYY-MM-DD で比べる
+    if let Some(from) = from {
+        builder
+            .push(" AND substr(COALESCE(created, created_at), 1, 10) >= ")
+            .push_bind(from.to_string());
+    }
+    if let Some(to) = to {
+        builder
+            .push(" AND substr(COALESCE(created, created_at), 1, 10) <= ")
+            .push_bind(to.to_string());
+    }
+    builder.push(" ORDER BY COALESCE(created, created_at) DESC, path");
+
+    builder
+        .build()
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(row_to_meta)
+        .collect()
+}
+
+pub async fn load_tag_counts(pool: &SqlitePool) -> Result<Vec<TagCount>, sqlx::Error> {
+    init_notes_meta_table(pool).await?;
+    let rows = sqlx::query(
+        "SELECT tag.value AS tag, COUNT(*) AS count
+         FROM notes_meta, json_each(notes_meta.tags) AS tag
+         WHERE notes_meta.deleted_at IS NULL
+         GROUP BY tag.value
+         ORDER BY count DESC, tag.value",
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(TagCount {
+                tag: row.try_get("tag")?,
+                count: row.try_get("count")?,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_note_meta(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Option<NoteMeta>, String> {
+    check_relative(&path)?;
+    load_meta_by_path(&state.pool, &path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_notes_meta(
+    state: State<'_, AppState>,
+    tag: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<NoteMeta>, String> {
+    query_notes_meta(&state.pool, tag.as_deref(), from.as_deref(), to.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_note_tags(state: State<'_, AppState>) -> Result<Vec<TagCount>, String> {
+    load_tag_counts(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::search::indexer::sync_notes;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::fs;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let content = "---\ntitle: \"Weekly plan\"\ntags: [work, '#plan']\ncreated: 2024-05-01\naliases:\n  - Plan\n  - Roadmap\nextra:\n  nested: value\n---\n# Body\n";
+        assert_eq!(
+            parse_front_matter(content),
+            Some(FrontMatter {
+                id: None,
+                title: Some("Weekly plan".to_string()),
+                tags: vec!["work".to_string(), "plan".to_string()],
+                created: Some("2024-05-01".to_string()),
+                aliases: vec!["Plan".to_string(), "Roadmap".to_string()],
+            })
+        );
+        assert_eq!(parse_front_matter("# No front matter\n---\n"), None);
+        assert_eq!(parse_front_matter("---\ntitle: open\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_meta_keeps_identity_across_renames() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let content = "---\ntags: work\n---\nbody";
+        fs::write(root.join("a.md"), content).unwrap();
+
+        let none = HashSet::new();
+        index_meta(&pool, &root, "a.md", content, &none)
+            .await
+            .unwrap();
+        let before = load_meta_by_path(&pool, "a.md").await.unwrap().unwrap();
+
+        // 別のエディタで名前を変えた場合
+        fs::rename(root.join("a.md"), root.join("b.md")).unwrap();
+        let vanished = HashSet::from(["a.md".to_string()]);
+        index_meta(&pool, &root, "b.md", content, &vanished)
+            .await
+            .unwrap();
+        mark_meta_deleted(&pool, "a.md").await.unwrap();
+
+        let after = load_meta_by_path(&pool, "b.md").await.unwrap().unwrap();
+        assert_eq!(after.id, before.id);
+        assert!(load_meta_by_path(&pool, "a.md").await.unwrap().is_none());
+
+        let tagged = query_notes_meta(&pool, Some("work"), None, None)
+            .await
+            .unwrap();
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].path, "b.md");
+
+        // 同じ内容でも、前の同期までに消えていたノートの id は引き継がない
+        fs::remove_file(root.join("b.md")).unwrap();
+        mark_meta_deleted(&pool, "b.md").await.unwrap();
+        fs::write(root.join("c.md"), content).unwrap();
+        index_meta(&pool, &root, "c.md", content, &none)
+            .await
+            .unwrap();
+        let copied = load_meta_by_path(&pool, "c.md").await.unwrap().unwrap();
+        assert_ne!(copied.id, before.id);
+    }
+
+    #[tokio::test]
+    async fn test_sync_does_not_reuse_ids_of_deleted_notes() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let root = root.canonicalize().unwrap();
+
+        // 空のノートを消してから別の空のノートを作る
+        fs::write(root.join("Old.md"), "").unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        let old = load_meta_by_path(&pool, "Old.md").await.unwrap().unwrap();
+        fs::remove_file(root.join("Old.md")).unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        fs::write(root.join("New.md"), "").unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        let new = load_meta_by_path(&pool, "New.md").await.unwrap().unwrap();
+        assert_ne!(new.id, old.id);
+
+        // 空のノートは同じ同期の中で入れ替わっても引き継がない
+        fs::rename(root.join("New.md"), root.join("Renamed.md")).unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        let renamed = load_meta_by_path(&pool, "Renamed.md")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(renamed.id, new.id);
+
+        // 中身のあるノートの名前変更は引き継ぐ
+        fs::write(root.join("Plan.md"), "# Plan\n").unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        let plan = load_meta_by_path(&pool, "Plan.md").await.unwrap().unwrap();
+        fs::rename(root.join("Plan.md"), root.join("Roadmap.md")).unwrap();
+        sync_notes(&pool, &root).await.unwrap();
+        let roadmap = load_meta_by_path(&pool, "Roadmap.md")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(roadmap.id, plan.id);
+    }
+}
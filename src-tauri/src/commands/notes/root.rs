@@ -0,0 +1,186 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::notes::trash::{init_trash_table, purge_trash_items, TRASH_DIR};
+use crate::commands::notes::watcher::watch_notes_root;
+use crate::commands::search::indexer::sync_notes;
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::AppState;
+
+// ノートのルートフォルダ (未設定ならドキュメントフォルダの NorunosNotes)
+pub const NOTES_ROOT_KEY: &str = "notes_root";
+const NOTES_DIR_NAME: &str = "NorunosNotes";
+
+#[derive(Debug, Serialize)]
+pub struct NotesRoot {
+    pub path: String,
+    pub is_default: bool,
+}
+
+// ルートを切り替えたときに移動したもの・移動できなかったもの (ルートからの相対パス)
+#[derive(Debug, Serialize, Default)]
+pub struct NotesRootMigration {
+    pub root: String,
+    pub moved: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+pub fn default_notes_root(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let base = app_handle
+        .path()
+        .document_dir()
+        .or_else(|_| app_handle.path().app_data_dir())
+        .map_err(|e| e.to_string())?;
+    Ok(base.join(NOTES_DIR_NAME))
+}
+
+async fn configured_root(pool: &SqlitePool) -> Result<Option<PathBuf>, String> {
+    let root: Option<String> = get_setting(pool, NOTES_ROOT_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(root.map(PathBuf::from))
+}
+
+pub async fn notes_root(app_handle: &AppHandle, pool: &SqlitePool) -> Result<PathBuf, String> {
+    match configured_root(pool).await? {
+        Some(root) => Ok(root),
+        None => default_notes_root(app_handle),
+    }
+}
+
+// ルートを作成し、実体のパス (シンボリックリンクを解決したもの) を返す
+pub async fn ensure_notes_dir(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+) -> Result<PathBuf, String> {
+    let path = notes_root(app_handle, pool).await?;
+    if !path.exists() {
+        fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+    }
+    path.canonicalize().map_err(|e| e.to_string())
+}
+
+// 別のドライブへの移動では rename できないため、コピーしてから消す
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::remove_dir(from)
+    } else {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    }
+}
+
+// old_root 直下の項目を new_root に移す (同名の項目がある場合は移さない)
+pub fn migrate_notes(old_root: &Path, new_root: &Path) -> Result<NotesRootMigration, String> {
+    let mut report = NotesRootMigration {
+        root: new_root.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    if !old_root.exists() {
+        return Ok(report);
+    }
+
+    for entry in fs::read_dir(old_root).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let target = new_root.join(entry.file_name());
+        if target.exists() {
+            report.skipped.push(name);
+            continue;
+        }
+        match move_path(&entry.path(), &target) {
+            Ok(()) => report.moved.push(name),
+            Err(_) => report.skipped.push(name),
+        }
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn get_notes_root(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<NotesRoot, String> {
+    let configured = configured_root(&state.pool).await?;
+    Ok(NotesRoot {
+        is_default: configured.is_none(),
+        path: notes_root(&app_handle, &state.pool)
+            .await?
+            .to_string_lossy()
+            .to_string(),
+    })
+}
+
+// ルートを切り替える (None で既定のフォルダに戻す)
+// 既存のフォルダ (Obsidian の Vault など) もそのまま指定できる
+// migrate が true の場合は、今のルートのノートを新しいルートに移す
+#[tauri::command]
+pub async fn set_notes_root(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: Option<String>,
+    migrate: bool,
+) -> Result<NotesRootMigration, String> {
+    let old_root = notes_root(&app_handle, &state.pool).await?;
+    let new_root = match &path {
+        Some(path) => PathBuf::from(path),
+        None => default_notes_root(&app_handle)?,
+    };
+    if !new_root.is_absolute() {
+        return Err("Notes root must be an absolute path".to_string());
+    }
+    fs::create_dir_all(&new_root).map_err(|e| e.to_string())?;
+
+    let old_canonical = old_root.canonicalize().ok();
+    let new_canonical = new_root.canonicalize().map_err(|e| e.to_string())?;
+
+    let report = match &old_canonical {
+        Some(old) if migrate && *old != new_canonical => {
+            if new_canonical.starts_with(old) || old.starts_with(&new_canonical) {
+                return Err(
+                    "Cannot migrate notes into a folder inside the current root".to_string()
+                );
+            }
+            migrate_notes(old, &new_canonical)?
+        }
+        _ => NotesRootMigration {
+            root: new_canonical.to_string_lossy().to_string(),
+            ..Default::default()
+        },
+    };
+
+    // 監視を始められないルートは保存しない
+    watch_notes_root(&app_handle, state.pool.clone(), new_canonical.clone())?;
+    let value = path.map(|_| new_canonical.to_string_lossy().to_string());
+    set_setting(&state.pool, NOTES_ROOT_KEY, &value)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // ゴミ箱を一緒に移さなかった場合は元に戻せないので、古いルートのゴミ箱を空にする
+    let switched = old_canonical.as_ref() != Some(&new_canonical);
+    if switched && !report.moved.iter().any(|name| name == TRASH_DIR) {
+        init_trash_table(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        purge_trash_items(
+            &state.pool,
+            old_canonical.as_deref().unwrap_or(&old_root),
+            None,
+        )
+        .await?;
+    }
+    // 検索・ノート情報を新しいルートの内容にする
+    sync_notes(&state.pool, &new_canonical).await?;
+    Ok(report)
+}
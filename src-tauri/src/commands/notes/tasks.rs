@@ -0,0 +1,533 @@
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::commands::notes::links::list_notes;
+use crate::commands::notes::meta::{load_meta_by_path, parse_front_matter};
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::notes::versions::snapshot_note;
+use crate::commands::sandbox::{relative_to, resolve_within};
+use crate::commands::search::indexer::index_note;
+use crate::commands::task::sql::task::{load_by_id, save_task};
+use crate::commands::task::task::Task;
+use crate::AppState;
+
+// ノート本文中の "- [ ] ..." の行
+#[derive(Debug, PartialEq)]
+pub struct Checkbox {
+    pub line: u32,
+    pub checked: bool,
+    pub text: String,
+    // "[ ]" の中の文字の位置 (書き換え用)
+    pub mark: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteTask {
+    pub path: String,
+    pub line: u32,
+    pub text: String,
+    pub completed: bool,
+    // タスクにしたものはそのタスクの id
+    pub task_id: Option<Uuid>,
+}
+
+// タスクにしたチェックボックス。行番号は編集でずれるので、本文も持っておいて探し直す
+struct TaskLink {
+    task_id: String,
+    note_id: String,
+    line: u32,
+    text: String,
+}
+
+// "- ", "* ", "+ ", "1. ", "1) " のあとの位置
+fn after_list_marker(line: &str) -> Option<usize> {
+    let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let rest = &line[indent..];
+    let marker = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 || !rest[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let spaces = rest[marker..].bytes().take_while(|b| *b == b' ').count();
+    if spaces == 0 {
+        return None;
+    }
+    Some(indent + marker + spaces)
+}
+
+// front matter とコードブロックの中は読まない
+pub fn parse_checkboxes(content: &str) -> Vec<Checkbox> {
+    let mut checkboxes = Vec::new();
+    let mut offset = 0;
+    let mut fence: Option<char> = None;
+    let mut in_front_matter = false;
+    let has_front_matter = parse_front_matter(content).is_some();
+    for (index, raw) in content.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        let start = offset;
+        offset += raw.len();
+
+        if index == 0 && has_front_matter {
+            in_front_matter = true;
+            continue;
+        }
+        if in_front_matter {
+            in_front_matter = line != "---" && line != "...";
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if let Some(c) = fence {
+            if trimmed.starts_with(&format!("{c}{c}{c}")) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = trimmed.chars().next();
+            continue;
+        }
+
+        let Some(position) = after_list_marker(line) else {
+            continue;
+        };
+        let rest = &line[position..];
+        let checked = match rest.get(..3) {
+            Some("[ ]") => false,
+            Some("[x]") | Some("[X]") => true,
+            _ => continue,
+        };
+        let text = &rest[3..];
+        if !text.is_empty() && !text.starts_with(' ') {
+            continue;
+        }
+        checkboxes.push(Checkbox {
+            line: index as u32 + 1,
+            checked,
+            text: text.trim().to_string(),
+            mark: start + position + 1,
+        });
+    }
+    checkboxes
+}
+
+// 同じ行の同じ本文、次に同じ本文で一番近い行、最後に同じ行のもの (本文だけ書き換えられた)
+fn locate<'a>(checkboxes: &'a [Checkbox], line: u32, text: &str) -> Option<&'a Checkbox> {
+    checkboxes
+        .iter()
+        .filter(|c| c.text == text)
+        .min_by_key(|c| c.line.abs_diff(line))
+        .or_else(|| checkboxes.iter().find(|c| c.line == line))
+}
+
+fn set_checked(content: &mut String, checkbox: &Checkbox, checked: bool) {
+    let mark = if checked { "x" } else { " " };
+    content.replace_range(checkbox.mark..checkbox.mark + 1, mark);
+}
+
+pub async fn init_note_task_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS note_task_links (
+            task_id TEXT PRIMARY KEY,
+            note_id TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            text TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_note_task_links_note ON note_task_links(note_id)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn row_to_link(row: &sqlx::sqlite::SqliteRow) -> TaskLink {
+    TaskLink {
+        task_id: row.get("task_id"),
+        note_id: row.get("note_id"),
+        line: row.get("line"),
+        text: row.get("text"),
+    }
+}
+
+async fn load_links_for_note(
+    pool: &SqlitePool,
+    note_id: &str,
+) -> Result<Vec<TaskLink>, sqlx::Error> {
+    init_note_task_table(pool).await?;
+    let rows = sqlx::query("SELECT * FROM note_task_links WHERE note_id = ?")
+        .bind(note_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(row_to_link).collect())
+}
+
+async fn load_link_for_task(
+    pool: &SqlitePool,
+    task_id: Uuid,
+) -> Result<Option<TaskLink>, sqlx::Error> {
+    init_note_task_table(pool).await?;
+    let row = sqlx::query("SELECT * FROM note_task_links WHERE task_id = ?")
+        .bind(task_id.to_string())
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.as_ref().map(row_to_link))
+}
+
+async fn save_link(pool: &SqlitePool, link: &TaskLink) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO note_task_links (task_id, note_id, line, text) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&link.task_id)
+    .bind(&link.note_id)
+    .bind(link.line)
+    .bind(&link.text)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn remove_link(pool: &SqlitePool, task_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM note_task_links WHERE task_id = ?")
+        .bind(task_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn is_note_task(pool: &SqlitePool, task_id: Uuid) -> Result<bool, sqlx::Error> {
+    Ok(load_link_for_task(pool, task_id).await?.is_some())
+}
+
+// ノートが保存・変更されたときに、タスクにしたチェックボックスの状態をタスクへ反映する
+pub async fn sync_note_tasks(
+    pool: &SqlitePool,
+    path: &str,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    let Some(meta) = load_meta_by_path(pool, path).await? else {
+        return Ok(());
+    };
+    let links = load_links_for_note(pool, &meta.id).await?;
+    if links.is_empty() {
+        return Ok(());
+    }
+    let checkboxes = parse_checkboxes(content);
+    for mut link in links {
+        let task_id = Uuid::parse_str(&link.task_id).unwrap_or_default();
+        let Some(mut task) = load_by_id(pool, task_id)
+            .await?
+            .filter(|t| t.deleted_at.is_none())
+        else {
+            remove_link(pool, &link.task_id).await?;
+            continue;
+        };
+        let Some(checkbox) = locate(&checkboxes, link.line, &link.text) else {
+            continue;
+        };
+        if checkbox.line != link.line || checkbox.text != link.text {
+            link.line = checkbox.line;
+            link.text = checkbox.text.clone();
+            save_link(pool, &link).await?;
+        }
+        if task.completed != checkbox.checked {
+            task.completed = checkbox.checked;
+            save_task(pool, &mut task).await?;
+        }
+    }
+    Ok(())
+}
+
+// タスクの完了状態をノートのチェックボックスへ書き戻す。書き換えたら true
+pub async fn write_task_completion(
+    pool: &SqlitePool,
+    root: &Path,
+    task: &Task,
+) -> Result<bool, String> {
+    let Some(mut link) = load_link_for_task(pool, task.id)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(false);
+    };
+    let row = sqlx::query("SELECT path FROM notes_meta WHERE id = ? AND deleted_at IS NULL")
+        .bind(&link.note_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(path) = row.map(|row| row.get::<String, _>("path")) else {
+        return Ok(false);
+    };
+    let file_path = resolve_within(root, &path)?;
+    let mut content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    let checkboxes = parse_checkboxes(&content);
+    let Some(checkbox) = locate(&checkboxes, link.line, &link.text) else {
+        return Ok(false);
+    };
+    if checkbox.checked == task.completed {
+        return Ok(false);
+    }
+    link.line = checkbox.line;
+    link.text = checkbox.text.clone();
+    let previous = content.clone();
+    set_checked(&mut content, checkbox, task.completed);
+    snapshot_note(pool, root, &path, &previous, &content)
+        .await
+        .map_err(|e| e.to_string())?;
+    fs::write(&file_path, &content).map_err(|e| e.to_string())?;
+    save_link(pool, &link).await.map_err(|e| e.to_string())?;
+    index_note(pool, root, &file_path).await?;
+    Ok(true)
+}
+
+async fn note_tasks(
+    pool: &SqlitePool,
+    path: &str,
+    content: &str,
+) -> Result<Vec<NoteTask>, sqlx::Error> {
+    let links = match load_meta_by_path(pool, path).await? {
+        Some(meta) => load_links_for_note(pool, &meta.id).await?,
+        None => Vec::new(),
+    };
+    let checkboxes = parse_checkboxes(content);
+    // 一つのチェックボックスに結び付けるタスクは一つだけ
+    let mut linked: Vec<(u32, Uuid)> = Vec::new();
+    for link in &links {
+        if let Some(checkbox) = locate(&checkboxes, link.line, &link.text) {
+            if let Ok(id) = Uuid::parse_str(&link.task_id) {
+                linked.push((checkbox.line, id));
+            }
+        }
+    }
+    Ok(checkboxes
+        .into_iter()
+        .map(|checkbox| NoteTask {
+            path: path.to_string(),
+            task_id: linked
+                .iter()
+                .find(|(line, _)| *line == checkbox.line)
+                .map(|(_, id)| *id),
+            line: checkbox.line,
+            text: checkbox.text,
+            completed: checkbox.checked,
+        })
+        .collect())
+}
+
+// path はノートかフォルダ (省略するとすべてのノート)
+#[tauri::command]
+pub async fn list_note_tasks(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: Option<String>,
+    include_completed: Option<bool>,
+) -> Result<Vec<NoteTask>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let target = resolve_within(&root, path.as_deref().unwrap_or(""))?;
+    let relative = relative_to(&root, &target)?;
+    let prefix = format!("{}/", relative);
+    let notes: Vec<String> = list_notes(&root)?
+        .into_iter()
+        .filter(|note| relative.is_empty() || *note == relative || note.starts_with(&prefix))
+        .collect();
+
+    let include_completed = include_completed.unwrap_or(false);
+    let mut tasks = Vec::new();
+    for note in notes {
+        let Ok(content) = fs::read_to_string(root.join(&note)) else {
+            continue;
+        };
+        let found = note_tasks(&state.pool, &note, &content)
+            .await
+            .map_err(|e| e.to_string())?;
+        tasks.extend(
+            found
+                .into_iter()
+                .filter(|t| include_completed || !t.completed),
+        );
+    }
+    Ok(tasks)
+}
+
+// チェックボックスをタスクにする (すでにタスクにしていればそのタスクを返す)
+pub async fn promote_checkbox(
+    pool: &SqlitePool,
+    root: &Path,
+    path: &str,
+    line: u32,
+) -> Result<Task, String> {
+    let file_path = resolve_within(root, path)?;
+    let relative = relative_to(root, &file_path)?;
+    let content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    let existing = note_tasks(pool, &relative, &content)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|t| t.line == line)
+        .ok_or_else(|| format!("No checkbox at {}:{}", relative, line))?;
+    if let Some(task_id) = existing.task_id {
+        if let Some(task) = load_by_id(pool, task_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|t| t.deleted_at.is_none())
+        {
+            return Ok(task);
+        }
+    }
+    if existing.text.is_empty() {
+        return Err("Checkbox has no text".to_string());
+    }
+
+    // ノートの id を確定させるため先に索引しておく
+    index_note(pool, root, &file_path).await?;
+    let meta = load_meta_by_path(pool, &relative)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Note is not indexed")?;
+
+    let mut task = Task::new();
+    task.description = existing.text.clone();
+    task.completed = existing.completed;
+    save_task(pool, &mut task)
+        .await
+        .map_err(|e| e.to_string())?;
+    save_link(
+        pool,
+        &TaskLink {
+            task_id: task.id.to_string(),
+            note_id: meta.id,
+            line,
+            text: existing.text,
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn promote_note_task(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    line: u32,
+) -> Result<Task, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    promote_checkbox(&state.pool, &root, &path, line).await
+}
+
+// 一覧からチェックを切り替える (タスクにしたものはタスクも更新される)
+#[tauri::command]
+pub async fn set_note_task_completed(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    line: u32,
+    completed: bool,
+) -> Result<(), String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let file_path = resolve_within(&root, &path)?;
+    let mut content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    let checkbox = parse_checkboxes(&content)
+        .into_iter()
+        .find(|c| c.line == line)
+        .ok_or_else(|| format!("No checkbox at {}:{}", path, line))?;
+    if checkbox.checked == completed {
+        return Ok(());
+    }
+    let previous = content.clone();
+    set_checked(&mut content, &checkbox, completed);
+    snapshot_note(
+        &state.pool,
+        &root,
+        &relative_to(&root, &file_path)?,
+        &previous,
+        &content,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    fs::write(&file_path, &content).map_err(|e| e.to_string())?;
+    index_note(&state.pool, &root, &file_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::notes::versions::load_note_versions;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[test]
+    fn test_parse_checkboxes() {
+        let content = "---\ntags: [a]\n- [ ] not a task\n---\n- [ ] first\n  * [x] nested\n1. [X] numbered\n- [] broken\n- [ ]no space\n```\n- [ ] code\n```\n+ [ ]\n";
+        let found: Vec<(u32, bool, String)> = parse_checkboxes(content)
+            .into_iter()
+            .map(|c| (c.line, c.checked, c.text))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (5, false, "first".to_string()),
+                (6, true, "nested".to_string()),
+                (7, true, "numbered".to_string()),
+                (13, false, String::new()),
+            ]
+        );
+        let first = &parse_checkboxes(content)[0];
+        assert_eq!(&content[first.mark - 1..first.mark + 2], "[ ]");
+    }
+
+    #[tokio::test]
+    async fn test_promote_and_sync() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        let root = root.canonicalize().unwrap();
+        let note = root.join("Plan.md");
+        fs::write(&note, "# Plan\n\n- [ ] write report\n- [ ] other\n").unwrap();
+
+        let task = promote_checkbox(&pool, &root, "Plan.md", 3).await.unwrap();
+        assert_eq!(task.description, "write report");
+        assert!(!task.completed);
+        let again = promote_checkbox(&pool, &root, "Plan.md", 3).await.unwrap();
+        assert_eq!(again.id, task.id);
+
+        // 行がずれてもチェックを付ければタスクが完了する
+        fs::write(
+            &note,
+            "# Plan\n\nintro\n\n- [x] write report\n- [ ] other\n",
+        )
+        .unwrap();
+        index_note(&pool, &root, &note).await.unwrap();
+        let mut synced = load_by_id(&pool, task.id).await.unwrap().unwrap();
+        assert!(synced.completed);
+
+        // タスク側で未完了に戻すとノートも書き換わる
+        synced.completed = false;
+        save_task(&pool, &mut synced).await.unwrap();
+        assert!(write_task_completion(&pool, &root, &synced).await.unwrap());
+        assert_eq!(
+            fs::read_to_string(&note).unwrap(),
+            "# Plan\n\nintro\n\n- [ ] write report\n- [ ] other\n"
+        );
+        // 書き換える前の内容は版として残る
+        let versions = load_note_versions(&pool, "Plan.md").await.unwrap();
+        assert_eq!(versions.len(), 1);
+        let listed = note_tasks(&pool, "Plan.md", &fs::read_to_string(&note).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(listed[0].task_id, Some(task.id));
+        assert_eq!(listed[1].task_id, None);
+    }
+}
@@ -0,0 +1,295 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::fs::available_path;
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::sandbox::{relative_to, resolve_within};
+use crate::commands::search::indexer::sync_notes;
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::settings::timezone::{now_for_db, to_db_datetime};
+use crate::AppState;
+
+pub const TRASH_DIR: &str = ".trash";
+pub const TRASH_POLICY_KEY: &str = "notes_trash_policy";
+
+// ゴミ箱に入れてから days 日を過ぎたものを自動で完全に削除する
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashPolicy {
+    pub enabled: bool,
+    pub days: u32,
+}
+
+impl Default for TrashPolicy {
+    fn default() -> Self {
+        TrashPolicy {
+            enabled: true,
+            days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrashItem {
+    pub id: String,
+    // 削除前のルートからの相対パス
+    pub original_path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub deleted_at: String,
+}
+
+pub async fn init_trash_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS notes_trash (
+            id TEXT PRIMARY KEY,
+            original_path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            is_dir BOOLEAN NOT NULL,
+            deleted_at TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ゴミ箱の中では .trash/<id>/<元の名前> に置く
+fn trashed_path(root: &Path, id: &str, name: &str) -> PathBuf {
+    root.join(TRASH_DIR).join(id).join(name)
+}
+
+// root は ensure_notes_dir で得た実体のパス、path は削除するファイル・フォルダ
+pub async fn move_to_trash(
+    pool: &SqlitePool,
+    root: &Path,
+    path: &Path,
+) -> Result<TrashItem, String> {
+    init_trash_table(pool).await.map_err(|e| e.to_string())?;
+    let original_path = relative_to(root, path)?;
+    if original_path.is_empty() || original_path.split('/').next() == Some(TRASH_DIR) {
+        return Err("Cannot move this item to the trash".to_string());
+    }
+
+    let item = TrashItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or("Invalid path")?,
+        is_dir: path.is_dir(),
+        original_path,
+        deleted_at: now_for_db(),
+    };
+    let target = trashed_path(root, &item.id, &item.name);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::rename(path, &target).map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        "INSERT INTO notes_trash (id, original_path, name, is_dir, deleted_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&item.id)
+    .bind(&item.original_path)
+    .bind(&item.name)
+    .bind(item.is_dir)
+    .bind(&item.deleted_at)
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(item)
+}
+
+pub async fn load_trash(pool: &SqlitePool) -> Result<Vec<TrashItem>, sqlx::Error> {
+    init_trash_table(pool).await?;
+    let rows = sqlx::query("SELECT * FROM notes_trash ORDER BY datetime(deleted_at) DESC")
+        .fetch_all(pool)
+        .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(TrashItem {
+                id: row.try_get("id")?,
+                original_path: row.try_get("original_path")?,
+                name: row.try_get("name")?,
+                is_dir: row.try_get("is_dir")?,
+                deleted_at: row.try_get("deleted_at")?,
+            })
+        })
+        .collect()
+}
+
+async fn load_trash_item(pool: &SqlitePool, id: &str) -> Result<TrashItem, String> {
+    load_trash(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| format!("Trash item not found: {}", id))
+}
+
+// 元の場所に戻す。同じ名前のものがあれば別の名前にし、戻した先の相対パスを返す
+pub async fn restore_from_trash(
+    pool: &SqlitePool,
+    root: &Path,
+    id: &str,
+) -> Result<String, String> {
+    let item = load_trash_item(pool, id).await?;
+    let source = trashed_path(root, &item.id, &item.name);
+    let target = available_path(&resolve_within(root, &item.original_path)?);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::rename(&source, &target).map_err(|e| e.to_string())?;
+    forget_trash_item(pool, root, &item.id).await?;
+    relative_to(root, &target)
+}
+
+async fn forget_trash_item(pool: &SqlitePool, root: &Path, id: &str) -> Result<(), String> {
+    let dir = root.join(TRASH_DIR).join(id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    sqlx::query("DELETE FROM notes_trash WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ids が None ならゴミ箱を空にする。削除した件数を返す
+pub async fn purge_trash_items(
+    pool: &SqlitePool,
+    root: &Path,
+    ids: Option<&[String]>,
+) -> Result<usize, String> {
+    let items = load_trash(pool).await.map_err(|e| e.to_string())?;
+    let mut purged = 0;
+    for item in items {
+        if ids.is_none_or(|ids| ids.contains(&item.id)) {
+            forget_trash_item(pool, root, &item.id).await?;
+            purged += 1;
+        }
+    }
+    Ok(purged)
+}
+
+pub async fn load_trash_policy(pool: &SqlitePool) -> Result<TrashPolicy, sqlx::Error> {
+    Ok(get_setting(pool, TRASH_POLICY_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+// 保存期間を過ぎたものを完全に削除する
+pub async fn purge_expired_trash(pool: &SqlitePool, root: &Path) -> Result<usize, String> {
+    let policy = load_trash_policy(pool).await.map_err(|e| e.to_string())?;
+    if !policy.enabled {
+        return Ok(0);
+    }
+    init_trash_table(pool).await.map_err(|e| e.to_string())?;
+    let cutoff = to_db_datetime(&(Utc::now() - Duration::days(policy.days as i64)));
+    let rows = sqlx::query("SELECT id FROM notes_trash WHERE datetime(deleted_at) < datetime(?)")
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    for row in &rows {
+        forget_trash_item(pool, root, row.get("id")).await?;
+    }
+    Ok(rows.len())
+}
+
+#[tauri::command]
+pub async fn list_trash(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashItem>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    purge_expired_trash(&state.pool, &root).await?;
+    load_trash(&state.pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn restore_trash_item(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let restored = restore_from_trash(&state.pool, &root, &id).await?;
+    sync_notes(&state.pool, &root).await?;
+    Ok(restored)
+}
+
+// ids を省略するとゴミ箱を空にする
+#[tauri::command]
+pub async fn purge_trash(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    purge_trash_items(&state.pool, &root, ids.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn get_trash_policy(state: State<'_, AppState>) -> Result<TrashPolicy, String> {
+    load_trash_policy(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_trash_policy(
+    state: State<'_, AppState>,
+    policy: TrashPolicy,
+) -> Result<TrashPolicy, String> {
+    set_setting(&state.pool, TRASH_POLICY_KEY, &policy)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_trash_and_restore() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        let root = root.canonicalize().unwrap();
+        fs::write(root.join("Projects/Plan.md"), "old").unwrap();
+
+        let item = move_to_trash(&pool, &root, &root.join("Projects/Plan.md"))
+            .await
+            .unwrap();
+        assert!(!root.join("Projects/Plan.md").exists());
+        assert_eq!(item.original_path, "Projects/Plan.md");
+
+        // 同じ名前のノートが作り直されていたら別名で戻す
+        fs::write(root.join("Projects/Plan.md"), "new").unwrap();
+        let restored = restore_from_trash(&pool, &root, &item.id).await.unwrap();
+        assert_eq!(restored, "Projects/Plan (2).md");
+        assert_eq!(fs::read_to_string(root.join(&restored)).unwrap(), "old");
+        assert!(load_trash(&pool).await.unwrap().is_empty());
+        assert!(!root.join(TRASH_DIR).join(&item.id).exists());
+
+        move_to_trash(&pool, &root, &root.join("Projects"))
+            .await
+            .unwrap();
+        assert_eq!(purge_trash_items(&pool, &root, None).await.unwrap(), 1);
+        assert_eq!(fs::read_dir(root.join(TRASH_DIR)).unwrap().count(), 0);
+    }
+}
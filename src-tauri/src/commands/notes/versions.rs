@@ -0,0 +1,468 @@
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::meta::{content_hash, index_meta, load_meta_by_path};
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::sandbox::{relative_to, resolve_within};
+use crate::commands::search::indexer::index_note;
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::settings::timezone::{now_for_db, to_db_datetime};
+use crate::AppState;
+
+pub const NOTE_VERSION_POLICY_KEY: &str = "note_version_policy";
+
+// これを超える大きさの差分は行ごとに比べず、全体の削除・追加として返す
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+// ノートごとに残す版の数と日数 (0 なら制限なし)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteVersionPolicy {
+    pub max_versions: u32,
+    pub max_days: u32,
+}
+
+impl Default for NoteVersionPolicy {
+    fn default() -> Self {
+        NoteVersionPolicy {
+            max_versions: 50,
+            max_days: 0,
+        }
+    }
+}
+
+// 版はノートの id (notes_meta) に紐づけるので名前を変えても引き継がれる
+#[derive(Debug, Serialize)]
+pub struct NoteVersion {
+    pub id: String,
+    pub note_id: String,
+    // 保存した時点のパス
+    pub path: String,
+    pub content_hash: String,
+    pub size: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+    // 1 始まりの行番号 (追加行は old_line、削除行は new_line が None)
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+}
+
+// 最長共通部分列による行単位の差分
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let line = |kind, text: &str, old_line, new_line| DiffLine {
+        kind,
+        text: text.to_string(),
+        old_line,
+        new_line,
+    };
+
+    // 前後の一致する行は表を作らずに済ませる
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut result: Vec<DiffLine> = (0..prefix)
+        .map(|i| line(DiffKind::Same, old[i], Some(i + 1), Some(i + 1)))
+        .collect();
+
+    if (a.len() + 1) * (b.len() + 1) > MAX_DIFF_CELLS {
+        result.extend(
+            a.iter()
+                .enumerate()
+                .map(|(i, text)| line(DiffKind::Removed, text, Some(prefix + i + 1), None)),
+        );
+        result.extend(
+            b.iter()
+                .enumerate()
+                .map(|(j, text)| line(DiffKind::Added, text, None, Some(prefix + j + 1))),
+        );
+    } else {
+        // lcs[i][j] = a[i..] と b[j..] の最長共通部分列の長さ
+        let width = b.len() + 1;
+        let mut lcs = vec![0usize; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            let (old_no, new_no) = (prefix + i + 1, prefix + j + 1);
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                result.push(line(DiffKind::Same, a[i], Some(old_no), Some(new_no)));
+                i += 1;
+                j += 1;
+            } else if j < b.len()
+                && (i == a.len() || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j])
+            {
+                result.push(line(DiffKind::Added, b[j], None, Some(new_no)));
+                j += 1;
+            } else {
+                result.push(line(DiffKind::Removed, a[i], Some(old_no), None));
+                i += 1;
+            }
+        }
+    }
+
+    let old_offset = old.len() - suffix;
+    let new_offset = new.len() - suffix;
+    result.extend((0..suffix).map(|k| {
+        line(
+            DiffKind::Same,
+            old[old_offset + k],
+            Some(old_offset + k + 1),
+            Some(new_offset + k + 1),
+        )
+    }));
+    result
+}
+
+pub async fn init_note_versions_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS note_versions (
+            id TEXT PRIMARY KEY,
+            note_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            content TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_note_versions_note ON note_versions(note_id, created_at)",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn row_to_version(row: &SqliteRow) -> Result<NoteVersion, sqlx::Error> {
+    Ok(NoteVersion {
+        id: row.try_get("id")?,
+        note_id: row.try_get("note_id")?,
+        path: row.try_get("path")?,
+        content_hash: row.try_get("content_hash")?,
+        size: row.try_get("size")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+pub async fn load_version_policy(pool: &SqlitePool) -> Result<NoteVersionPolicy, sqlx::Error> {
+    Ok(get_setting(pool, NOTE_VERSION_POLICY_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+// 保存期間・件数を超えた古い版を消す
+pub async fn prune_note_versions(
+    pool: &SqlitePool,
+    note_id: &str,
+    policy: &NoteVersionPolicy,
+) -> Result<(), sqlx::Error> {
+    if policy.max_days > 0 {
+        let cutoff = to_db_datetime(&(Utc::now() - Duration::days(policy.max_days as i64)));
+        sqlx::query(
+            "DELETE FROM note_versions WHERE note_id = ? AND datetime(created_at) < datetime(?)",
+        )
+        .bind(note_id)
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+    }
+    if policy.max_versions > 0 {
+        sqlx::query(
+            "DELETE FROM note_versions
+             WHERE note_id = ? AND id NOT IN (
+                 SELECT id FROM note_versions
+                 WHERE note_id = ?
+                 ORDER BY datetime(created_at) DESC, rowid DESC
+                 LIMIT ?
+             )",
+        )
+        .bind(note_id)
+        .bind(note_id)
+        .bind(policy.max_versions)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+// 上書きする前の内容 (previous) を版として残す
+// 内容が変わらない場合や、直近の版と同じ内容の場合は残さない
+pub async fn snapshot_note(
+    pool: &SqlitePool,
+    root: &Path,
+    path: &str,
+    previous: &str,
+    next: &str,
+) -> Result<(), sqlx::Error> {
+    if previous == next {
+        return Ok(());
+    }
+    init_note_versions_table(pool).await?;
+    let note_id = match load_meta_by_path(pool, path).await? {
+        Some(meta) => meta.id,
+        None => {
+            index_meta(pool, root, path, previous, &HashSet::new()).await?;
+            match load_meta_by_path(pool, path).await? {
+                Some(meta) => meta.id,
+                None => return Ok(()),
+            }
+        }
+    };
+
+    let hash = content_hash(previous);
+    let latest: Option<String> = sqlx::query(
+        "SELECT content_hash FROM note_versions WHERE note_id = ?
+         ORDER BY datetime(created_at) DESC, rowid DESC LIMIT 1",
+    )
+    .bind(&note_id)
+    .fetch_optional(pool)
+    .await?
+    .map(|row| row.get("content_hash"));
+    if latest.as_deref() == Some(hash.as_str()) {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO note_versions (id, note_id, path, content, content_hash, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(&note_id)
+    .bind(path)
+    .bind(previous)
+    .bind(&hash)
+    .bind(now_for_db())
+    .execute(pool)
+    .await?;
+
+    let policy = load_version_policy(pool).await?;
+    prune_note_versions(pool, &note_id, &policy).await
+}
+
+pub async fn load_note_versions(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Vec<NoteVersion>, sqlx::Error> {
+    init_note_versions_table(pool).await?;
+    let Some(meta) = load_meta_by_path(pool, path).await? else {
+        return Ok(Vec::new());
+    };
+    let rows = sqlx::query(
+        "SELECT id, note_id, path, content_hash, length(CAST(content AS BLOB)) AS size, created_at
+         FROM note_versions WHERE note_id = ?
+         ORDER BY datetime(created_at) DESC, rowid DESC",
+    )
+    .bind(&meta.id)
+    .fetch_all(pool)
+    .await?;
+    rows.iter().map(row_to_version).collect()
+}
+
+// path のノートの版の内容 (他のノートの版は読めない)
+async fn load_version_content(
+    pool: &SqlitePool,
+    path: &str,
+    version_id: &str,
+) -> Result<String, String> {
+    init_note_versions_table(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let meta = load_meta_by_path(pool, path)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Note has no history")?;
+    sqlx::query("SELECT content FROM note_versions WHERE id = ? AND note_id = ?")
+        .bind(version_id)
+        .bind(&meta.id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|row| row.get("content"))
+        .ok_or_else(|| "Note version not found".to_string())
+}
+
+#[tauri::command]
+pub async fn get_note_versions(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<Vec<NoteVersion>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let path = relative_to(&root, &resolve_within(&root, &path)?)?;
+    load_note_versions(&state.pool, &path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn read_note_version(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    version_id: String,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let path = relative_to(&root, &resolve_within(&root, &path)?)?;
+    load_version_content(&state.pool, &path, &version_id).await
+}
+
+// from / to を省略すると現在のファイルの内容と比べる
+#[tauri::command]
+pub async fn diff_note_versions(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<DiffLine>, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let file_path = resolve_within(&root, &path)?;
+    let path = relative_to(&root, &file_path)?;
+    let mut contents = Vec::new();
+    for version_id in [&from, &to] {
+        contents.push(match version_id {
+            Some(id) => load_version_content(&state.pool, &path, id).await?,
+            None => fs::read_to_string(&file_path).map_err(|e| e.to_string())?,
+        });
+    }
+    Ok(diff_lines(&contents[0], &contents[1]))
+}
+
+// 過去の版の内容でノートを書き戻す (現在の内容も版として残る)
+#[tauri::command]
+pub async fn restore_note_version(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    version_id: String,
+) -> Result<String, String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let file_path = resolve_within(&root, &path)?;
+    let path = relative_to(&root, &file_path)?;
+    let content = load_version_content(&state.pool, &path, &version_id).await?;
+    let current = fs::read_to_string(&file_path).unwrap_or_default();
+    snapshot_note(&state.pool, &root, &path, &current, &content)
+        .await
+        .map_err(|e| e.to_string())?;
+    fs::write(&file_path, &content).map_err(|e| e.to_string())?;
+    index_note(&state.pool, &root, &file_path).await?;
+    Ok(content)
+}
+
+#[tauri::command]
+pub async fn get_note_version_policy(
+    state: State<'_, AppState>,
+) -> Result<NoteVersionPolicy, String> {
+    load_version_policy(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_note_version_policy(
+    state: State<'_, AppState>,
+    policy: NoteVersionPolicy,
+) -> Result<NoteVersionPolicy, String> {
+    set_setting(&state.pool, NOTE_VERSION_POLICY_KEY, &policy)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc\nd", "a\nc\nx\nd");
+        let summary: Vec<(DiffKind, &str)> =
+            diff.iter().map(|l| (l.kind, l.text.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (DiffKind::Same, "a"),
+                (DiffKind::Removed, "b"),
+                (DiffKind::Same, "c"),
+                (DiffKind::Added, "x"),
+                (DiffKind::Same, "d"),
+            ]
+        );
+        assert_eq!(diff[3].new_line, Some(3));
+        assert_eq!(diff[4].old_line, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_dedup_and_retention() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let root = Path::new("/nonexistent");
+        set_setting(
+            &pool,
+            NOTE_VERSION_POLICY_KEY,
+            &NoteVersionPolicy {
+                max_versions: 2,
+                max_days: 0,
+            },
+        )
+        .await
+        .unwrap();
+
+        for (previous, next) in [
+            ("v1", "v2"),
+            ("v1", "v2"),
+            ("v2", "v2"),
+            ("v2", "v3"),
+            ("v3", "v4"),
+        ] {
+            snapshot_note(&pool, root, "a.md", previous, next)
+                .await
+                .unwrap();
+        }
+
+        let versions = load_note_versions(&pool, "a.md").await.unwrap();
+        let hashes: Vec<String> = versions.iter().map(|v| v.content_hash.clone()).collect();
+        assert_eq!(hashes, vec![content_hash("v3"), content_hash("v2")]);
+        assert_eq!(versions[0].size, 2);
+    }
+}
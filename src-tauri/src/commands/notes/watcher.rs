@@ -0,0 +1,220 @@
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::sandbox::relative_to;
+use crate::commands::search::indexer::sync_notes;
+
+// フロントエンドに通知するイベント名
+pub const NOTES_CHANGED_EVENT: &str = "notes-changed";
+
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteChangeKind {
+    Created,
+    Modified,
+    Renamed,
+    Deleted,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct NoteChange {
+    pub kind: NoteChangeKind,
+    // ノートのルートからの相対パス (名前変更の場合は変更後)
+    pub path: String,
+    // 名前変更前のパス
+    pub from: Option<String>,
+}
+
+// 監視中のノートフォルダ (ルートを変更したら張り直す)
+#[derive(Default)]
+pub struct NotesWatcher(Mutex<Option<Debouncer<RecommendedWatcher, RecommendedCache>>>);
+
+// ".trash" などの隠しファイル・フォルダの変化は通知しない
+fn visible_relative(root: &Path, path: &Path) -> Option<String> {
+    let relative = relative_to(root, path).ok()?;
+    if relative.is_empty() || relative.split('/').any(|part| part.starts_with('.')) {
+        return None;
+    }
+    Some(relative)
+}
+
+fn to_changes(root: &Path, kind: &EventKind, paths: &[PathBuf]) -> Vec<NoteChange> {
+    let change = |kind: NoteChangeKind, path: &PathBuf| {
+        visible_relative(root, path).map(|path| NoteChange {
+            kind,
+            path,
+            from: None,
+        })
+    };
+    let all =
+        |kind| -> Vec<NoteChange> { paths.iter().filter_map(|path| change(kind, path)).collect() };
+
+    match kind {
+        EventKind::Create(_) => all(NoteChangeKind::Created),
+        EventKind::Remove(_) => all(NoteChangeKind::Deleted),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+            match (
+                visible_relative(root, &paths[0]),
+                visible_relative(root, &paths[1]),
+            ) {
+                (Some(from), Some(path)) => vec![NoteChange {
+                    kind: NoteChangeKind::Renamed,
+                    path,
+                    from: Some(from),
+                }],
+                // 隠しフォルダ (ゴミ箱など) との間の移動は削除・作成として扱う
+                (Some(from), None) => vec![NoteChange {
+                    kind: NoteChangeKind::Deleted,
+                    path: from,
+                    from: None,
+                }],
+                (None, Some(path)) => vec![NoteChange {
+                    kind: NoteChangeKind::Created,
+                    path,
+                    from: None,
+                }],
+                (None, None) => Vec::new(),
+            }
+        }
+        // ルートの外との間で移動された場合は片側しか分からない
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => all(NoteChangeKind::Deleted),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => all(NoteChangeKind::Created),
+        EventKind::Modify(ModifyKind::Name(_)) => paths
+            .iter()
+            .filter_map(|path| {
+                let kind = if path.exists() {
+                    NoteChangeKind::Created
+                } else {
+                    NoteChangeKind::Deleted
+                };
+                change(kind, path)
+            })
+            .collect(),
+        EventKind::Modify(_) => all(NoteChangeKind::Modified),
+        _ => Vec::new(),
+    }
+}
+
+fn collect_changes(root: &Path, result: DebounceEventResult) -> Vec<NoteChange> {
+    let events = match result {
+        Ok(events) => events,
+        Err(errors) => {
+            for e in errors {
+                println!("Notes watcher error: {}", e);
+            }
+            return Vec::new();
+        }
+    };
+    let mut changes: Vec<NoteChange> = Vec::new();
+    for event in events {
+        for change in to_changes(root, &event.kind, &event.paths) {
+            if !changes.contains(&change) {
+                changes.push(change);
+            }
+        }
+    }
+    changes
+}
+
+// root を監視し、まとめた変更を NOTES_CHANGED_EVENT で通知する
+// 通知の前に検索・リンクの索引を更新しておく
+// (アプリ自身による保存も通知されるので、エディタは内容を比べてから読み直す)
+pub fn watch_notes_root(
+    app_handle: &AppHandle,
+    pool: SqlitePool,
+    root: PathBuf,
+) -> Result<(), String> {
+    let Some(watcher) = app_handle.try_state::<NotesWatcher>() else {
+        return Ok(());
+    };
+    let mut current = watcher.0.lock().map_err(|e| e.to_string())?;
+    // 以前のルートの監視を止める
+    current.take();
+
+    let handle = app_handle.clone();
+    let watched_root = root.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE, None, move |result| {
+        let changes = collect_changes(&watched_root, result);
+        if changes.is_empty() {
+            return;
+        }
+        let handle = handle.clone();
+        let pool = pool.clone();
+        let root = watched_root.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = sync_notes(&pool, &root).await {
+                println!("Failed to update the notes index: {}", e);
+            }
+            if let Err(e) = handle.emit(NOTES_CHANGED_EVENT, changes) {
+                println!("Failed to emit {}: {}", NOTES_CHANGED_EVENT, e);
+            }
+        });
+    })
+    .map_err(|e| e.to_string())?;
+
+    debouncer
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| e.to_string())?;
+    *current = Some(debouncer);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange};
+    use std::fs;
+
+    #[test]
+    fn test_to_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let created = to_changes(
+            &root,
+            &EventKind::Create(CreateKind::File),
+            &[root.join("Projects/Plan.md"), root.join(".trash/Old.md")],
+        );
+        assert_eq!(
+            created,
+            vec![NoteChange {
+                kind: NoteChangeKind::Created,
+                path: "Projects/Plan.md".to_string(),
+                from: None,
+            }]
+        );
+
+        let renamed = to_changes(
+            &root,
+            &EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &[root.join("Plan.md"), root.join("Projects/Plan.md")],
+        );
+        assert_eq!(renamed[0].kind, NoteChangeKind::Renamed);
+        assert_eq!(renamed[0].from.as_deref(), Some("Plan.md"));
+
+        let trashed = to_changes(
+            &root,
+            &EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &[root.join("Plan.md"), root.join(".trash/Plan.md")],
+        );
+        assert_eq!(trashed[0].kind, NoteChangeKind::Deleted);
+
+        let modified = to_changes(
+            &root,
+            &EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &[root.join("Plan.md")],
+        );
+        assert_eq!(modified[0].kind, NoteChangeKind::Modified);
+    }
+}
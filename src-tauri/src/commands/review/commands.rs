@@ -0,0 +1,68 @@
+use chrono::{Duration, NaiveDate};
+use std::fs;
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::review::report::{build_review, render_markdown, ReviewReport};
+use crate::commands::sandbox::relative_to;
+use crate::commands::settings::timezone::{load_user_timezone, start_of_day};
+use crate::AppState;
+
+const REVIEW_FOLDER: &str = "Reviews";
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date {}: {}", date, e))
+}
+
+async fn load_review(
+    state: &State<'_, AppState>,
+    start_date: &str,
+    end_date: &str,
+) -> Result<ReviewReport, String> {
+    let (start, end) = (parse_date(start_date)?, parse_date(end_date)?);
+    if end < start {
+        return Err("End date must not be before start date".to_string());
+    }
+    let tz = load_user_timezone(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let from = start_of_day(start, tz);
+    let to = start_of_day(end + Duration::days(1), tz);
+
+    build_review(&state.pool, start, end, from, to, tz)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// start_date から end_date まで (両端を含む) の振り返り
+#[tauri::command]
+pub async fn get_review_report(
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<ReviewReport, String> {
+    load_review(&state, &start_date, &end_date).await
+}
+
+// 振り返りを Markdown のノートとして Reviews フォルダに保存し、ルートからの相対パスを返す
+#[tauri::command]
+pub async fn save_review_note(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    overwrite: Option<bool>,
+) -> Result<String, String> {
+    let report = load_review(&state, &start_date, &end_date).await?;
+
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    let dir = root.join(REVIEW_FOLDER);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("Review {} - {}.md", start_date, end_date));
+    if path.exists() && !overwrite.unwrap_or(false) {
+        return Err("Review note already exists".to_string());
+    }
+
+    fs::write(&path, render_markdown(&report)).map_err(|e| e.to_string())?;
+    relative_to(&root, &path)
+}
@@ -0,0 +1,264 @@
+use chrono::{DateTime, Local, NaiveDate};
+use chrono_tz::Tz;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+use crate::commands::books::commands::load_reading_activities;
+use crate::commands::calendar::memo::{load_memos, CalendarMemo};
+use crate::commands::mindmap::db::{init_mind_map_table, MindMap};
+use crate::commands::task::sql::task::{init_task_table, rows_to_tasks};
+use crate::commands::task::sql::task_query::to_sql_datetime;
+use crate::commands::task::task::Task;
+
+// 期間中の読書量 (本ごと)
+#[derive(Debug, Serialize, Clone)]
+pub struct BookReview {
+    pub book_title: String,
+    pub pages_read: i32,
+    pub start_page: i32,
+    pub end_page: i32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MindMapReview {
+    pub id: String,
+    pub title: String,
+    pub updated_at: Option<String>,
+}
+
+// 週次・月次の振り返り
+#[derive(Serialize)]
+pub struct ReviewReport {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub completed_tasks: Vec<Task>,
+    // 期日が期間内に過ぎたのに、期日までに完了しなかったタスク
+    pub slipped_tasks: Vec<Task>,
+    pub new_tasks: Vec<Task>,
+    pub reading: Vec<BookReview>,
+    pub memos: Vec<CalendarMemo>,
+    pub mind_maps: Vec<MindMapReview>,
+}
+
+async fn tasks_where(
+    pool: &SqlitePool,
+    condition: &str,
+    binds: &[String],
+) -> Result<Vec<Task>, sqlx::Error> {
+    init_task_table(pool).await?;
+
+    let sql = format!(
+        "SELECT * FROM tasks WHERE deleted_at IS NULL AND {} ORDER BY datetime(created_at)",
+        condition
+    );
+    let mut query = sqlx::query(&sql);
+    for bind in binds {
+        query = query.bind(bind);
+    }
+    let rows = query.fetch_all(pool).await?;
+    rows_to_tasks(pool, rows).await
+}
+
+async fn load_mind_maps_edited(
+    pool: &SqlitePool,
+    from: &str,
+    to: &str,
+) -> Result<Vec<MindMapReview>, sqlx::Error> {
+    init_mind_map_table(pool).await?;
+
+    let maps = sqlx::query_as::<_, MindMap>(
+        "SELECT * FROM mind_maps
+         WHERE deleted_at IS NULL
+           AND datetime(COALESCE(updated_at, created_at)) >= ?
+           AND datetime(COALESCE(updated_at, created_at)) < ?
+         ORDER BY datetime(COALESCE(updated_at, created_at))",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(maps
+        .into_iter()
+        .map(|m| MindMapReview {
+            id: m.id,
+            title: m.title,
+            updated_at: m.updated_at.or(Some(m.created_at)),
+        })
+        .collect())
+}
+
+// [from, to) の振り返り (start_date/end_date は表示用の両端を含む日付)
+pub async fn build_review(
+    pool: &SqlitePool,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    tz: Tz,
+) -> Result<ReviewReport, sqlx::Error> {
+    let (from_sql, to_sql) = (to_sql_datetime(from), to_sql_datetime(to));
+    let now_sql = to_sql_datetime(Local::now());
+
+    let completed_tasks = tasks_where(
+        pool,
+        "completed = 1 AND datetime(completed_at) >= ? AND datetime(completed_at) < ?",
+        &[from_sql.clone(), to_sql.clone()],
+    )
+    .await?;
+    let slipped_tasks = tasks_where(
+        pool,
+        "end_date IS NOT NULL
+         AND datetime(end_date) >= ? AND datetime(end_date) < ? AND datetime(end_date) < ?
+         AND (completed = 0 OR datetime(completed_at) > datetime(end_date))",
+        &[from_sql.clone(), to_sql.clone(), now_sql],
+    )
+    .await?;
+    let new_tasks = tasks_where(
+        pool,
+        "datetime(created_at) >= ? AND datetime(created_at) < ?",
+        &[from_sql.clone(), to_sql.clone()],
+    )
+    .await?;
+
+    let mut books: BTreeMap<String, BookReview> = BTreeMap::new();
+    for activity in load_reading_activities(pool, from, to, tz).await? {
+        let book = books
+            .entry(activity.book_title.clone())
+            .or_insert(BookReview {
+                book_title: activity.book_title.clone(),
+                pages_read: 0,
+                start_page: activity.start_page,
+                end_page: activity.end_page,
+            });
+        book.pages_read += (activity.end_page - activity.start_page).max(0);
+        book.end_page = activity.end_page;
+    }
+
+    let memos = load_memos(
+        pool,
+        &start_date.format("%Y-%m-%d").to_string(),
+        &end_date.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let mind_maps = load_mind_maps_edited(pool, &from_sql, &to_sql).await?;
+
+    Ok(ReviewReport {
+        start_date,
+        end_date,
+        completed_tasks,
+        slipped_tasks,
+        new_tasks,
+        reading: books.into_values().collect(),
+        memos,
+        mind_maps,
+    })
+}
+
+fn push_task_list(out: &mut Vec<String>, title: &str, tasks: &[Task]) {
+    out.push(format!("## {} ({})", title, tasks.len()));
+    out.push(String::new());
+    if tasks.is_empty() {
+        out.push("- (none)".to_string());
+    }
+    for task in tasks {
+        let mark = if task.completed { "x" } else { " " };
+        let due = task
+            .end_datetime
+            .map(|dt| format!(" (due {})", dt.format("%Y-%m-%d")))
+            .unwrap_or_default();
+        out.push(format!("- [{}] {}{}", mark, task.description, due));
+    }
+    out.push(String::new());
+}
+
+pub fn render_markdown(report: &ReviewReport) -> String {
+    let mut out = vec![
+        format!(
+            "# Review {} – {}",
+            report.start_date.format("%Y-%m-%d"),
+            report.end_date.format("%Y-%m-%d")
+        ),
+        String::new(),
+    ];
+
+    push_task_list(&mut out, "Completed tasks", &report.completed_tasks);
+    push_task_list(&mut out, "Slipped tasks", &report.slipped_tasks);
+    push_task_list(&mut out, "New tasks", &report.new_tasks);
+
+    let total_pages: i32 = report.reading.iter().map(|b| b.pages_read).sum();
+    out.push(format!("## Reading ({} pages)", total_pages));
+    out.push(String::new());
+    if report.reading.is_empty() {
+        out.push("- (none)".to_string());
+    }
+    for book in &report.reading {
+        out.push(format!(
+            "- {}: {} pages (p.{} → p.{})",
+            book.book_title, book.pages_read, book.start_page, book.end_page
+        ));
+    }
+    out.push(String::new());
+
+    out.push(format!("## Memos ({})", report.memos.len()));
+    out.push(String::new());
+    if report.memos.is_empty() {
+        out.push("- (none)".to_string());
+    }
+    for memo in &report.memos {
+        let first_line = memo
+            .content
+            .lines()
+            .map(|l| l.trim().trim_start_matches('#').trim())
+            .find(|l| !l.is_empty())
+            .unwrap_or("");
+        out.push(format!("- {}: {}", memo.date, first_line));
+    }
+    out.push(String::new());
+
+    out.push(format!("## Mind maps ({})", report.mind_maps.len()));
+    out.push(String::new());
+    if report.mind_maps.is_empty() {
+        out.push("- (none)".to_string());
+    }
+    for map in &report.mind_maps {
+        out.push(format!("- {}", map.title));
+    }
+    out.push(String::new());
+
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let mut task = Task::new();
+        task.description = "Ship release".to_string();
+        task.completed = true;
+        let report = ReviewReport {
+            start_date: NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+            completed_tasks: vec![task],
+            slipped_tasks: Vec::new(),
+            new_tasks: Vec::new(),
+            reading: vec![BookReview {
+                book_title: "Rust".to_string(),
+                pages_read: 40,
+                start_page: 10,
+                end_page: 50,
+            }],
+            memos: Vec::new(),
+            mind_maps: Vec::new(),
+        };
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.starts_with("# Review 2024-05-06 – 2024-05-12\n"));
+        assert!(markdown.contains("## Completed tasks (1)\n\n- [x] Ship release\n"));
+        assert!(markdown.contains("## Slipped tasks (0)\n\n- (none)\n"));
+        assert!(markdown.contains("- Rust: 40 pages (p.10 → p.50)"));
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::commands::notes::trash::TRASH_DIR;
+
+// webview から渡されるパスはルートからの相対パスに限定する
+// (絶対パス・".." とゴミ箱の中は受け付けない)
+pub fn check_relative(relative: &str) -> Result<(), String> {
+    let mut components = Path::new(relative)
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .peekable();
+    let in_trash = components
+        .peek()
+        .is_some_and(|c| c.as_os_str() == TRASH_DIR);
+    let ok = !in_trash && components.all(|c| matches!(c, Component::Normal(_)));
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("Invalid path: {}", relative))
+    }
+}
+
+// ファイル名・フォルダ名として使えるか (区切り文字を含まない1要素)
+pub fn check_name(name: &str) -> Result<(), String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => Err(format!("Invalid name: {}", name)),
+    }
+}
+
+fn escapes(root: &Path) -> String {
+    format!("Path is outside of {}", root.display())
+}
+
+// root からの相対パスを実際のパスにする
+// シンボリックリンクをたどった先が root の外になる場合も拒否する
+pub fn resolve_within(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    check_relative(relative)?;
+    let root = root.canonicalize().map_err(|e| e.to_string())?;
+    let path = root.join(relative);
+
+    // 存在する一番近い祖先までを実体に直して確かめる
+    let mut existing = path.as_path();
+    loop {
+        if existing.symlink_metadata().is_ok() {
+            break;
+        }
+        existing = existing.parent().ok_or_else(|| escapes(&root))?;
+    }
+    // 存在しない先を指すリンクも canonicalize に失敗するので拒否される
+    let real = existing.canonicalize().map_err(|_| escapes(&root))?;
+    if !real.starts_with(&root) {
+        return Err(escapes(&root));
+    }
+
+    let rest = path.strip_prefix(existing).map_err(|e| e.to_string())?;
+    if rest.as_os_str().is_empty() {
+        Ok(real)
+    } else {
+        Ok(real.join(rest))
+    }
+}
+
+// root からの相対パス ("/" 区切り)
+pub fn relative_to(root: &Path, path: &Path) -> Result<String, String> {
+    let root = root.canonicalize().map_err(|e| e.to_string())?;
+    let relative = path.strip_prefix(&root).map_err(|_| escapes(&root))?;
+    Ok(relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_resolve_within() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().to_path_buf();
+        let root = base.join("root");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(base.join("outside")).unwrap();
+
+        let resolved = resolve_within(&root, "sub/new.md").unwrap();
+        assert_eq!(relative_to(&root, &resolved).unwrap(), "sub/new.md");
+        assert!(resolve_within(&root, "../outside").is_err());
+        assert!(resolve_within(&root, "/etc/passwd").is_err());
+        assert!(check_relative(".trash/abc/Old.md").is_err());
+        assert!(check_relative("./.trash").is_err());
+        assert!(check_relative("sub/.trash").is_ok());
+        assert!(check_name("a/b").is_err());
+        assert!(check_name("..").is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("outside"), root.join("link")).unwrap();
+            assert!(resolve_within(&root, "link/secret.md").is_err());
+        }
+    }
+}
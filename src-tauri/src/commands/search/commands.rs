@@ -0,0 +1,36 @@
+use tauri::{AppHandle, State};
+
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::search::db::{search_documents, SearchKind, SearchResult};
+use crate::commands::search::indexer::rebuild_index;
+use crate::AppState;
+
+const DEFAULT_LIMIT: u32 = 50;
+
+// kinds を省略した場合はすべての種類から探す
+#[tauri::command]
+pub async fn search(
+    state: State<'_, AppState>,
+    query: String,
+    kinds: Option<Vec<SearchKind>>,
+    limit: Option<u32>,
+) -> Result<Vec<SearchResult>, String> {
+    // アプリの外で編集されたノートは、起動時の同期とフォルダの監視で反映している
+    search_documents(
+        &state.pool,
+        &query,
+        &kinds.unwrap_or_default(),
+        limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rebuild_search_index(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let root = ensure_notes_dir(&app_handle, &state.pool).await?;
+    rebuild_index(&app_handle, &state.pool, &root).await
+}
@@ -0,0 +1,392 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::commands::settings::timezone::now_for_db;
+
+// snippet() で一致箇所を囲む目印 (本文に現れない私用領域の文字)
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+const SNIPPET_CHARS: usize = 48;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Note,
+    Task,
+    ReadingMemo,
+    CalendarMemo,
+    MindMap,
+}
+
+impl SearchKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::Note => "note",
+            SearchKind::Task => "task",
+            SearchKind::ReadingMemo => "reading_memo",
+            SearchKind::CalendarMemo => "calendar_memo",
+            SearchKind::MindMap => "mind_map",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        [
+            SearchKind::Note,
+            SearchKind::Task,
+            SearchKind::ReadingMemo,
+            SearchKind::CalendarMemo,
+            SearchKind::MindMap,
+        ]
+        .into_iter()
+        .find(|k| k.as_str() == kind)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    // ノートはルートからの相対パス、それ以外は各データの id
+    pub item_id: String,
+    pub title: String,
+    // HTML エスケープ済みで、一致箇所を <mark> で囲んだ抜粋
+    pub snippet: String,
+    pub score: f64,
+}
+
+// 日本語でも部分一致できるよう trigram で分割する (3文字未満の語は LIKE で探す)
+pub async fn init_search_tables(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS search_documents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(kind, item_id)
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(title, body, tokenize = 'trigram')",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn upsert_document(
+    pool: &SqlitePool,
+    kind: SearchKind,
+    item_id: &str,
+    title: &str,
+    body: &str,
+) -> Result<(), sqlx::Error> {
+    init_search_tables(pool).await?;
+
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query(
+        "INSERT INTO search_documents (kind, item_id, updated_at) VALUES (?, ?, ?)
+         ON CONFLICT(kind, item_id) DO UPDATE SET updated_at = excluded.updated_at
+         RETURNING id",
+    )
+    .bind(kind.as_str())
+    .bind(item_id)
+    .bind(now_for_db())
+    .fetch_one(&mut *tx)
+    .await?;
+    let id: i64 = row.try_get("id")?;
+
+    sqlx::query("DELETE FROM search_index WHERE rowid = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO search_index (rowid, title, body) VALUES (?, ?, ?)")
+        .bind(id)
+        .bind(title)
+        .bind(body)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+pub async fn remove_document(
+    pool: &SqlitePool,
+    kind: SearchKind,
+    item_id: &str,
+) -> Result<(), sqlx::Error> {
+    init_search_tables(pool).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "DELETE FROM search_index WHERE rowid IN
+         (SELECT id FROM search_documents WHERE kind = ? AND item_id = ?)",
+    )
+    .bind(kind.as_str())
+    .bind(item_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM search_documents WHERE kind = ? AND item_id = ?")
+        .bind(kind.as_str())
+        .bind(item_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+pub async fn clear_documents(pool: &SqlitePool, kind: SearchKind) -> Result<(), sqlx::Error> {
+    init_search_tables(pool).await?;
+
+    sqlx::query(
+        "DELETE FROM search_index WHERE rowid IN (SELECT id FROM search_documents WHERE kind = ?)",
+    )
+    .bind(kind.as_str())
+    .execute(pool)
+    .await?;
+    sqlx::query("DELETE FROM search_documents WHERE kind = ?")
+        .bind(kind.as_str())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            MARK_START => escaped.push_str("<mark>"),
+            MARK_END => escaped.push_str("</mark>"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// trigram で探せない短い語のための抜粋 (最初の一致の前後を切り出して目印を付ける)
+fn fallback_snippet(body: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = body.chars().collect();
+    let lower: Vec<char> = body.to_lowercase().chars().collect();
+    // 小文字にすると文字数が変わる場合は目印を付けずに先頭を返す
+    if chars.len() != lower.len() {
+        return chars.iter().take(SNIPPET_CHARS).collect();
+    }
+
+    let terms: Vec<Vec<char>> = terms
+        .iter()
+        .map(|t| t.to_lowercase().chars().collect())
+        .filter(|t: &Vec<char>| !t.is_empty())
+        .collect();
+    let find_at = |i: usize| {
+        terms
+            .iter()
+            .find(|t| lower[i..].starts_with(t))
+            .map(|t| t.len())
+    };
+
+    let first = (0..lower.len())
+        .find(|i| find_at(*i).is_some())
+        .unwrap_or(0);
+    let start = first.saturating_sub(SNIPPET_CHARS / 4);
+    let end = (start + SNIPPET_CHARS).min(chars.len());
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let mut i = start;
+    while i < end {
+        match find_at(i) {
+            Some(len) => {
+                let len = len.min(end - i);
+                out.push(MARK_START);
+                out.extend(&chars[i..i + len]);
+                out.push(MARK_END);
+                i += len;
+            }
+            None => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len() + 2);
+    escaped.push('%');
+    for c in term.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('%');
+    escaped
+}
+
+// 空白区切りの語をすべて含むものを探す (語はそれぞれフレーズとして扱う)
+pub fn build_match_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+fn push_kind_filter(builder: &mut QueryBuilder<'_, Sqlite>, kinds: &[SearchKind]) {
+    if kinds.is_empty() {
+        return;
+    }
+    builder.push(" AND d.kind IN (");
+    let mut separated = builder.separated(", ");
+    for kind in kinds {
+        separated.push_bind(kind.as_str());
+    }
+    separated.push_unseparated(")");
+}
+
+pub async fn search_documents(
+    pool: &SqlitePool,
+    query: &str,
+    kinds: &[SearchKind],
+    limit: u32,
+) -> Result<Vec<SearchResult>, sqlx::Error> {
+    init_search_tables(pool).await?;
+
+    let terms: Vec<String> = query.split_whitespace().map(|t| t.to_string()).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let use_fts = terms.iter().all(|t| t.chars().count() >= 3);
+
+    let mut builder = QueryBuilder::<Sqlite>::new("");
+    if use_fts {
+        // タイトルの一致を本文より重く数える
+        builder
+            .push(format!(
+                "SELECT d.kind, d.item_id, s.title, bm25(search_index, 5.0, 1.0) AS score,
+                    snippet(search_index, 1, '{}', '{}', '…', 16) AS snippet
+                 FROM search_index s JOIN search_documents d ON d.id = s.rowid
+                 WHERE search_index MATCH ",
+                MARK_START, MARK_END
+            ))
+            .push_bind(build_match_query(&terms));
+        push_kind_filter(&mut builder, kinds);
+        builder.push(" ORDER BY score");
+    } else {
+        builder.push(
+            "SELECT d.kind, d.item_id, s.title, 0.0 AS score, s.body AS snippet
+             FROM search_index s JOIN search_documents d ON d.id = s.rowid
+             WHERE 1 = 1",
+        );
+        for term in &terms {
+            let pattern = escape_like(term);
+            builder
+                .push(" AND (s.title LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR s.body LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+        push_kind_filter(&mut builder, kinds);
+        builder.push(" ORDER BY d.updated_at DESC");
+    }
+    builder.push(" LIMIT ").push_bind(limit as i64);
+
+    let rows = builder.build().fetch_all(pool).await?;
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        let kind: String = row.try_get("kind")?;
+        let Some(kind) = SearchKind::parse(&kind) else {
+            continue;
+        };
+        let snippet: String = row.try_get("snippet")?;
+        let snippet = if use_fts {
+            snippet
+        } else {
+            fallback_snippet(&snippet, &terms)
+        };
+        // bm25 は小さいほど関連が高いので、符号を反転して返す
+        let score: f64 = row.try_get("score")?;
+        results.push(SearchResult {
+            kind,
+            item_id: row.try_get("item_id")?,
+            title: row.try_get("title")?,
+            snippet: escape_html(&snippet),
+            score: -score,
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_search_documents() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        upsert_document(
+            &pool,
+            SearchKind::Note,
+            "a.md",
+            "議事録",
+            "来週のリリース計画 <draft>",
+        )
+        .await
+        .unwrap();
+        upsert_document(&pool, SearchKind::Task, "t1", "Plan release", "")
+            .await
+            .unwrap();
+
+        let results = search_documents(&pool, "リリース計画", &[], 10)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item_id, "a.md");
+        assert!(results[0].snippet.contains("<mark>リリース計画</mark>"));
+        assert!(results[0].snippet.contains("&lt;draft&gt;"));
+
+        let results = search_documents(&pool, "release", &[SearchKind::Task], 10)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // 3文字未満は LIKE で探す
+        let results = search_documents(&pool, "来週", &[], 10).await.unwrap();
+        assert_eq!(
+            results[0].snippet,
+            "<mark>来週</mark>のリリース計画 &lt;draft&gt;"
+        );
+
+        // 再登録で古い内容は残らない
+        upsert_document(&pool, SearchKind::Note, "a.md", "議事録", "別の内容")
+            .await
+            .unwrap();
+        assert!(search_documents(&pool, "リリース計画", &[], 10)
+            .await
+            .unwrap()
+            .is_empty());
+        remove_document(&pool, SearchKind::Task, "t1")
+            .await
+            .unwrap();
+        assert!(search_documents(&pool, "release", &[], 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+
+use crate::commands::books::fs::read_memo_content;
+use crate::commands::calendar::memo::{load_all_memos, CalendarMemo};
+use crate::commands::mindmap::db::{init_mind_map_table, MindMap};
+use crate::commands::notes::links::{clear_links, index_links, remove_links};
+use crate::commands::notes::meta::{index_meta, mark_meta_deleted, prune_missing_meta};
+use crate::commands::notes::tasks::sync_note_tasks;
+use crate::commands::sandbox::relative_to;
+use crate::commands::search::db::{
+    clear_documents, init_search_tables, remove_document, upsert_document, SearchKind,
+};
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::task::sql::task::{init_task_table, rows_to_tasks};
+use crate::commands::task::task::Task;
+
+const INDEX_BUILT_KEY: &str = "search_index_built";
+
+// 削除済みのタスクは索引から外す (アーカイブ済みは検索できるよう残す)
+pub async fn index_task(pool: &SqlitePool, task: &Task) -> Result<(), sqlx::Error> {
+    let id = task.id.to_string();
+    if task.deleted_at.is_some() {
+        return remove_document(pool, SearchKind::Task, &id).await;
+    }
+
+    let mut body = task.details.clone().unwrap_or_default();
+    for subtask in &task.subtasks {
+        if subtask.deleted_at.is_none() {
+            body.push('\n');
+            body.push_str(&subtask.description);
+        }
+    }
+    upsert_document(pool, SearchKind::Task, &id, &task.description, &body).await
+}
+
+// 日別メモは日付 (YYYY-MM-DD) で引けるようにする
+pub async fn index_calendar_memo(
+    pool: &SqlitePool,
+    memo: &CalendarMemo,
+) -> Result<(), sqlx::Error> {
+    if memo.deleted_at.is_some() {
+        return remove_document(pool, SearchKind::CalendarMemo, &memo.date).await;
+    }
+    upsert_document(
+        pool,
+        SearchKind::CalendarMemo,
+        &memo.date,
+        &memo.date,
+        &memo.content,
+    )
+    .await
+}
+
+pub async fn index_reading_memo(
+    pool: &SqlitePool,
+    memo_id: &str,
+    book_id: &str,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    let book_title: Option<String> = sqlx::query("SELECT title FROM books WHERE id = ?")
+        .bind(book_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.get("title"));
+    upsert_document(
+        pool,
+        SearchKind::ReadingMemo,
+        memo_id,
+        &book_title.unwrap_or_default(),
+        content,
+    )
+    .await
+}
+
+// React Flow の { nodes: [{ data: { label } }] } からノードのラベルを取り出す
+pub fn mind_map_labels(content: &str) -> Vec<String> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    value["nodes"]
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|node| node["data"]["label"].as_str())
+                .map(|label| label.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub async fn index_mind_map(pool: &SqlitePool, mind_map: &MindMap) -> Result<(), sqlx::Error> {
+    if mind_map.deleted_at.is_some() {
+        return remove_document(pool, SearchKind::MindMap, &mind_map.id).await;
+    }
+    upsert_document(
+        pool,
+        SearchKind::MindMap,
+        &mind_map.id,
+        &mind_map.title,
+        &mind_map_labels(&mind_map.content).join("\n"),
+    )
+    .await
+}
+
+async fn init_note_state_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS search_note_files (
+            path TEXT PRIMARY KEY,
+            modified TEXT NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn modified_key(path: &Path) -> String {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| {
+            format!(
+                "{}:{}",
+                d.as_millis(),
+                fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn is_note(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("md")
+}
+
+// ".trash" などの隠しフォルダは対象外
+pub fn collect_notes(dir: &Path, notes: &mut Vec<std::path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => collect_notes(&path, notes),
+            Ok(t) if t.is_file() && is_note(&path) => notes.push(path),
+            _ => {}
+        }
+    }
+}
+
+// root は ensure_notes_dir で得た実体のパス
+pub async fn index_note(pool: &SqlitePool, root: &Path, path: &Path) -> Result<(), String> {
+    reindex_note(pool, root, path, &HashSet::new()).await
+}
+
+// vanished は同じ同期の中でなくなったノート (名前変更・移動の元かもしれないもの)
+async fn reindex_note(
+    pool: &SqlitePool,
+    root: &Path,
+    path: &Path,
+    vanished: &HashSet<String>,
+) -> Result<(), String> {
+    init_note_state_table(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let relative = relative_to(root, path)?;
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let title = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    upsert_document(pool, SearchKind::Note, &relative, &title, &content)
+        .await
+        .map_err(|e| e.to_string())?;
+    index_links(pool, &relative, &content)
+        .await
+        .map_err(|e| e.to_string())?;
+    index_meta(pool, root, &relative, &content, vanished)
+        .await
+        .map_err(|e| e.to_string())?;
+    sync_note_tasks(pool, &relative, &content)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("INSERT OR REPLACE INTO search_note_files (path, modified) VALUES (?, ?)")
+        .bind(&relative)
+        .bind(modified_key(path))
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// 変更されたノートだけを索引し直し、なくなったノートを外す
+pub async fn sync_notes(pool: &SqlitePool, root: &Path) -> Result<(), String> {
+    init_search_tables(pool).await.map_err(|e| e.to_string())?;
+    init_note_state_table(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let rows = sqlx::query("SELECT path, modified FROM search_note_files")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut known: HashMap<String, String> = rows
+        .iter()
+        .map(|row| (row.get("path"), row.get("modified")))
+        .collect();
+
+    let mut notes = Vec::new();
+    collect_notes(root, &mut notes);
+    let mut changed = Vec::new();
+    for path in notes {
+        let relative = relative_to(root, &path)?;
+        if known.remove(&relative) != Some(modified_key(&path)) {
+            changed.push(path);
+        }
+    }
+
+    // 残ったものはなくなったノート。外部での名前変更・移動なら新しいパスに id を引き継ぐ
+    let vanished: HashSet<String> = known.into_keys().collect();
+    for path in changed {
+        // 読めないファイル (UTF-8 でないなど) は飛ばす
+        if reindex_note(pool, root, &path, &vanished).await.is_err() {
+            continue;
+        }
+    }
+
+    for relative in &vanished {
+        remove_document(pool, SearchKind::Note, relative)
+            .await
+            .map_err(|e| e.to_string())?;
+        remove_links(pool, relative)
+            .await
+            .map_err(|e| e.to_string())?;
+        mark_meta_deleted(pool, relative)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("DELETE FROM search_note_files WHERE path = ?")
+            .bind(relative)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// 索引を作り直す
+pub async fn rebuild_index(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    notes_root: &Path,
+) -> Result<(), String> {
+    let e = |e: sqlx::Error| e.to_string();
+
+    clear_documents(pool, SearchKind::Task).await.map_err(e)?;
+    init_task_table(pool).await.map_err(e)?;
+    let rows = sqlx::query("SELECT * FROM tasks WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(e)?;
+    for task in rows_to_tasks(pool, rows).await.map_err(e)? {
+        index_task(pool, &task).await.map_err(e)?;
+    }
+
+    clear_documents(pool, SearchKind::CalendarMemo)
+        .await
+        .map_err(e)?;
+    for memo in load_all_memos(pool).await.map_err(e)? {
+        index_calendar_memo(pool, &memo).await.map_err(e)?;
+    }
+
+    clear_documents(pool, SearchKind::MindMap)
+        .await
+        .map_err(e)?;
+    init_mind_map_table(pool).await.map_err(e)?;
+    let maps = sqlx::query_as::<_, MindMap>("SELECT * FROM mind_maps WHERE deleted_at IS NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(e)?;
+    for map in &maps {
+        index_mind_map(pool, map).await.map_err(e)?;
+    }
+
+    clear_documents(pool, SearchKind::ReadingMemo)
+        .await
+        .map_err(e)?;
+    // 削除した本の読書メモは含めない
+    let memos = sqlx::query(
+        "SELECT m.id, m.book_id, m.content_path FROM reading_memos m
+         JOIN books b ON b.id = m.book_id
+         WHERE m.deleted_at IS NULL AND b.deleted_at IS NULL",
+    )
+    .fetch_all(pool)
+    .await
+    // 本の機能をまだ使っていない場合はテーブルがない
+    .unwrap_or_default();
+    for memo in memos {
+        let id: String = memo.get("id");
+        let book_id: String = memo.get("book_id");
+        let content_path: String = memo.get("content_path");
+        let content = read_memo_content(app_handle, &content_path).unwrap_or_default();
+        index_reading_memo(pool, &id, &book_id, &content)
+            .await
+            .map_err(e)?;
+    }
+
+    clear_documents(pool, SearchKind::Note).await.map_err(e)?;
+    clear_links(pool).await.map_err(e)?;
+    init_note_state_table(pool).await.map_err(e)?;
+    sqlx::query("DELETE FROM search_note_files")
+        .execute(pool)
+        .await
+        .map_err(e)?;
+    sync_notes(pool, notes_root).await?;
+    prune_missing_meta(pool, notes_root).await.map_err(e)
+}
+
+// 起動時に一度呼ぶ。索引を作ったことがなければ (アップグレード直後など) 作り直し、
+// 作ってあればアプリを閉じている間に変わったノートだけを反映する
+pub async fn prepare_index(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    notes_root: &Path,
+) -> Result<(), String> {
+    let built: Option<bool> = get_setting(pool, INDEX_BUILT_KEY)
+        .await
+        .map_err(|e| e.to_string())?;
+    if built.unwrap_or(false) {
+        return sync_notes(pool, notes_root).await;
+    }
+    rebuild_index(app_handle, pool, notes_root).await?;
+    set_setting(pool, INDEX_BUILT_KEY, &true)
+        .await
+        .map_err(|e| e.to_string())
+}
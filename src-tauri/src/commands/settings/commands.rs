@@ -0,0 +1,45 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::settings::db::set_setting;
+use crate::commands::settings::timezone::{
+    load_user_timezone, parse_timezone, system_timezone, USER_TIMEZONE_KEY,
+};
+use crate::AppState;
+
+#[derive(Serialize)]
+pub struct TimezoneSettings {
+    // 実際に使われるタイムゾーン
+    pub timezone: String,
+    pub system_timezone: Option<String>,
+}
+
+async fn timezone_settings(state: &State<'_, AppState>) -> Result<TimezoneSettings, String> {
+    let tz = load_user_timezone(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(TimezoneSettings {
+        timezone: tz.name().to_string(),
+        system_timezone: system_timezone().map(|tz| tz.name().to_string()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_timezone_settings(state: State<'_, AppState>) -> Result<TimezoneSettings, String> {
+    timezone_settings(&state).await
+}
+
+// None を渡すと OS のタイムゾーンに従う
+#[tauri::command]
+pub async fn update_user_timezone(
+    state: State<'_, AppState>,
+    timezone: Option<String>,
+) -> Result<TimezoneSettings, String> {
+    if let Some(name) = &timezone {
+        parse_timezone(name)?;
+    }
+    set_setting(&state.pool, USER_TIMEZONE_KEY, &timezone)
+        .await
+        .map_err(|e| e.to_string())?;
+    timezone_settings(&state).await
+}
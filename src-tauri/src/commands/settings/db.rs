@@ -0,0 +1,61 @@
+use crate::commands::settings::timezone::now_for_db;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+// アプリ設定 (キーごとに JSON で保存する)
+pub async fn init_settings_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_setting<T: DeserializeOwned>(
+    pool: &SqlitePool,
+    key: &str,
+) -> Result<Option<T>, sqlx::Error> {
+    init_settings_table(pool).await?;
+
+    let row = sqlx::query("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    match row {
+        Some(row) => {
+            let value: String = row.try_get("value")?;
+            // 壊れた値は未設定として扱い、呼び出し側の既定値に任せる
+            Ok(serde_json::from_str(&value).ok())
+        }
+        None => Ok(None),
+    }
+}
+
+pub async fn set_setting<T: Serialize>(
+    pool: &SqlitePool,
+    key: &str,
+    value: &T,
+) -> Result<(), sqlx::Error> {
+    init_settings_table(pool).await?;
+
+    let value = serde_json::to_string(value).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let now = now_for_db();
+    sqlx::query(
+        "INSERT INTO settings (key, value, created_at, updated_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+    )
+    .bind(key)
+    .bind(value)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+
+use crate::commands::settings::db::get_setting;
+
+// ユーザーが選んだ IANA タイムゾーン名 (未設定なら OS の設定に従う)
+pub const USER_TIMEZONE_KEY: &str = "user_timezone";
+
+// OS の IANA タイムゾーン名を取得する
+pub fn system_timezone() -> Option<Tz> {
+    iana_time_zone::get_timezone().ok()?.parse().ok()
+}
+
+// DB には UTC の RFC3339 (ミリ秒まで) で保存する
+// 別のタイムゾーンの端末と同期しても、文字列のまま時刻順に比較できるように
+pub fn to_db_datetime<T: TimeZone>(dt: &DateTime<T>) -> String {
+    dt.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub fn now_for_db() -> String {
+    to_db_datetime(&Utc::now())
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse()
+        .map_err(|_| format!("Unknown timezone: {}", name))
+}
+
+pub async fn load_user_timezone(pool: &SqlitePool) -> Result<Tz, sqlx::Error> {
+    let name: Option<String> = get_setting(pool, USER_TIMEZONE_KEY).await?;
+    Ok(name
+        .and_then(|name| name.parse().ok())
+        .or_else(system_timezone)
+        .unwrap_or(Tz::UTC))
+}
+
+// ユーザーのタイムゾーンでのその日の 0 時
+// (夏時間の切り替えで 0 時が存在しない日は、その直後の時刻にする)
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Local> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    let start = tz
+        .from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            (1..=4)
+                .map(|h| midnight + chrono::Duration::hours(h))
+                .find_map(|t| tz.from_local_datetime(&t).earliest())
+        })
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc());
+    start.with_timezone(&Local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_of_day_uses_given_timezone() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let tokyo = start_of_day(date, chrono_tz::Asia::Tokyo);
+        assert_eq!(to_db_datetime(&tokyo), "2024-04-30T15:00:00.000Z");
+
+        // サンパウロは 2018-11-04 の 0 時が存在しない
+        let sao_paulo = start_of_day(
+            NaiveDate::from_ymd_opt(2018, 11, 4).unwrap(),
+            chrono_tz::America::Sao_Paulo,
+        );
+        assert_eq!(to_db_datetime(&sao_paulo), "2018-11-04T03:00:00.000Z");
+    }
+}
@@ -1,4 +1,7 @@
+pub mod archive_policy;
+pub mod smart_list;
 pub mod sql;
 pub mod sub_task;
 pub mod task;
 pub mod task_group;
+pub mod task_query;
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+pub const ARCHIVE_POLICY_KEY: &str = "task_archive_policy";
+
+// 完了後 days 日を過ぎたタスクを自動でアーカイブする
+// 知らないうちにタスクが見えなくならないよう、設定で有効にしたときだけ動かす
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchivePolicy {
+    pub enabled: bool,
+    pub days: u32,
+}
+
+impl Default for ArchivePolicy {
+    fn default() -> Self {
+        ArchivePolicy {
+            enabled: false,
+            days: 30,
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::task::task_query::TaskQuery;
+
+// 名前付きで保存された絞り込み条件 (サイドバーに件数付きで表示する)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SmartList {
+    pub id: Uuid,
+    pub name: String,
+    pub query: TaskQuery,
+    pub order: i32,
+    pub created_at: DateTime<Local>,
+    pub updated_at: Option<DateTime<Local>>,
+    pub deleted_at: Option<DateTime<Local>>,
+}
+
+impl SmartList {
+    pub fn new() -> Self {
+        SmartList {
+            id: Uuid::new_v4(),
+            name: "".to_string(),
+            query: TaskQuery::default(),
+            order: 0,
+            created_at: Local::now(),
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    pub fn update_updated_at(&mut self) {
+        self.updated_at = Some(Local::now());
+    }
+
+    pub fn set_deleted(&mut self) {
+        self.deleted_at = Some(Local::now());
+    }
+}
+
+#[derive(Serialize)]
+pub struct SmartListWithCount {
+    #[serde(flatten)]
+    pub list: SmartList,
+    pub count: i64,
+}
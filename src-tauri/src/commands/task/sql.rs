@@ -1,6 +1,12 @@
+pub mod archive;
+pub mod archive_commands;
+pub mod smart_list;
+pub mod smart_list_commands;
 pub mod task;
 pub mod task_commands;
 pub mod task_group;
 pub mod task_group_commands;
+pub mod task_query;
 pub mod task_subtask;
+pub mod task_tag;
 pub mod task_task_group;
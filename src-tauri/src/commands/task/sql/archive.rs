@@ -0,0 +1,80 @@
+use chrono::{DateTime, Duration, Local};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::settings::timezone::now_for_db;
+use crate::commands::task::archive_policy::{ArchivePolicy, ARCHIVE_POLICY_KEY};
+use crate::commands::task::sql::task::init_task_table;
+use crate::commands::task::sql::task_query::to_sql_datetime;
+
+pub async fn load_policy(pool: &SqlitePool) -> Result<ArchivePolicy, sqlx::Error> {
+    Ok(get_setting(pool, ARCHIVE_POLICY_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+pub async fn save_policy(pool: &SqlitePool, policy: &ArchivePolicy) -> Result<(), sqlx::Error> {
+    set_setting(pool, ARCHIVE_POLICY_KEY, policy).await
+}
+
+// ポリシーに従って古い完了タスクをアーカイブし、件数を返す
+pub async fn apply_archive_policy(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let policy = load_policy(pool).await?;
+    if !policy.enabled {
+        return Ok(0);
+    }
+    archive_completed_before(pool, Local::now() - Duration::days(policy.days as i64)).await
+}
+
+pub async fn archive_completed_before(
+    pool: &SqlitePool,
+    cutoff: DateTime<Local>,
+) -> Result<u64, sqlx::Error> {
+    init_task_table(pool).await?;
+
+    // completed_at 追加前に完了したタスクは最終更新日時で判定する
+    let now = now_for_db();
+    let result = sqlx::query(
+        "UPDATE tasks SET archived_at = ?, updated_at = ?
+         WHERE completed = 1 AND archived_at IS NULL AND deleted_at IS NULL
+           AND datetime(COALESCE(completed_at, updated_at, created_at)) < ?",
+    )
+    .bind(&now)
+    .bind(&now)
+    .bind(to_sql_datetime(cutoff))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn archive_task(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+    init_task_table(pool).await?;
+
+    let now = now_for_db();
+    sqlx::query("UPDATE tasks SET archived_at = ?, updated_at = ? WHERE id = ?")
+        .bind(&now)
+        .bind(&now)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn unarchive_task(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+    init_task_table(pool).await?;
+
+    // 完了日時を取り直さないと、次回のポリシー適用ですぐに再アーカイブされてしまう
+    let now = now_for_db();
+    sqlx::query(
+        "UPDATE tasks SET archived_at = NULL, updated_at = ?,
+             completed_at = CASE WHEN completed = 1 THEN ? ELSE NULL END
+         WHERE id = ?",
+    )
+    .bind(&now)
+    .bind(&now)
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use uuid::Uuid;
+
+use crate::commands::task::archive_policy::ArchivePolicy;
+use crate::commands::task::sql::{archive, task, task_query};
+use crate::commands::task::task::Task;
+use crate::commands::task::task_query::TaskQuery;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_archive_policy(
+    state: tauri::State<'_, AppState>,
+) -> Result<ArchivePolicy, String> {
+    archive::load_policy(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_archive_policy(
+    state: tauri::State<'_, AppState>,
+    policy: ArchivePolicy,
+) -> Result<ArchivePolicy, String> {
+    archive::save_policy(&state.pool, &policy)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(policy)
+}
+
+#[tauri::command]
+pub async fn archive_completed_tasks(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Task>, String> {
+    archive::apply_archive_policy(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    task::load_all(&state.pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn archive_task(
+    state: tauri::State<'_, AppState>,
+    id: Uuid,
+) -> Result<Vec<Task>, String> {
+    archive::archive_task(&state.pool, id)
+        .await
+        .map_err(|e| e.to_string())?;
+    task::load_all(&state.pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_archived_tasks(state: tauri::State<'_, AppState>) -> Result<Vec<Task>, String> {
+    let query = TaskQuery {
+        archived: true,
+        ..TaskQuery::default()
+    };
+    task_query::query_tasks(&state.pool, &query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_archived_tasks(
+    state: tauri::State<'_, AppState>,
+    query: TaskQuery,
+) -> Result<Vec<Task>, String> {
+    let query = TaskQuery {
+        archived: true,
+        ..query
+    };
+    task_query::query_tasks(&state.pool, &query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unarchive_task(
+    state: tauri::State<'_, AppState>,
+    id: Uuid,
+) -> Result<Vec<Task>, String> {
+    archive::unarchive_task(&state.pool, id)
+        .await
+        .map_err(|e| e.to_string())?;
+    get_archived_tasks(state).await
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Local};
+use sqlx::{Row, SqlitePool};
+use uuid::Uuid;
+
+use crate::commands::settings::timezone::to_db_datetime;
+use crate::commands::task::smart_list::{SmartList, SmartListWithCount};
+use crate::commands::task::sql::task_query::count_tasks;
+use crate::commands::task::task_query::{DueFilter, TaskQuery};
+
+pub async fn init_smart_list_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS smart_lists (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            query TEXT NOT NULL,
+            order_num INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            deleted_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    // 初回のみ既定のスマートリストを作成する (削除済みの行も数えるので、消したものは復活しない)
+    let count: i64 = sqlx::query("SELECT COUNT(*) AS count FROM smart_lists")
+        .fetch_one(pool)
+        .await?
+        .try_get("count")?;
+    if count == 0 {
+        let defaults = [
+            ("Overdue", DueFilter::Overdue),
+            ("Due this week", DueFilter::ThisWeek),
+            ("No date", DueFilter::NoDate),
+        ];
+        for (order, (name, due)) in defaults.into_iter().enumerate() {
+            let mut list = SmartList::new();
+            list.name = name.to_string();
+            list.order = order as i32;
+            list.query = TaskQuery {
+                completed: Some(false),
+                due: Some(due),
+                ..TaskQuery::default()
+            };
+            insert_smart_list(pool, &list).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn insert_smart_list(pool: &SqlitePool, list: &SmartList) -> Result<(), sqlx::Error> {
+    let query = serde_json::to_string(&list.query).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let updated_at = list.updated_at.map(|dt| to_db_datetime(&dt));
+    let deleted_at = list.deleted_at.map(|dt| to_db_datetime(&dt));
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO smart_lists (id, name, query, order_num, created_at, updated_at, deleted_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(list.id.to_string())
+    .bind(&list.name)
+    .bind(query)
+    .bind(list.order)
+    .bind(to_db_datetime(&list.created_at))
+    .bind(&updated_at)
+    .bind(&deleted_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn save_smart_list(pool: &SqlitePool, list: &mut SmartList) -> Result<(), sqlx::Error> {
+    init_smart_list_table(pool).await?;
+    list.update_updated_at();
+    insert_smart_list(pool, list).await
+}
+
+pub async fn load_all(pool: &SqlitePool) -> Result<Vec<SmartList>, sqlx::Error> {
+    init_smart_list_table(pool).await?;
+
+    let rows = sqlx::query("SELECT * FROM smart_lists WHERE deleted_at IS NULL ORDER BY order_num")
+        .fetch_all(pool)
+        .await?;
+
+    let mut lists = Vec::new();
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        let id = Uuid::parse_str(&id).unwrap_or(Uuid::new_v4());
+        let name: String = row.try_get("name")?;
+        let query: String = row.try_get("query")?;
+        let query: TaskQuery = serde_json::from_str(&query).unwrap_or_default();
+        let order: i32 = row.try_get("order_num")?;
+        let created_at_str: String = row.try_get("created_at")?;
+        let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+            .unwrap_or_else(|_| Local::now().into())
+            .with_timezone(&Local);
+        let updated_at: Option<String> = row.try_get("updated_at")?;
+        let updated_at = updated_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local));
+        let deleted_at: Option<String> = row.try_get("deleted_at")?;
+        let deleted_at = deleted_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local));
+
+        lists.push(SmartList {
+            id,
+            name,
+            query,
+            order,
+            created_at,
+            updated_at,
+            deleted_at,
+        });
+    }
+    Ok(lists)
+}
+
+pub async fn load_all_with_count(
+    pool: &SqlitePool,
+) -> Result<Vec<SmartListWithCount>, sqlx::Error> {
+    let mut lists = Vec::new();
+    for list in load_all(pool).await? {
+        let count = count_tasks(pool, &list.query).await?;
+        lists.push(SmartListWithCount { list, count });
+    }
+    Ok(lists)
+}
@@ -0,0 +1,64 @@
+use crate::commands::task::smart_list::{SmartList, SmartListWithCount};
+use crate::commands::task::sql::smart_list;
+use crate::commands::task::task_query::TaskQuery;
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_smart_lists(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SmartListWithCount>, String> {
+    smart_list::load_all_with_count(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_smart_list(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    query: TaskQuery,
+) -> Result<Vec<SmartListWithCount>, String> {
+    let existing = smart_list::load_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut list = SmartList::new();
+    list.name = name;
+    list.query = query;
+    list.order = existing.iter().map(|l| l.order + 1).max().unwrap_or(0);
+    smart_list::save_smart_list(&state.pool, &mut list)
+        .await
+        .map_err(|e| e.to_string())?;
+    smart_list::load_all_with_count(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_smart_list(
+    state: tauri::State<'_, AppState>,
+    list: SmartList,
+) -> Result<Vec<SmartListWithCount>, String> {
+    let mut list = list;
+    smart_list::save_smart_list(&state.pool, &mut list)
+        .await
+        .map_err(|e| e.to_string())?;
+    smart_list::load_all_with_count(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_smart_list(
+    state: tauri::State<'_, AppState>,
+    list: SmartList,
+) -> Result<Vec<SmartListWithCount>, String> {
+    let mut list = list;
+    list.set_deleted();
+    smart_list::save_smart_list(&state.pool, &mut list)
+        .await
+        .map_err(|e| e.to_string())?;
+    smart_list::load_all_with_count(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
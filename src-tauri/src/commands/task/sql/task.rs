@@ -1,8 +1,12 @@
 use chrono::{DateTime, Local};
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
-use crate::commands::task::sql::task_subtask;
+use crate::commands::migration::add_column_if_missing;
+use crate::commands::search::indexer::index_task;
+use crate::commands::settings::timezone::to_db_datetime;
+use crate::commands::task::sql::{task_subtask, task_tag};
 use crate::commands::task::sub_task::Subtask;
 use crate::commands::task::task::Task;
 
@@ -16,6 +20,9 @@ pub async fn init_task_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
             start_date TEXT,
             end_date TEXT,
             progress INTEGER NOT NULL,
+            priority INTEGER NOT NULL DEFAULT 0,
+            completed_at TEXT,
+            archived_at TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT,
             deleted_at TEXT
@@ -23,6 +30,17 @@ pub async fn init_task_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     )
     .execute(pool)
     .await?;
+    add_column_if_missing(pool, "tasks", "priority", "INTEGER NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(pool, "tasks", "completed_at", "TEXT").await?;
+    add_column_if_missing(pool, "tasks", "archived_at", "TEXT").await?;
+    // カレンダーの期間検索用 (検索側も同じ式 datetime(...) で比較すること)
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_start_date ON tasks(datetime(start_date))")
+        .execute(pool)
+        .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tasks_end_date ON tasks(datetime(end_date))")
+        .execute(pool)
+        .await?;
+    task_tag::init_rela_task_tag_table(pool).await?;
     Ok(())
 }
 
@@ -31,14 +49,17 @@ pub async fn save_task(pool: &SqlitePool, task: &mut Task) -> Result<(), sqlx::E
     task_subtask::init_rela_task_subtask_table(pool).await?;
 
     task.update_updated_at();
-    let start_dt = task.start_datetime.map(|dt| dt.to_rfc3339());
-    let end_dt = task.end_datetime.map(|dt| dt.to_rfc3339());
-    let updated_at = task.updated_at.map(|dt| dt.to_rfc3339());
-    let deleted_at = task.deleted_at.map(|dt| dt.to_rfc3339());
+    task.sync_completed_at();
+    let start_dt = task.start_datetime.map(|dt| to_db_datetime(&dt));
+    let end_dt = task.end_datetime.map(|dt| to_db_datetime(&dt));
+    let updated_at = task.updated_at.map(|dt| to_db_datetime(&dt));
+    let deleted_at = task.deleted_at.map(|dt| to_db_datetime(&dt));
+    let completed_at = task.completed_at.map(|dt| to_db_datetime(&dt));
+    let archived_at = task.archived_at.map(|dt| to_db_datetime(&dt));
 
     sqlx::query("
-            INSERT OR REPLACE INTO tasks (id, completed, description, details, start_date, end_date, progress, created_at, updated_at, deleted_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            INSERT OR REPLACE INTO tasks (id, completed, description, details, start_date, end_date, progress, priority, completed_at, archived_at, created_at, updated_at, deleted_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(task.id.to_string())
         .bind(task.completed)
@@ -47,7 +68,10 @@ pub async fn save_task(pool: &SqlitePool, task: &mut Task) -> Result<(), sqlx::E
         .bind(&start_dt)
         .bind(&end_dt)
         .bind(task.progress)
-        .bind(task.created_at.to_rfc3339())
+        .bind(task.priority)
+        .bind(&completed_at)
+        .bind(&archived_at)
+        .bind(to_db_datetime(&task.created_at))
         .bind(&updated_at)
         .bind(&deleted_at)
         .execute(pool)
@@ -68,18 +92,39 @@ pub async fn save_task(pool: &SqlitePool, task: &mut Task) -> Result<(), sqlx::E
             .await?;
     }
 
+    task_tag::delete_rela_task_tag_by_task_id(pool, task.id.to_string()).await?;
+    for tag in &task.tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        task_tag::save_rela_task_tag(pool, task.id.to_string(), tag.to_string()).await?;
+    }
+
+    // 索引に失敗してもタスクは保存済みなので、エラーにはしない
+    if let Err(e) = index_task(pool, task).await {
+        println!("Failed to index task {}: {}", task.id, e);
+    }
     Ok(())
 }
 
 pub async fn load_all(pool: &SqlitePool) -> Result<Vec<Task>, sqlx::Error> {
     init_task_table(pool).await?;
-    let rows = sqlx::query("SELECT * FROM tasks WHERE deleted_at IS NULL")
+    let rows = sqlx::query("SELECT * FROM tasks WHERE deleted_at IS NULL AND archived_at IS NULL")
         .fetch_all(pool)
         .await?;
+    rows_to_tasks(pool, rows).await
+}
 
+// tasks テーブルの行をサブタスク・タグ込みの Task に変換する
+pub async fn rows_to_tasks(
+    pool: &SqlitePool,
+    rows: Vec<SqliteRow>,
+) -> Result<Vec<Task>, sqlx::Error> {
     let mut tasks = Vec::new();
     for row in rows {
         let id: String = row.try_get("id")?;
+        let tags = task_tag::get_tags_from_task_id(pool, &id).await?;
         let id = Uuid::parse_str(&id).unwrap_or(Uuid::new_v4());
         let completed: bool = row.try_get("completed")?;
         let description: String = row.try_get("description")?;
@@ -93,6 +138,15 @@ pub async fn load_all(pool: &SqlitePool) -> Result<Vec<Task>, sqlx::Error> {
             .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
             .map(|dt| dt.with_timezone(&Local));
         let progress: u32 = row.try_get("progress")?;
+        let priority: u32 = row.try_get("priority")?;
+        let completed_at: Option<String> = row.try_get("completed_at")?;
+        let completed_at = completed_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local));
+        let archived_at: Option<String> = row.try_get("archived_at")?;
+        let archived_at = archived_at
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local));
         let created_at_str: String = row.try_get("created_at")?;
         let created_at = DateTime::parse_from_rfc3339(&created_at_str)
             .unwrap_or_else(|_| Local::now().into())
@@ -114,6 +168,10 @@ pub async fn load_all(pool: &SqlitePool) -> Result<Vec<Task>, sqlx::Error> {
             start_datetime,
             end_datetime,
             progress,
+            priority,
+            tags,
+            completed_at,
+            archived_at,
             created_at,
             updated_at,
             deleted_at,
@@ -122,3 +180,13 @@ pub async fn load_all(pool: &SqlitePool) -> Result<Vec<Task>, sqlx::Error> {
     }
     Ok(tasks)
 }
+
+// 削除済み・アーカイブ済みも含めて1件取得する
+pub async fn load_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Task>, sqlx::Error> {
+    init_task_table(pool).await?;
+    let rows = sqlx::query("SELECT * FROM tasks WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_all(pool)
+        .await?;
+    Ok(rows_to_tasks(pool, rows).await?.into_iter().next())
+}
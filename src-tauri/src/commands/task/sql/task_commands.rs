@@ -1,8 +1,13 @@
+use crate::commands::notes::root::ensure_notes_dir;
+use crate::commands::notes::tasks::{is_note_task, write_task_completion};
 use crate::commands::task::sql::task;
+use crate::commands::task::sql::task_query;
 use crate::commands::task::sql::task_task_group::save_rela_task_task_group;
 use crate::commands::task::task::Task;
+use crate::commands::task::task_query::TaskQuery;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize)]
@@ -12,6 +17,10 @@ pub struct CreateTaskDto {
     pub start_datetime: Option<DateTime<Local>>,
     pub end_datetime: Option<DateTime<Local>>,
     pub group_id: Option<Uuid>,
+    #[serde(default)]
+    pub priority: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 use crate::AppState;
 
@@ -21,6 +30,16 @@ pub async fn get_tasks(state: tauri::State<'_, AppState>) -> Result<Vec<Task>, S
     task::load_all(&state.pool).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn query_tasks(
+    state: tauri::State<'_, AppState>,
+    query: TaskQuery,
+) -> Result<Vec<Task>, String> {
+    task_query::query_tasks(&state.pool, &query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn create_task(
     state: tauri::State<'_, AppState>,
@@ -31,6 +50,8 @@ pub async fn create_task(
     t.details = task_dto.details;
     t.start_datetime = task_dto.start_datetime;
     t.end_datetime = task_dto.end_datetime;
+    t.priority = task_dto.priority;
+    t.tags = task_dto.tags;
 
     task::save_task(&state.pool, &mut t)
         .await
@@ -45,8 +66,26 @@ pub async fn create_task(
     task::load_all(&state.pool).await.map_err(|e| e.to_string())
 }
 
+// ノートのチェックボックスから作ったタスクなら、完了状態をノートへ書き戻す
+async fn write_back_to_note(
+    app_handle: &tauri::AppHandle,
+    pool: &SqlitePool,
+    task: &Task,
+) -> Result<(), String> {
+    if !is_note_task(pool, task.id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(());
+    }
+    let root = ensure_notes_dir(app_handle, pool).await?;
+    write_task_completion(pool, &root, task).await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn update_task(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, AppState>,
     task: Task,
 ) -> Result<Vec<Task>, String> {
@@ -54,6 +93,10 @@ pub async fn update_task(
     task::save_task(&state.pool, &mut t)
         .await
         .map_err(|e| e.to_string())?;
+    // タスクは保存済みなので、ノートへの書き戻しに失敗しても保存は失敗にしない
+    if let Err(e) = write_back_to_note(&app_handle, &state.pool, &t).await {
+        println!("Failed to update the note checkbox of task {}: {}", t.id, e);
+    }
     task::load_all(&state.pool).await.map_err(|e| e.to_string())
 }
 
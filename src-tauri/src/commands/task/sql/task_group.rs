@@ -2,6 +2,7 @@ use chrono::{DateTime, Local};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
+use crate::commands::settings::timezone::to_db_datetime;
 use crate::commands::task::{
     sql::task_task_group::{get_task_id_from_task_group_id, init_rela_task_task_group_table},
     task_group::TaskGroup,
@@ -29,8 +30,8 @@ pub async fn save_task_group(
     init_task_group_table(pool).await?;
     init_rela_task_task_group_table(pool).await?;
     task_group.update_updated_at();
-    let updated_at = task_group.updated_at.map(|dt| dt.to_rfc3339());
-    let deleted_at = task_group.deleted_at.map(|dt| dt.to_rfc3339());
+    let updated_at = task_group.updated_at.map(|dt| to_db_datetime(&dt));
+    let deleted_at = task_group.deleted_at.map(|dt| to_db_datetime(&dt));
 
     sqlx::query(
         "
@@ -40,7 +41,7 @@ pub async fn save_task_group(
     )
     .bind(&task_group.id.to_string())
     .bind(&task_group.name)
-    .bind(&task_group.created_at.to_rfc3339())
+    .bind(&to_db_datetime(&task_group.created_at))
     .bind(&updated_at)
     .bind(&deleted_at)
     .execute(pool)
@@ -0,0 +1,238 @@
+use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::commands::settings::timezone::load_user_timezone;
+use crate::commands::task::sql::task::{init_task_table, rows_to_tasks};
+use crate::commands::task::sql::task_task_group::init_rela_task_task_group_table;
+use crate::commands::task::task::Task;
+use crate::commands::task::task_query::{DueFilter, TaskQuery};
+
+// SQLite の datetime() が返す形式 (UTC) に揃えて比較する
+pub fn to_sql_datetime<T: TimeZone>(dt: DateTime<T>) -> String {
+    dt.with_timezone(&Utc)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    format!("%{}%", escaped)
+}
+
+fn push_conditions(builder: &mut QueryBuilder<'_, Sqlite>, query: &TaskQuery, now: DateTime<Tz>) {
+    builder.push(" WHERE t.deleted_at IS NULL");
+    if query.archived {
+        builder.push(" AND t.archived_at IS NOT NULL");
+    } else {
+        builder.push(" AND t.archived_at IS NULL");
+    }
+
+    if let Some(group_id) = query.group_id {
+        builder
+            .push(" AND t.id IN (SELECT task_id FROM rela_task_task_group WHERE task_group_id = ")
+            .push_bind(group_id.to_string())
+            .push(")");
+    }
+
+    if let Some(completed) = query.completed {
+        builder.push(" AND t.completed = ").push_bind(completed);
+    }
+
+    match &query.due {
+        None => {}
+        Some(DueFilter::Overdue) => {
+            builder
+                .push(" AND t.completed = 0 AND t.end_date IS NOT NULL AND datetime(t.end_date) < ")
+                .push_bind(to_sql_datetime(now));
+        }
+        Some(DueFilter::NoDate) => {
+            builder.push(" AND t.start_date IS NULL AND t.end_date IS NULL");
+        }
+        Some(filter) => {
+            if let Some(range) = filter.range(now) {
+                builder.push(" AND t.end_date IS NOT NULL");
+                if let Some(from) = range.from {
+                    builder
+                        .push(" AND datetime(t.end_date) >= ")
+                        .push_bind(to_sql_datetime(from));
+                }
+                if let Some(to) = range.to {
+                    builder
+                        .push(" AND datetime(t.end_date) < ")
+                        .push_bind(to_sql_datetime(to));
+                }
+            }
+        }
+    }
+
+    if let Some(text) = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        let pattern = escape_like(text);
+        builder
+            .push(" AND (t.description LIKE ")
+            .push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR t.details LIKE ")
+            .push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+
+    if let Some(tag) = query
+        .tag
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        builder
+            .push(
+                " AND EXISTS (SELECT 1 FROM rela_task_tag rt WHERE rt.task_id = t.id AND rt.tag = ",
+            )
+            .push_bind(tag.to_string())
+            .push(" COLLATE NOCASE)");
+    }
+
+    if let Some(min_priority) = query.min_priority {
+        builder.push(" AND t.priority >= ").push_bind(min_priority);
+    }
+}
+
+pub async fn query_tasks(pool: &SqlitePool, query: &TaskQuery) -> Result<Vec<Task>, sqlx::Error> {
+    init_task_table(pool).await?;
+    init_rela_task_task_group_table(pool).await?;
+
+    let mut builder = QueryBuilder::<Sqlite>::new("SELECT t.* FROM tasks t");
+    let now = Utc::now().with_timezone(&load_user_timezone(pool).await?);
+    push_conditions(&mut builder, query, now);
+    builder.push(" ORDER BY t.end_date IS NULL, datetime(t.end_date), t.created_at");
+
+    let rows = builder.build().fetch_all(pool).await?;
+    rows_to_tasks(pool, rows).await
+}
+
+// 期間 [from, to) に期日・開始日があるか、期間をまたいで進行中のタスク
+pub async fn query_tasks_between(
+    pool: &SqlitePool,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Result<Vec<Task>, sqlx::Error> {
+    init_task_table(pool).await?;
+
+    let (from, to) = (to_sql_datetime(from), to_sql_datetime(to));
+    let rows = sqlx::query(
+        "SELECT * FROM tasks
+         WHERE deleted_at IS NULL AND archived_at IS NULL AND (
+             (datetime(end_date) >= ? AND datetime(start_date) < ?)
+             OR (start_date IS NULL AND datetime(end_date) >= ? AND datetime(end_date) < ?)
+             OR (end_date IS NULL AND datetime(start_date) >= ? AND datetime(start_date) < ?)
+         )
+         ORDER BY datetime(COALESCE(start_date, end_date))",
+    )
+    .bind(&from)
+    .bind(&to)
+    .bind(&from)
+    .bind(&to)
+    .bind(&from)
+    .bind(&to)
+    .fetch_all(pool)
+    .await?;
+    rows_to_tasks(pool, rows).await
+}
+
+pub async fn count_tasks(pool: &SqlitePool, query: &TaskQuery) -> Result<i64, sqlx::Error> {
+    init_task_table(pool).await?;
+    init_rela_task_task_group_table(pool).await?;
+
+    let mut builder = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) AS count FROM tasks t");
+    let now = Utc::now().with_timezone(&load_user_timezone(pool).await?);
+    push_conditions(&mut builder, query, now);
+
+    let row = builder.build().fetch_one(pool).await?;
+    row.try_get("count")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::task::sql::task::save_task;
+    use chrono::Duration;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_test_db() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("Failed to connect to test database")
+    }
+
+    async fn add_task(
+        pool: &SqlitePool,
+        description: &str,
+        due: Option<DateTime<Local>>,
+        tags: &[&str],
+    ) {
+        let mut task = Task::new();
+        task.description = description.to_string();
+        task.end_datetime = due;
+        task.tags = tags.iter().map(|t| t.to_string()).collect();
+        save_task(pool, &mut task)
+            .await
+            .expect("Failed to save task");
+    }
+
+    #[tokio::test]
+    async fn test_query_tasks_filters() {
+        let pool = setup_test_db().await;
+        let now = Local::now();
+        add_task(
+            &pool,
+            "Past report",
+            Some(now - Duration::days(2)),
+            &["work"],
+        )
+        .await;
+        add_task(&pool, "Future 50%_off", Some(now + Duration::days(30)), &[]).await;
+        add_task(&pool, "Someday", None, &["home"]).await;
+
+        let overdue = TaskQuery {
+            due: Some(DueFilter::Overdue),
+            ..TaskQuery::default()
+        };
+        let tasks = query_tasks(&pool, &overdue).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description, "Past report");
+        assert_eq!(tasks[0].tags, vec!["work".to_string()]);
+
+        let no_date = TaskQuery {
+            due: Some(DueFilter::NoDate),
+            ..TaskQuery::default()
+        };
+        assert_eq!(count_tasks(&pool, &no_date).await.unwrap(), 1);
+
+        let text = TaskQuery {
+            text: Some("50%_".to_string()),
+            ..TaskQuery::default()
+        };
+        assert_eq!(count_tasks(&pool, &text).await.unwrap(), 1);
+
+        let tag = TaskQuery {
+            tag: Some("HOME".to_string()),
+            ..TaskQuery::default()
+        };
+        let tasks = query_tasks(&pool, &tag).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].description, "Someday");
+
+        assert_eq!(count_tasks(&pool, &TaskQuery::default()).await.unwrap(), 3);
+    }
+}
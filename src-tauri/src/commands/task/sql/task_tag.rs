@@ -0,0 +1,53 @@
+use sqlx::{Row, SqlitePool};
+
+pub async fn init_rela_task_tag_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS rela_task_tag (
+            task_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag)
+        )",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn save_rela_task_tag(
+    pool: &SqlitePool,
+    task_id: String,
+    tag: String,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR REPLACE INTO rela_task_tag (task_id, tag) VALUES (?, ?)")
+        .bind(task_id)
+        .bind(tag)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_rela_task_tag_by_task_id(
+    pool: &SqlitePool,
+    task_id: String,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM rela_task_tag WHERE task_id = ?")
+        .bind(task_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_tags_from_task_id(
+    pool: &SqlitePool,
+    task_id: &String,
+) -> Result<Vec<String>, sqlx::Error> {
+    let rows = sqlx::query("SELECT tag FROM rela_task_tag WHERE task_id = ? ORDER BY tag")
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+    let mut tags = Vec::new();
+    for row in rows {
+        tags.push(row.try_get("tag")?);
+    }
+    Ok(tags)
+}
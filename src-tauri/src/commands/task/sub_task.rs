@@ -1,3 +1,4 @@
+use crate::commands::settings::timezone::to_db_datetime;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
@@ -46,8 +47,8 @@ impl Subtask {
     // データベース操作
     pub async fn save(&mut self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
         self.update_updated_at();
-        let updated_at = self.updated_at.map(|dt| dt.to_rfc3339());
-        let deleted_at = self.deleted_at.map(|dt| dt.to_rfc3339());
+        let updated_at = self.updated_at.map(|dt| to_db_datetime(&dt));
+        let deleted_at = self.deleted_at.map(|dt| to_db_datetime(&dt));
 
         sqlx::query(
             "INSERT OR REPLACE INTO subtasks (id, order_num, description, completed, created_at, updated_at, deleted_at)
@@ -57,7 +58,7 @@ impl Subtask {
         .bind(self.order)
         .bind(&self.description)
         .bind(self.completed)
-        .bind(self.created_at.to_rfc3339())
+        .bind(to_db_datetime(&self.created_at))
         .bind(&updated_at)
         .bind(&deleted_at)
         .execute(pool)
@@ -15,6 +15,16 @@ pub struct Task {
     pub start_datetime: Option<DateTime<Local>>,
     pub end_datetime: Option<DateTime<Local>>,
     pub progress: u32, //進捗率
+    //絞り込み
+    #[serde(default)]
+    pub priority: u32, //優先度 (0: なし, 1: 低, 2: 中, 3: 高)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    //アーカイブ
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Local>>,
     //メタ情報
     pub created_at: DateTime<Local>,
     pub updated_at: Option<DateTime<Local>>,
@@ -32,6 +42,10 @@ impl Task {
             start_datetime: None,
             end_datetime: None,
             progress: 0,
+            priority: 0,
+            tags: Vec::new(),
+            completed_at: None,
+            archived_at: None,
             created_at: Local::now(),
             updated_at: None,
             deleted_at: None,
@@ -59,4 +73,13 @@ impl Task {
     pub fn set_deleted(&mut self) {
         self.deleted_at = Some(Local::now());
     }
+
+    // 完了状態に合わせて完了日時を更新 (アーカイブ判定に使う)
+    pub fn sync_completed_at(&mut self) {
+        if !self.completed {
+            self.completed_at = None;
+        } else if self.completed_at.is_none() {
+            self.completed_at = Some(Local::now());
+        }
+    }
 }
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Datelike, Duration, Local};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::commands::settings::timezone::start_of_day;
+
+// タスクの絞り込み条件 (指定されていない条件は無視する)
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TaskQuery {
+    pub group_id: Option<Uuid>,
+    pub completed: Option<bool>,
+    pub due: Option<DueFilter>,
+    pub text: Option<String>,
+    pub tag: Option<String>,
+    pub min_priority: Option<u32>,
+    // true の場合はアーカイブ済みのタスクだけを対象にする
+    pub archived: bool,
+}
+
+// 期日 (end_datetime) による絞り込み
+// スマートリストとして保存しても古くならないよう、相対的な期間は実行時に解決する
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DueFilter {
+    Overdue,
+    Today,
+    ThisWeek,
+    NoDate,
+    Range {
+        from: Option<DateTime<Local>>,
+        to: Option<DateTime<Local>>,
+    },
+}
+
+// 期日の範囲 [from, to)
+pub struct DueRange {
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+}
+
+impl DueFilter {
+    // Overdue / NoDate は範囲ではなく専用の条件で扱う
+    // 「今日」「今週」は now のタイムゾーン (ユーザーの設定) で判定する
+    pub fn range(&self, now: DateTime<Tz>) -> Option<DueRange> {
+        let tz = now.timezone();
+        let today = now.date_naive();
+        match self {
+            DueFilter::Overdue | DueFilter::NoDate => None,
+            DueFilter::Today => Some(DueRange {
+                from: Some(start_of_day(today, tz)),
+                to: Some(start_of_day(today + Duration::days(1), tz)),
+            }),
+            DueFilter::ThisWeek => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                Some(DueRange {
+                    from: Some(start_of_day(monday, tz)),
+                    to: Some(start_of_day(monday + Duration::days(7), tz)),
+                })
+            }
+            DueFilter::Range { from, to } => Some(DueRange {
+                from: *from,
+                to: *to,
+            }),
+        }
+    }
+}
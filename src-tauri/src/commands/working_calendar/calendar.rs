@@ -0,0 +1,210 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+// アプリに同梱している祝日の一覧
+// jp.csv は 2024 年から 2027 年まで。それ以外の年は、休日の CSV を取り込む (import_holidays) まで
+// 祝日が分からないので、稼働日を問い合わせるとエラーにする
+const BUNDLED_HOLIDAYS: &[(&str, &str)] = &[("jp", include_str!("holidays/jp.csv"))];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WorkingCalendarSettings {
+    pub non_working_weekdays: Vec<Weekday>,
+    // 同梱の祝日一覧の地域 (None なら使わない)
+    pub bundled_holidays: Option<String>,
+}
+
+impl Default for WorkingCalendarSettings {
+    fn default() -> Self {
+        WorkingCalendarSettings {
+            non_working_weekdays: vec![Weekday::Sat, Weekday::Sun],
+            bundled_holidays: Some("jp".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkingDay {
+    pub date: NaiveDate,
+    pub is_working_day: bool,
+    pub holiday_name: Option<String>,
+}
+
+pub fn bundled_regions() -> Vec<String> {
+    BUNDLED_HOLIDAYS
+        .iter()
+        .map(|(region, _)| region.to_string())
+        .collect()
+}
+
+// "2024-01-01,元日" と内閣府の CSV の "2024/1/1,元日" のどちらも読める
+// 読めない行 (見出しなど) は読み飛ばす
+pub fn parse_holiday_csv(text: &str) -> Vec<(NaiveDate, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (date, name) = line.trim().split_once(',')?;
+            let date = date.trim();
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+                .ok()?;
+            Some((date, name.trim().trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+pub fn bundled_holidays(region: &str) -> Option<Vec<(NaiveDate, String)>> {
+    BUNDLED_HOLIDAYS
+        .iter()
+        .find(|(r, _)| *r == region)
+        .map(|(_, csv)| parse_holiday_csv(csv))
+}
+
+// 一度に問い合わせられる日数 (約10年)
+const MAX_DAYS: i64 = 3660;
+
+// 祝日が分かっている期間 (両端を含む)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HolidayCoverage {
+    pub from: NaiveDate,
+    pub until: NaiveDate,
+}
+
+// 同梱の祝日の年から、CSV で取り込んだ年が前後に続いている分だけ広げる
+// 1 日だけ追加した休日 (会社の休業日など) では広げない
+pub fn holiday_coverage(
+    bundled: &[(NaiveDate, String)],
+    imported_years: &BTreeSet<i32>,
+) -> Option<HolidayCoverage> {
+    let mut first = bundled.iter().map(|(date, _)| date.year()).min()?;
+    let mut last = bundled.iter().map(|(date, _)| date.year()).max()?;
+    while imported_years.contains(&(first - 1)) {
+        first -= 1;
+    }
+    while imported_years.contains(&(last + 1)) {
+        last += 1;
+    }
+    Some(HolidayCoverage {
+        from: NaiveDate::from_ymd_opt(first, 1, 1)?,
+        until: NaiveDate::from_ymd_opt(last, 12, 31)?,
+    })
+}
+
+pub struct WorkingCalendar {
+    non_working_weekdays: Vec<Weekday>,
+    holidays: HashMap<NaiveDate, String>,
+    // 祝日が分かっている期間 (None なら祝日の一覧を使っていない)
+    coverage: Option<HolidayCoverage>,
+}
+
+impl WorkingCalendar {
+    pub fn new(
+        non_working_weekdays: Vec<Weekday>,
+        holidays: Vec<(NaiveDate, String)>,
+        coverage: Option<HolidayCoverage>,
+    ) -> Self {
+        WorkingCalendar {
+            non_working_weekdays,
+            holidays: holidays.into_iter().collect(),
+            coverage,
+        }
+    }
+
+    pub fn holiday_name(&self, date: NaiveDate) -> Option<&str> {
+        self.holidays.get(&date).map(|s| s.as_str())
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        !self.non_working_weekdays.contains(&date.weekday()) && !self.holidays.contains_key(&date)
+    }
+
+    // start から end まで (両端を含む)
+    // 祝日が分からない年にかかる場合や、期間が長すぎる場合はエラー
+    pub fn days(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<WorkingDay>, String> {
+        if (end - start).num_days() >= MAX_DAYS {
+            return Err(format!("Date range must be shorter than {} days", MAX_DAYS));
+        }
+        if let Some(coverage) = self
+            .coverage
+            .filter(|coverage| start < coverage.from || end > coverage.until)
+        {
+            return Err(format!(
+                "Holidays are only known from {} to {}. Import a holiday list for other years",
+                coverage.from, coverage.until
+            ));
+        }
+        Ok(start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .map(|date| WorkingDay {
+                date,
+                is_working_day: self.is_working_day(date),
+                holiday_name: self.holiday_name(date).map(|s| s.to_string()),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_working_days_skip_weekends_and_holidays() {
+        let settings = WorkingCalendarSettings::default();
+        let holidays = bundled_holidays("jp").unwrap();
+        let coverage = holiday_coverage(&holidays, &BTreeSet::new());
+        assert_eq!(
+            coverage,
+            Some(HolidayCoverage {
+                from: date(2024, 1, 1),
+                until: date(2027, 12, 31)
+            })
+        );
+        let calendar = WorkingCalendar::new(settings.non_working_weekdays, holidays, coverage);
+
+        // 2025-05-02 (金) の次の稼働日はゴールデンウィーク明けの 05-07 (水)
+        let working: Vec<NaiveDate> = calendar
+            .days(date(2025, 5, 1), date(2025, 5, 9))
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.is_working_day)
+            .map(|d| d.date)
+            .collect();
+        assert_eq!(
+            working,
+            vec![
+                date(2025, 5, 1),
+                date(2025, 5, 2),
+                date(2025, 5, 7),
+                date(2025, 5, 8),
+                date(2025, 5, 9)
+            ]
+        );
+        assert_eq!(calendar.holiday_name(date(2025, 5, 5)), Some("こどもの日"));
+
+        // 祝日が分からない年と長すぎる期間はエラー
+        assert!(calendar.days(date(2027, 12, 1), date(2028, 1, 5)).is_err());
+        assert!(calendar.days(date(2023, 12, 25), date(2024, 1, 5)).is_err());
+        assert!(calendar.days(date(2000, 1, 1), date(2027, 1, 1)).is_err());
+        assert_eq!(
+            parse_holiday_csv("国民の祝日・休日月日,名称\n2025/1/1,元日"),
+            vec![(date(2025, 1, 1), "元日".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_holiday_coverage_extends_only_with_adjacent_imported_years() {
+        let holidays = bundled_holidays("jp").unwrap();
+        let coverage =
+            |years: &[i32]| holiday_coverage(&holidays, &years.iter().copied().collect()).unwrap();
+        // 離れた年を取り込んでも、間の年は分からないまま
+        assert_eq!(coverage(&[2030]).until, date(2027, 12, 31));
+        assert_eq!(coverage(&[2028, 2029]).until, date(2029, 12, 31));
+        assert_eq!(coverage(&[2023]).from, date(2023, 1, 1));
+    }
+}
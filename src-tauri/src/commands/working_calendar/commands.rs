@@ -0,0 +1,106 @@
+use chrono::{Datelike, NaiveDate};
+use std::fs;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::commands::working_calendar::calendar::{
+    bundled_regions, parse_holiday_csv, WorkingCalendarSettings, WorkingDay,
+};
+use crate::commands::working_calendar::db::{
+    add_imported_years, delete_user_holiday, load_settings, load_user_holidays,
+    load_working_calendar, save_settings, save_user_holiday, Holiday,
+};
+use crate::AppState;
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| format!("Invalid date {}: {}", date, e))
+}
+
+#[tauri::command]
+pub async fn get_working_calendar_settings(
+    state: State<'_, AppState>,
+) -> Result<WorkingCalendarSettings, String> {
+    load_settings(&state.pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_working_calendar_settings(
+    state: State<'_, AppState>,
+    settings: WorkingCalendarSettings,
+) -> Result<WorkingCalendarSettings, String> {
+    if let Some(region) = &settings.bundled_holidays {
+        if !bundled_regions().contains(region) {
+            return Err(format!("Unknown holiday region: {}", region));
+        }
+    }
+    save_settings(&state.pool, &settings)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_holiday_regions() -> Result<Vec<String>, String> {
+    Ok(bundled_regions())
+}
+
+// start_date から end_date まで (両端を含む) の各日が稼働日かどうか
+// 祝日が分からない年にかかる場合と、約10年を超える期間はエラー
+#[tauri::command]
+pub async fn get_working_days(
+    state: State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<WorkingDay>, String> {
+    let calendar = load_working_calendar(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    calendar.days(parse_date(&start_date)?, parse_date(&end_date)?)
+}
+
+#[tauri::command]
+pub async fn get_user_holidays(state: State<'_, AppState>) -> Result<Vec<Holiday>, String> {
+    load_user_holidays(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn add_user_holiday(
+    state: State<'_, AppState>,
+    date: String,
+    name: String,
+) -> Result<Vec<Holiday>, String> {
+    save_user_holiday(&state.pool, parse_date(&date)?, &name)
+        .await
+        .map_err(|e| e.to_string())?;
+    load_user_holidays(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_holiday(state: State<'_, AppState>, id: Uuid) -> Result<Vec<Holiday>, String> {
+    delete_user_holiday(&state.pool, id)
+        .await
+        .map_err(|e| e.to_string())?;
+    load_user_holidays(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// "日付,名前" の CSV から休日を取り込み、取り込んだ件数を返す
+#[tauri::command]
+pub async fn import_holidays(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    let text = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let holidays = parse_holiday_csv(&text);
+    for (date, name) in &holidays {
+        save_user_holiday(&state.pool, *date, name)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    add_imported_years(&state.pool, holidays.iter().map(|(date, _)| date.year()))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(holidays.len())
+}
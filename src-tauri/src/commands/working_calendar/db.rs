@@ -0,0 +1,181 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+use crate::commands::settings::db::{get_setting, set_setting};
+use crate::commands::settings::timezone::now_for_db;
+use crate::commands::working_calendar::calendar::{
+    bundled_holidays, holiday_coverage, WorkingCalendar, WorkingCalendarSettings,
+};
+
+pub const WORKING_CALENDAR_KEY: &str = "working_calendar";
+// 休日の CSV を取り込んだ年 (祝日が分かっている期間を広げる)
+pub const IMPORTED_HOLIDAY_YEARS_KEY: &str = "imported_holiday_years";
+
+// ユーザーが追加した休日 (会社の休業日など)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Holiday {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub name: String,
+    //メタ情報
+    pub created_at: DateTime<Local>,
+    pub updated_at: Option<DateTime<Local>>,
+    pub deleted_at: Option<DateTime<Local>>,
+}
+
+pub async fn init_holiday_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS holidays (
+            id TEXT PRIMARY KEY,
+            date TEXT NOT NULL,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT,
+            deleted_at TEXT
+        )",
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_holidays_date ON holidays(date) WHERE deleted_at IS NULL",
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn row_to_holiday(row: &SqliteRow) -> Result<Holiday, sqlx::Error> {
+    let parse = |s: String| {
+        DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&Local))
+            .ok()
+    };
+
+    let id: String = row.try_get("id")?;
+    let date: String = row.try_get("date")?;
+    let created_at: String = row.try_get("created_at")?;
+    let updated_at: Option<String> = row.try_get("updated_at")?;
+    let deleted_at: Option<String> = row.try_get("deleted_at")?;
+
+    Ok(Holiday {
+        id: Uuid::parse_str(&id).unwrap_or(Uuid::new_v4()),
+        date: NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        name: row.try_get("name")?,
+        created_at: parse(created_at).unwrap_or_else(Local::now),
+        updated_at: updated_at.and_then(parse),
+        deleted_at: deleted_at.and_then(parse),
+    })
+}
+
+pub async fn load_user_holidays(pool: &SqlitePool) -> Result<Vec<Holiday>, sqlx::Error> {
+    init_holiday_table(pool).await?;
+
+    let rows = sqlx::query("SELECT * FROM holidays WHERE deleted_at IS NULL ORDER BY date")
+        .fetch_all(pool)
+        .await?;
+    rows.iter().map(row_to_holiday).collect()
+}
+
+// 同じ日付の休日があれば名前を上書きする
+pub async fn save_user_holiday(
+    pool: &SqlitePool,
+    date: NaiveDate,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    init_holiday_table(pool).await?;
+
+    let now = now_for_db();
+    let date = date.format("%Y-%m-%d").to_string();
+    let updated = sqlx::query(
+        "UPDATE holidays SET name = ?, updated_at = ? WHERE date = ? AND deleted_at IS NULL",
+    )
+    .bind(name)
+    .bind(&now)
+    .bind(&date)
+    .execute(pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        sqlx::query("INSERT INTO holidays (id, date, name, created_at) VALUES (?, ?, ?, ?)")
+            .bind(Uuid::new_v4().to_string())
+            .bind(&date)
+            .bind(name)
+            .bind(&now)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn delete_user_holiday(pool: &SqlitePool, id: Uuid) -> Result<(), sqlx::Error> {
+    init_holiday_table(pool).await?;
+
+    let now = now_for_db();
+    sqlx::query("UPDATE holidays SET deleted_at = ?, updated_at = ? WHERE id = ?")
+        .bind(&now)
+        .bind(&now)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn load_imported_years(pool: &SqlitePool) -> Result<BTreeSet<i32>, sqlx::Error> {
+    Ok(get_setting(pool, IMPORTED_HOLIDAY_YEARS_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+pub async fn add_imported_years(
+    pool: &SqlitePool,
+    years: impl IntoIterator<Item = i32>,
+) -> Result<(), sqlx::Error> {
+    let mut imported = load_imported_years(pool).await?;
+    imported.extend(years);
+    set_setting(pool, IMPORTED_HOLIDAY_YEARS_KEY, &imported).await
+}
+
+pub async fn load_settings(pool: &SqlitePool) -> Result<WorkingCalendarSettings, sqlx::Error> {
+    Ok(get_setting(pool, WORKING_CALENDAR_KEY)
+        .await?
+        .unwrap_or_default())
+}
+
+pub async fn save_settings(
+    pool: &SqlitePool,
+    settings: &WorkingCalendarSettings,
+) -> Result<(), sqlx::Error> {
+    set_setting(pool, WORKING_CALENDAR_KEY, settings).await
+}
+
+// 設定・同梱の祝日・ユーザーの休日をまとめた稼働日カレンダー
+pub async fn load_working_calendar(pool: &SqlitePool) -> Result<WorkingCalendar, sqlx::Error> {
+    let settings = load_settings(pool).await?;
+
+    let bundled = settings
+        .bundled_holidays
+        .as_deref()
+        .and_then(bundled_holidays);
+    // 祝日が分かっている期間は、同梱の祝日と CSV で取り込んだ年だけから決める
+    let coverage = match &bundled {
+        Some(bundled) => holiday_coverage(bundled, &load_imported_years(pool).await?),
+        None => None,
+    };
+    let mut holidays = bundled.unwrap_or_default();
+    holidays.extend(
+        load_user_holidays(pool)
+            .await?
+            .into_iter()
+            .map(|h| (h.date, h.name)),
+    );
+    Ok(WorkingCalendar::new(
+        settings.non_working_weekdays,
+        holidays,
+        coverage,
+    ))
+}
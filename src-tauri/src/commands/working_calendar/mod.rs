@@ -0,0 +1,3 @@
+pub mod calendar;
+pub mod commands;
+pub mod db;
@@ -1,5 +1,7 @@
 mod commands;
 
+use crate::commands::task::sql::archive_commands::*;
+use crate::commands::task::sql::smart_list_commands::*;
 use crate::commands::task::sql::task_commands::*;
 use crate::commands::task::sql::task_group_commands::*;
 use sqlx::sqlite::SqliteConnectOptions;
@@ -47,21 +49,101 @@ pub fn run() {
         .setup(move |app| {
             let pool = rt.block_on(setup_pool(&app.handle()));
             rt.block_on(init_db(&pool));
+            if let Err(e) = rt.block_on(commands::migration::normalize_datetimes_to_utc(&pool)) {
+                println!("Failed to convert stored datetimes to UTC: {}", e);
+            }
+            if let Err(e) = rt.block_on(commands::task::sql::archive::apply_archive_policy(&pool)) {
+                println!("Failed to archive completed tasks: {}", e);
+            }
+            app.manage(commands::notes::watcher::NotesWatcher::default());
+            app.manage(commands::notes::attachments::DroppedFiles::default());
+            match rt.block_on(commands::notes::root::ensure_notes_dir(app.handle(), &pool)) {
+                Ok(root) => {
+                    if let Err(e) =
+                        rt.block_on(commands::notes::trash::purge_expired_trash(&pool, &root))
+                    {
+                        println!("Failed to empty expired notes from the trash: {}", e);
+                    }
+                    if let Err(e) = commands::notes::watcher::watch_notes_root(
+                        app.handle(),
+                        pool.clone(),
+                        root.clone(),
+                    ) {
+                        println!("Failed to watch the notes folder: {}", e);
+                    }
+                    let handle = app.handle().clone();
+                    let pool = pool.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) =
+                            commands::search::indexer::prepare_index(&handle, &pool, &root).await
+                        {
+                            println!("Failed to prepare the search index: {}", e);
+                        }
+                    });
+                }
+                Err(e) => println!("Failed to prepare the notes folder: {}", e),
+            }
             app.manage(AppState { pool });
             Ok(())
         })
+        // ドロップされたファイルのパスは webview を経由させずにここで受け取る
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+                window
+                    .state::<commands::notes::attachments::DroppedFiles>()
+                    .set(paths.clone());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             create_task,
             get_tasks,
+            query_tasks,
             update_task,
             delete_task,
             get_task_groups,
             create_task_group,
             update_task_group,
             delete_task_group,
+            get_smart_lists,
+            create_smart_list,
+            update_smart_list,
+            delete_smart_list,
+            get_archive_policy,
+            update_archive_policy,
+            archive_completed_tasks,
+            archive_task,
+            get_archived_tasks,
+            search_archived_tasks,
+            unarchive_task,
             commands::calendar::memo::get_memos,
             commands::calendar::memo::save_memo,
             commands::calendar::memo::delete_memo,
+            commands::calendar::memo::get_memo_versions,
+            commands::calendar::memo::restore_memo_version,
+            commands::calendar::journal::get_daily_template,
+            commands::calendar::journal::update_daily_template,
+            commands::calendar::journal::open_daily_memo,
+            commands::working_calendar::commands::get_working_calendar_settings,
+            commands::working_calendar::commands::update_working_calendar_settings,
+            commands::working_calendar::commands::get_holiday_regions,
+            commands::working_calendar::commands::get_working_days,
+            commands::working_calendar::commands::get_user_holidays,
+            commands::working_calendar::commands::add_user_holiday,
+            commands::working_calendar::commands::delete_holiday,
+            commands::working_calendar::commands::import_holidays,
+            commands::settings::commands::get_timezone_settings,
+            commands::settings::commands::update_user_timezone,
+            commands::review::commands::get_review_report,
+            commands::review::commands::save_review_note,
+            commands::search::commands::search,
+            commands::search::commands::rebuild_search_index,
+            commands::calendar::event::get_events,
+            commands::calendar::event::create_event,
+            commands::calendar::event::update_event,
+            commands::calendar::event::delete_event,
+            commands::calendar::items::get_calendar_items,
+            commands::ical::commands::export_ics,
+            commands::ical::commands::import_ics,
             commands::notes::fs::get_notes_tree,
             commands::notes::fs::read_note,
             commands::notes::fs::save_note,
@@ -69,6 +151,36 @@ pub fn run() {
             commands::notes::fs::create_folder,
             commands::notes::fs::delete_item,
             commands::notes::fs::rename_item,
+            commands::notes::fs::move_item,
+            commands::notes::fs::duplicate_item,
+            commands::notes::attachments::save_attachment,
+            commands::notes::attachments::attach_dropped_files,
+            commands::notes::attachments::cleanup_attachments,
+            commands::notes::export::export_notes_html,
+            commands::notes::import::notion::import_notion_export,
+            commands::notes::import::obsidian::import_obsidian_vault,
+            commands::notes::links::get_backlinks,
+            commands::notes::links::get_outgoing_links,
+            commands::notes::links::get_unresolved_links,
+            commands::notes::meta::get_note_meta,
+            commands::notes::meta::list_notes_meta,
+            commands::notes::meta::get_note_tags,
+            commands::notes::tasks::list_note_tasks,
+            commands::notes::tasks::promote_note_task,
+            commands::notes::tasks::set_note_task_completed,
+            commands::notes::trash::list_trash,
+            commands::notes::trash::restore_trash_item,
+            commands::notes::trash::purge_trash,
+            commands::notes::trash::get_trash_policy,
+            commands::notes::trash::update_trash_policy,
+            commands::notes::versions::get_note_versions,
+            commands::notes::versions::read_note_version,
+            commands::notes::versions::diff_note_versions,
+            commands::notes::versions::restore_note_version,
+            commands::notes::versions::get_note_version_policy,
+            commands::notes::versions::update_note_version_policy,
+            commands::notes::root::get_notes_root,
+            commands::notes::root::set_notes_root,
             commands::books::commands::get_books,
             commands::books::commands::create_book,
             commands::books::commands::update_book,
@@ -1,6 +1,7 @@
 import React, { useMemo, useRef, useEffect, useState } from "react";
 import { Task, TaskGroup } from "../../type";
 import { getTaskGroups } from "../../tauri/to_do_list_api";
+import { getWorkingDays, WorkingDay } from "../../tauri/working_calendar_api";
 
 interface GanttChartViewProps {
 	tasks: Task[];
@@ -10,6 +11,9 @@ const PIXELS_PER_DAY = 50;
 const ROW_HEIGHT = 40;
 const SIDEBAR_WIDTH = 250;
 
+const toDateKey = (date: Date) =>
+	`${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, "0")}-${String(date.getDate()).padStart(2, "0")}`;
+
 const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 	const sidebarRef = useRef<HTMLDivElement>(null);
 	const headerRef = useRef<HTMLDivElement>(null);
@@ -94,14 +98,57 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 		return { minDate: min, maxDate: max, totalDays: days };
 	}, [groupedData]);
 
+	// 4. Working days (weekends and holidays are shaded and bars skip them)
+	const [workingDays, setWorkingDays] = useState<Map<string, WorkingDay>>(new Map());
+	const [workingDaysError, setWorkingDaysError] = useState<string | null>(null);
+
+	useEffect(() => {
+		const end = new Date(minDate);
+		end.setDate(end.getDate() + totalDays);
+		getWorkingDays(toDateKey(minDate), toDateKey(end))
+			.then(days => {
+				setWorkingDays(new Map(days.map(d => [d.date, d])));
+				setWorkingDaysError(null);
+			})
+			.catch(e => {
+				// 祝日が分からない年にかかる場合など。休みを塗らずに表示する
+				setWorkingDays(new Map());
+				setWorkingDaysError(String(e));
+			});
+	}, [minDate, totalDays]);
+
+	const isNonWorkingDay = (date: Date) => workingDays.get(toDateKey(date))?.is_working_day === false;
+
+	// minDate の日を 0 とした日の番号
+	const dayIndex = (dateStr: string) => {
+		const d = new Date(dateStr);
+		d.setHours(0, 0, 0, 0);
+		const base = new Date(minDate);
+		base.setHours(0, 0, 0, 0);
+		return Math.round((d.getTime() - base.getTime()) / (1000 * 60 * 60 * 24));
+	};
+
+	// 稼働日が続く区間ごとにバーを分ける (週末・休日はバーを切る)
+	const workingSegments = (startStr: string, endStr: string) => {
+		const first = dayIndex(startStr);
+		const last = dayIndex(endStr);
+		const segments: { start: number; length: number }[] = [];
+		for (let i = first; i <= last; i++) {
+			const d = new Date(minDate);
+			d.setDate(d.getDate() + i);
+			if (isNonWorkingDay(d)) continue;
+			const prev = segments[segments.length - 1];
+			if (prev && prev.start + prev.length === i) {
+				prev.length++;
+			} else {
+				segments.push({ start: i, length: 1 });
+			}
+		}
+		return segments;
+	};
+
 	// Format helpers
 	const formatDate = (date: Date) => `${date.getMonth() + 1}/${date.getDate()}`;
-	const getXForDate = (dateStr: string) => {
-		const date = new Date(dateStr);
-		const diffTime = date.getTime() - minDate.getTime();
-		const diffDays = diffTime / (1000 * 60 * 60 * 24);
-		return diffDays * PIXELS_PER_DAY;
-	};
 
 	const chartWidth = totalDays * PIXELS_PER_DAY;
 
@@ -128,8 +175,9 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 							const d = new Date(minDate);
 							d.setDate(d.getDate() + i);
 							const isToday = new Date().toDateString() === d.toDateString();
+							const holiday = workingDays.get(toDateKey(d))?.holiday_name;
 							return (
-								<div key={i} className={`shrink-0 flex justify-center items-center border-r border-border-secondary text-xs ${isToday ? "bg-accent-light text-accent-secondary font-bold" : ""}`} style={{ width: PIXELS_PER_DAY }}>
+								<div key={i} className={`shrink-0 flex justify-center items-center border-r border-border-secondary text-xs ${isToday ? "bg-accent-light text-accent-secondary font-bold" : isNonWorkingDay(d) ? "bg-bg-tertiary text-text-secondary" : ""}`} style={{ width: PIXELS_PER_DAY }} title={holiday ?? undefined}>
 									{formatDate(d)}
 								</div>
 							);
@@ -140,6 +188,12 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 
 			{/* Body */}
 			<div className="flex flex-row flex-1 min-h-0 relative">
+				{workingDaysError && (
+					<div className="absolute bottom-2 right-4 z-30 px-3 py-1 rounded-md bg-bg-primary border border-border-primary text-xs text-text-secondary">
+						{workingDaysError}
+					</div>
+				)}
+
 				{/* Sidebar */}
 				<div className="shrink-0 overflow-hidden border-r border-border-primary bg-bg-primary" style={{ width: SIDEBAR_WIDTH }} ref={sidebarRef}>
 					{groupedData.map(group => (
@@ -169,7 +223,7 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 								d.setDate(d.getDate() + i);
 								const isToday = new Date().toDateString() === d.toDateString();
 								return (
-									<div key={i} className={`border-r border-border-secondary shrink-0 h-full ${isToday ? "bg-accent-light opacity-70" : ""}`} style={{ width: PIXELS_PER_DAY }} />
+									<div key={i} className={`border-r border-border-secondary shrink-0 h-full ${isToday ? "bg-accent-light opacity-70" : isNonWorkingDay(d) ? "bg-bg-tertiary opacity-60" : ""}`} style={{ width: PIXELS_PER_DAY }} />
 								);
 							})}
 						</div>
@@ -182,9 +236,14 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 
 								{/* Tasks */}
 								{group.tasks.map(task => {
-									const x = getXForDate(task.start_datetime!);
-									const endX = getXForDate(task.end_datetime!) + PIXELS_PER_DAY;
-									const width = Math.max(PIXELS_PER_DAY, endX - x);
+									const working = workingSegments(task.start_datetime!, task.end_datetime!);
+									const workingDayCount = working.reduce((acc, s) => acc + s.length, 0);
+									// 稼働日が1日もないタスクは期間全体に描く
+									const first = dayIndex(task.start_datetime!);
+									const segments = working.length > 0
+										? working
+										: [{ start: first, length: Math.max(1, dayIndex(task.end_datetime!) - first + 1) }];
+									const totalLength = segments.reduce((acc, s) => acc + s.length, 0);
 
 									let progressPercent = 0;
 									if (task.subtasks && task.subtasks.length > 0) {
@@ -194,13 +253,23 @@ const GanttChartView: React.FC<GanttChartViewProps> = ({ tasks }) => {
 										progressPercent = task.completed ? 100 : 0;
 									}
 
+									// 進捗は稼働日の先頭から順に塗る
+									const filledDays = (progressPercent / 100) * totalLength;
+									let offset = 0;
+
 									return (
 										<div key={task.id} className="relative border-b border-transparent hover:bg-black/5" style={{ height: ROW_HEIGHT }}>
-											<div className="absolute ml-1 rounded-md shadow-sm overflow-hidden bg-bg-tertiary top-1"
-												style={{ left: x, width: width - 4, height: ROW_HEIGHT - 8 }}
-												title={`${task.description} (${Math.round(progressPercent)}%)`}>
-												<div className="h-full bg-accent-primary opacity-80" style={{ width: `${progressPercent}%` }} />
-											</div>
+											{segments.map(segment => {
+												const filled = Math.min(Math.max(filledDays - offset, 0), segment.length);
+												offset += segment.length;
+												return (
+													<div key={segment.start} className="absolute ml-1 rounded-md shadow-sm overflow-hidden bg-bg-tertiary top-1"
+														style={{ left: segment.start * PIXELS_PER_DAY, width: segment.length * PIXELS_PER_DAY - 4, height: ROW_HEIGHT - 8 }}
+														title={`${task.description} (${Math.round(progressPercent)}%, ${workingDayCount} working days)`}>
+														<div className="h-full bg-accent-primary opacity-80" style={{ width: `${(filled / segment.length) * 100}%` }} />
+													</div>
+												);
+											})}
 										</div>
 									);
 								})}
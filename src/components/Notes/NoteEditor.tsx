@@ -1,4 +1,4 @@
-import React, { useState, useEffect } from "react";
+import React, { useState, useEffect, useRef } from "react";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
 import { saveNote } from "../../tauri/notes_api";
@@ -6,15 +6,32 @@ import { saveNote } from "../../tauri/notes_api";
 interface NoteEditorProps {
     path: string | null;
     initialContent: string;
+    // 開いているノートの名前が変わった場合の変更前のパス
+    renamedFrom?: string | null;
     onSaveSuccess: () => void;
 }
 
-const NoteEditor: React.FC<NoteEditorProps> = ({ path, initialContent, onSaveSuccess }) => {
+const NoteEditor: React.FC<NoteEditorProps> = ({ path, initialContent, renamedFrom = null, onSaveSuccess }) => {
     const [content, setContent] = useState(initialContent);
     const [isDirty, setIsDirty] = useState(false);
 
+    // 表示中のノートと、最後に読み込んだ (保存した) ファイルの内容
+    const shownPath = useRef<string | null>(null);
+    const lastLoaded = useRef(initialContent);
+
     // Reset content when path changes
+    // 同じノートがアプリの外で書き換えられた場合は、未保存の内容と比べてから読み直す
     useEffect(() => {
+        const sameNote = shownPath.current !== null
+            && (shownPath.current === path || shownPath.current === renamedFrom);
+        shownPath.current = path;
+        const unchangedOnDisk = initialContent === lastLoaded.current;
+        lastLoaded.current = initialContent;
+        if (sameNote && isDirty && content !== initialContent) {
+            if (unchangedOnDisk || !confirm("This note was changed outside the editor. Discard your unsaved changes and reload it?")) {
+                return;
+            }
+        }
         setContent(initialContent);
         setIsDirty(false);
     }, [path, initialContent]);
@@ -28,6 +45,7 @@ const NoteEditor: React.FC<NoteEditorProps> = ({ path, initialContent, onSaveSuc
         if (!path) return;
         try {
             await saveNote(path, content);
+            lastLoaded.current = content;
             setIsDirty(false);
             onSaveSuccess();
         } catch (e) {
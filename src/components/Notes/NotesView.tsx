@@ -1,12 +1,19 @@
-import React, { useState, useEffect } from "react";
+import React, { useState, useEffect, useRef } from "react";
 import NotesSidebar from "./NotesSidebar";
 import NoteEditor from "./NoteEditor";
-import { getNotesTree, readNote, createNote, createFolder, deleteItem, renameItem, FileNode } from "../../tauri/notes_api";
+import { getNotesTree, readNote, createNote, createFolder, deleteItem, renameItem, onNotesChanged, FileNode } from "../../tauri/notes_api";
 
 const NotesView: React.FC = () => {
 	const [fileTree, setFileTree] = useState<FileNode[]>([]);
 	const [currentFile, setCurrentFile] = useState<string | null>(null);
 	const [fileContent, setFileContent] = useState<string>("");
+	// 開いているノートが名前変更された場合の変更前のパス (エディタの内容を引き継ぐ)
+	const [renamedFrom, setRenamedFrom] = useState<string | null>(null);
+	const currentFileRef = useRef<string | null>(null);
+
+	useEffect(() => {
+		currentFileRef.current = currentFile;
+	}, [currentFile]);
 
 	const refreshTree = () => {
 		getNotesTree().then(setFileTree).catch(console.error);
@@ -16,9 +23,45 @@ const NotesView: React.FC = () => {
 		refreshTree();
 	}, []);
 
+	// 他のエディタや同期ツールでフォルダが変わったらツリーを読み直す
+	// 開いているノートが変わった場合は読み直し、エディタ側で未保存の内容と比べる
+	useEffect(() => {
+		const reload = (path: string) => {
+			readNote(path)
+				.then((content) => {
+					if (currentFileRef.current === path) {
+						setFileContent(content);
+					}
+				})
+				.catch(console.error);
+		};
+		const unlisten = onNotesChanged((changes) => {
+			refreshTree();
+			for (const change of changes) {
+				const current = currentFileRef.current;
+				if (change.kind === "renamed" && change.from === current) {
+					currentFileRef.current = change.path;
+					setRenamedFrom(current);
+					setCurrentFile(change.path);
+					reload(change.path);
+				} else if ((change.kind === "modified" || change.kind === "created") && change.path === current) {
+					reload(current);
+				} else if (change.kind === "deleted" && change.path === current) {
+					currentFileRef.current = null;
+					setCurrentFile(null);
+					setFileContent("");
+				}
+			}
+		});
+		return () => {
+			unlisten.then((f) => f());
+		};
+	}, []);
+
 	const handleSelectFile = async (path: string) => {
 		try {
 			const content = await readNote(path);
+			setRenamedFrom(null);
 			setCurrentFile(path);
 			setFileContent(content);
 		} catch (e) {
@@ -70,6 +113,7 @@ const NotesView: React.FC = () => {
 			const newPath = await renameItem(path, newName);
 			// If renaming current file, update state
 			if (currentFile === path) {
+				setRenamedFrom(path);
 				setCurrentFile(newPath);
 			}
 			refreshTree();
@@ -92,6 +136,7 @@ const NotesView: React.FC = () => {
 			<NoteEditor
 				path={currentFile}
 				initialContent={fileContent}
+				renamedFrom={renamedFrom}
 				onSaveSuccess={() => { }}
 			/>
 		</div>
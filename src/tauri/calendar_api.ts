@@ -1,8 +1,21 @@
 import { invoke } from "@tauri-apps/api/core";
+import { CalendarEvent, CreateEventDto, ScheduleEvent } from "../type/calendar";
 
 export interface CalendarMemo {
+    id?: string;
     date: string;
     content: string;
+    created_at?: string;
+    updated_at?: string;
+    deleted_at?: string;
+}
+
+export interface CalendarMemoVersion {
+    id: string;
+    memo_id: string;
+    date: string;
+    content: string;
+    created_at: string;
 }
 
 export async function getMemos(startDate: string, endDate: string): Promise<CalendarMemo[]> {
@@ -31,3 +44,141 @@ export async function deleteMemo(date: string): Promise<void> {
         throw e;
     }
 }
+
+export async function getMemoVersions(date: string): Promise<CalendarMemoVersion[]> {
+    try {
+        return await invoke<CalendarMemoVersion[]>("get_memo_versions", { date });
+    } catch (e) {
+        console.error("getMemoVersions failed", e);
+        throw e;
+    }
+}
+
+export async function restoreMemoVersion(versionId: string): Promise<CalendarMemo> {
+    try {
+        return await invoke<CalendarMemo>("restore_memo_version", { versionId });
+    } catch (e) {
+        console.error("restoreMemoVersion failed", e);
+        throw e;
+    }
+}
+
+export interface DailyTemplate {
+    content: string;
+    store_as_note: boolean;
+    note_folder: string;
+}
+
+export interface DailyMemo {
+    memo: CalendarMemo;
+    note_path?: string;
+}
+
+export async function getDailyTemplate(): Promise<DailyTemplate> {
+    try {
+        return await invoke<DailyTemplate>("get_daily_template");
+    } catch (e) {
+        console.error("getDailyTemplate failed", e);
+        throw e;
+    }
+}
+
+export async function updateDailyTemplate(template: DailyTemplate): Promise<DailyTemplate> {
+    try {
+        return await invoke<DailyTemplate>("update_daily_template", { template });
+    } catch (e) {
+        console.error("updateDailyTemplate failed", e);
+        throw e;
+    }
+}
+
+export async function openDailyMemo(date: string): Promise<DailyMemo> {
+    try {
+        return await invoke<DailyMemo>("open_daily_memo", { date });
+    } catch (e) {
+        console.error("openDailyMemo failed", e);
+        throw e;
+    }
+}
+
+export async function getEvents(start: string, end: string): Promise<ScheduleEvent[]> {
+    try {
+        return await invoke<ScheduleEvent[]>("get_events", { start, end });
+    } catch (e) {
+        console.error("getEvents failed", e);
+        throw e;
+    }
+}
+
+export async function createEvent(eventDto: CreateEventDto): Promise<ScheduleEvent> {
+    try {
+        return await invoke<ScheduleEvent>("create_event", { eventDto });
+    } catch (e) {
+        console.error("createEvent failed", e);
+        throw e;
+    }
+}
+
+export async function updateEvent(event: ScheduleEvent): Promise<ScheduleEvent> {
+    try {
+        return await invoke<ScheduleEvent>("update_event", { event });
+    } catch (e) {
+        console.error("updateEvent failed", e);
+        throw e;
+    }
+}
+
+export async function deleteEvent(id: string): Promise<void> {
+    try {
+        await invoke("delete_event", { id });
+    } catch (e) {
+        console.error("deleteEvent failed", e);
+        throw e;
+    }
+}
+
+// startDate から endDate まで (両端を含む, YYYY-MM-DD) のタスク・予定・メモ・読書記録
+export async function getCalendarItems(startDate: string, endDate: string): Promise<CalendarEvent[]> {
+    try {
+        return await invoke<CalendarEvent[]>("get_calendar_items", { startDate, endDate });
+    } catch (e) {
+        console.error("getCalendarItems failed", e);
+        throw e;
+    }
+}
+
+// 省略した項目は既定値 (すべて書き出す)
+export interface IcsExportOptions {
+    include_tasks?: boolean;
+    include_completed?: boolean;
+    include_events?: boolean;
+    include_memos?: boolean;
+    group_id?: string;
+}
+
+export interface IcsImportReport {
+    created: number;
+    updated: number;
+    skipped: number;
+    memos: number;
+    warnings: string[];
+}
+
+export async function exportIcs(path: string, options?: IcsExportOptions): Promise<void> {
+    try {
+        await invoke("export_ics", { path, options });
+    } catch (e) {
+        console.error("exportIcs failed", e);
+        throw e;
+    }
+}
+
+// 取り込んだタスクは groupId のグループに入る
+export async function importIcs(path: string, groupId: string): Promise<IcsImportReport> {
+    try {
+        return await invoke<IcsImportReport>("import_ics", { path, groupId });
+    } catch (e) {
+        console.error("importIcs failed", e);
+        throw e;
+    }
+}
@@ -1,8 +1,10 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { Task } from "../type";
 
 export interface FileNode {
     name: string;
-    path: string; // Absolute path
+    path: string; // Relative to the notes root ("/" separated)
     is_dir: boolean;
     children?: FileNode[];
 }
@@ -31,6 +33,283 @@ export async function deleteItem(path: string): Promise<void> {
     await invoke("delete_item", { path });
 }
 
-export async function renameItem(path: string, newName: string): Promise<string> {
-    return await invoke("rename_item", { path, newName });
+// updateLinks が true ならこのノートを指しているリンクも書き換える
+export async function renameItem(path: string, newName: string, updateLinks = false): Promise<string> {
+    return await invoke("rename_item", { path, newName, updateLinks });
+}
+
+// 同じ名前のものがあるとき: "error" ならエラー、"rename" なら "name (2).md" のように番号を付ける
+export type OnConflict = "error" | "rename";
+
+// newParent が空文字ならルート直下へ移動する
+export async function moveItem(
+    path: string,
+    newParent: string,
+    onConflict: OnConflict = "error",
+    updateLinks = true,
+): Promise<string> {
+    return await invoke("move_item", { path, newParent, onConflict, updateLinks });
+}
+
+// newParent を省略すると同じフォルダに番号付きで複製する
+export async function duplicateItem(path: string, newParent?: string, onConflict: OnConflict = "error"): Promise<string> {
+    return await invoke("duplicate_item", { path, newParent, onConflict });
+}
+
+export interface NotesRoot {
+    path: string;
+    is_default: boolean;
+}
+
+export interface NotesRootMigration {
+    root: string;
+    moved: string[];
+    skipped: string[];
+}
+
+export async function getNotesRoot(): Promise<NotesRoot> {
+    return await invoke("get_notes_root");
+}
+
+// path が null の場合は既定のフォルダに戻す
+export async function setNotesRoot(path: string | null, migrate: boolean): Promise<NotesRootMigration> {
+    return await invoke("set_notes_root", { path, migrate });
+}
+
+export interface NoteLink {
+    source: string;
+    target: string;
+    kind: "wiki" | "markdown";
+    line: number;
+    resolved: string | null; // Relative path of the linked note, null if not found
+}
+
+export async function getBacklinks(path: string): Promise<NoteLink[]> {
+    return await invoke("get_backlinks", { path });
+}
+
+export async function getOutgoingLinks(path: string): Promise<NoteLink[]> {
+    return await invoke("get_outgoing_links", { path });
+}
+
+export async function getUnresolvedLinks(): Promise<NoteLink[]> {
+    return await invoke("get_unresolved_links");
+}
+
+export interface NoteChange {
+    kind: "created" | "modified" | "renamed" | "deleted";
+    path: string; // Relative to the notes root (new path for renames)
+    from: string | null; // Previous path for renames
+}
+
+// ノートフォルダの変更 (他のエディタや同期ツールによるものを含む) を受け取る
+export async function onNotesChanged(handler: (changes: NoteChange[]) => void): Promise<UnlistenFn> {
+    return await listen<NoteChange[]>("notes-changed", (event) => handler(event.payload));
+}
+
+export interface NoteMeta {
+    id: string; // Stable across renames
+    path: string;
+    title: string;
+    tags: string[];
+    aliases: string[];
+    created: string | null; // "created" in the front matter, as written
+    content_hash: string;
+    created_at: string;
+    updated_at: string;
+    deleted_at: string | null;
+}
+
+export interface TagCount {
+    tag: string;
+    count: number;
+}
+
+export async function getNoteMeta(path: string): Promise<NoteMeta | null> {
+    return await invoke("get_note_meta", { path });
+}
+
+// from / to は YYYY-MM-DD
+export async function listNotesMeta(tag?: string, from?: string, to?: string): Promise<NoteMeta[]> {
+    return await invoke("list_notes_meta", { tag, from, to });
+}
+
+export async function getNoteTags(): Promise<TagCount[]> {
+    return await invoke("get_note_tags");
+}
+
+export interface TrashItem {
+    id: string;
+    original_path: string; // Relative path before it was deleted
+    name: string;
+    is_dir: boolean;
+    deleted_at: string;
+}
+
+export interface TrashPolicy {
+    enabled: boolean;
+    days: number;
+}
+
+export async function listTrash(): Promise<TrashItem[]> {
+    return await invoke("list_trash");
+}
+
+// 戻した先の相対パスを返す (同じ名前のものがあれば別名になる)
+export async function restoreTrashItem(id: string): Promise<string> {
+    return await invoke("restore_trash_item", { id });
+}
+
+// ids を省略するとゴミ箱を空にする
+export async function purgeTrash(ids?: string[]): Promise<number> {
+    return await invoke("purge_trash", { ids });
+}
+
+export async function getTrashPolicy(): Promise<TrashPolicy> {
+    return await invoke("get_trash_policy");
+}
+
+export async function updateTrashPolicy(policy: TrashPolicy): Promise<TrashPolicy> {
+    return await invoke("update_trash_policy", { policy });
+}
+
+export interface NoteVersion {
+    id: string;
+    note_id: string;
+    path: string; // Path when the version was saved
+    content_hash: string;
+    size: number;
+    created_at: string;
+}
+
+export interface DiffLine {
+    kind: "same" | "added" | "removed";
+    text: string;
+    old_line: number | null;
+    new_line: number | null;
+}
+
+export interface NoteVersionPolicy {
+    max_versions: number; // 0 = unlimited
+    max_days: number; // 0 = unlimited
+}
+
+export async function getNoteVersions(path: string): Promise<NoteVersion[]> {
+    return await invoke("get_note_versions", { path });
+}
+
+export async function readNoteVersion(path: string, versionId: string): Promise<string> {
+    return await invoke("read_note_version", { path, versionId });
+}
+
+// from / to を省略すると現在の内容と比べる
+export async function diffNoteVersions(path: string, from?: string, to?: string): Promise<DiffLine[]> {
+    return await invoke("diff_note_versions", { path, from, to });
+}
+
+export async function restoreNoteVersion(path: string, versionId: string): Promise<string> {
+    return await invoke("restore_note_version", { path, versionId });
+}
+
+export async function getNoteVersionPolicy(): Promise<NoteVersionPolicy> {
+    return await invoke("get_note_version_policy");
+}
+
+export async function updateNoteVersionPolicy(policy: NoteVersionPolicy): Promise<NoteVersionPolicy> {
+    return await invoke("update_note_version_policy", { policy });
+}
+
+export interface Attachment {
+    path: string; // Relative to the notes root
+    markdown: string; // Link to paste into the note (relative to the note)
+}
+
+export interface AttachmentCleanup {
+    unused: { path: string; size: number }[];
+    total_size: number;
+    trashed: boolean;
+}
+
+// 貼り付けた画像などのデータを保存する (本文は生のバイト列、パスと名前はヘッダーで送る)
+export async function saveAttachmentData(notePath: string, data: Uint8Array, fileName?: string): Promise<Attachment> {
+    return await invoke("save_attachment", data, {
+        headers: {
+            "note-path": encodeURIComponent(notePath),
+            "file-name": encodeURIComponent(fileName ?? ""),
+        },
+    });
+}
+
+// 直前にウィンドウへドロップされたファイルを取り込む
+export async function attachDroppedFiles(notePath: string): Promise<Attachment[]> {
+    return await invoke("attach_dropped_files", { notePath });
+}
+
+// trash が true なら参照されていない添付ファイルをゴミ箱へ移す
+export async function cleanupAttachments(trash = false): Promise<AttachmentCleanup> {
+    return await invoke("cleanup_attachments", { trash });
+}
+
+export interface HtmlExport {
+    output_dir: string;
+    pages: string[]; // Relative to output_dir
+    assets: string[]; // Copied images and attachments
+    missing: string[]; // "note: target" for links and images that could not be exported
+}
+
+// path はノートかフォルダ ("" ならすべて)。outputDir はノートのフォルダの外の絶対パス
+// outputDir が空でない場合は overwrite を指定しないとエラーになる
+export async function exportNotesHtml(path: string, outputDir: string, inlineImages = false, overwrite = false): Promise<HtmlExport> {
+    return await invoke("export_notes_html", { path, outputDir, inlineImages, overwrite });
+}
+
+export interface NoteTask {
+    path: string;
+    line: number; // 1-based
+    text: string;
+    completed: boolean;
+    task_id: string | null; // Set when the checkbox has been promoted to a task
+}
+
+// path はノートかフォルダ (省略するとすべてのノート)
+export async function listNoteTasks(path?: string, includeCompleted = false): Promise<NoteTask[]> {
+    return await invoke("list_note_tasks", { path, includeCompleted });
+}
+
+// チェックボックスをタスクにする。以後、完了状態はノートとタスクで同期される
+export async function promoteNoteTask(path: string, line: number): Promise<Task> {
+    return await invoke("promote_note_task", { path, line });
+}
+
+export async function setNoteTaskCompleted(path: string, line: number, completed: boolean): Promise<void> {
+    return await invoke("set_note_task_completed", { path, line, completed });
+}
+
+export interface ImportIssue {
+    path: string; // Relative to the imported folder
+    line: number | null;
+    kind: string; // e.g. "note_embed", "block_reference", "callout", "plugin_block", "unsupported_file"
+    detail: string;
+}
+
+export interface ImportReport {
+    folder: string; // Relative to the notes root
+    notes: number;
+    attachments: number;
+    converted: number; // Links and embeds that were rewritten
+    tasks: number; // Tasks created from imported databases
+    issues: ImportIssue[];
+}
+
+// folder を省略すると Vault のフォルダ名で取り込む
+export async function importObsidianVault(vaultPath: string, folder?: string): Promise<ImportReport> {
+    return await invoke("import_obsidian_vault", { vaultPath, folder });
+}
+
+// "table": Markdown の表にする / "tasks": チェックボックスの一覧にしてタスクを作る
+export type NotionDatabaseMode = "table" | "tasks";
+
+// sourcePath は Notion の書き出し (Markdown & CSV) を展開したフォルダ
+export async function importNotionExport(sourcePath: string, folder?: string, databases?: NotionDatabaseMode): Promise<ImportReport> {
+    return await invoke("import_notion_export", { sourcePath, folder, databases });
 }
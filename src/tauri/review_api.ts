@@ -0,0 +1,45 @@
+import { invoke } from "@tauri-apps/api/core";
+import { Task } from "../type";
+import { CalendarMemo } from "../type/calendar";
+
+export interface BookReview {
+    book_title: string;
+    pages_read: number;
+    start_page: number;
+    end_page: number;
+}
+
+export interface MindMapReview {
+    id: string;
+    title: string;
+    updated_at?: string | null;
+}
+
+export interface ReviewReport {
+    start_date: string;
+    end_date: string;
+    completed_tasks: Task[];
+    slipped_tasks: Task[];
+    new_tasks: Task[];
+    reading: BookReview[];
+    memos: CalendarMemo[];
+    mind_maps: MindMapReview[];
+}
+
+export async function getReviewReport(startDate: string, endDate: string): Promise<ReviewReport> {
+    try {
+        return await invoke<ReviewReport>("get_review_report", { startDate, endDate });
+    } catch (e) {
+        console.error("getReviewReport failed", e);
+        throw e;
+    }
+}
+
+export async function saveReviewNote(startDate: string, endDate: string, overwrite = false): Promise<string> {
+    try {
+        return await invoke<string>("save_review_note", { startDate, endDate, overwrite });
+    } catch (e) {
+        console.error("saveReviewNote failed", e);
+        throw e;
+    }
+}
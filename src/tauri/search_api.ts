@@ -0,0 +1,31 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type SearchKind = "note" | "task" | "reading_memo" | "calendar_memo" | "mind_map";
+
+export interface SearchResult {
+    kind: SearchKind;
+    // ノートはルートからの相対パス、それ以外は各データの id
+    item_id: string;
+    title: string;
+    // HTML エスケープ済み、一致箇所は <mark> で囲まれている
+    snippet: string;
+    score: number;
+}
+
+export async function search(query: string, kinds?: SearchKind[], limit?: number): Promise<SearchResult[]> {
+    try {
+        return await invoke<SearchResult[]>("search", { query, kinds, limit });
+    } catch (e) {
+        console.error("search failed", e);
+        throw e;
+    }
+}
+
+export async function rebuildSearchIndex(): Promise<void> {
+    try {
+        await invoke("rebuild_search_index");
+    } catch (e) {
+        console.error("rebuildSearchIndex failed", e);
+        throw e;
+    }
+}
@@ -0,0 +1,25 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface TimezoneSettings {
+    timezone: string;
+    system_timezone?: string | null;
+}
+
+export async function getTimezoneSettings(): Promise<TimezoneSettings> {
+    try {
+        return await invoke<TimezoneSettings>("get_timezone_settings");
+    } catch (e) {
+        console.error("getTimezoneSettings failed", e);
+        throw e;
+    }
+}
+
+// null で OS のタイムゾーンに戻す
+export async function updateUserTimezone(timezone: string | null): Promise<TimezoneSettings> {
+    try {
+        return await invoke<TimezoneSettings>("update_user_timezone", { timezone });
+    } catch (e) {
+        console.error("updateUserTimezone failed", e);
+        throw e;
+    }
+}
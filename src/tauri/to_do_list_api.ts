@@ -1,5 +1,13 @@
 import { invoke } from "@tauri-apps/api/core";
-import { Task, CreateTaskPayload, TaskGroup } from "../type";
+import {
+    Task,
+    CreateTaskPayload,
+    TaskGroup,
+    ArchivePolicy,
+    TaskQuery,
+    SmartList,
+    SmartListWithCount,
+} from "../type";
 
 export async function getTasks(): Promise<Task[]> {
     try {
@@ -10,6 +18,15 @@ export async function getTasks(): Promise<Task[]> {
     }
 }
 
+export async function queryTasks(query: TaskQuery): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("query_tasks", { query });
+    } catch (e) {
+        console.error("queryTasks failed", e);
+        throw e;
+    }
+}
+
 export async function createTask(payload: CreateTaskPayload): Promise<Task[]> {
     try {
         return await invoke<Task[]>("create_task", { taskDto: payload });
@@ -72,4 +89,105 @@ export async function deleteTaskGroup(group: TaskGroup): Promise<TaskGroup[]> {
         console.error("deleteTaskGroup failed", e);
         throw e;
     }
-}
\ No newline at end of file
+}
+
+// Smart List APIs
+export async function getSmartLists(): Promise<SmartListWithCount[]> {
+    try {
+        return await invoke<SmartListWithCount[]>("get_smart_lists");
+    } catch (e) {
+        console.error("getSmartLists failed", e);
+        throw e;
+    }
+}
+
+export async function createSmartList(name: string, query: TaskQuery): Promise<SmartListWithCount[]> {
+    try {
+        return await invoke<SmartListWithCount[]>("create_smart_list", { name, query });
+    } catch (e) {
+        console.error("createSmartList failed", e);
+        throw e;
+    }
+}
+
+export async function updateSmartList(list: SmartList): Promise<SmartListWithCount[]> {
+    try {
+        return await invoke<SmartListWithCount[]>("update_smart_list", { list });
+    } catch (e) {
+        console.error("updateSmartList failed", e);
+        throw e;
+    }
+}
+
+export async function deleteSmartList(list: SmartList): Promise<SmartListWithCount[]> {
+    try {
+        return await invoke<SmartListWithCount[]>("delete_smart_list", { list });
+    } catch (e) {
+        console.error("deleteSmartList failed", e);
+        throw e;
+    }
+}
+
+// Archive APIs
+export async function getArchivePolicy(): Promise<ArchivePolicy> {
+    try {
+        return await invoke<ArchivePolicy>("get_archive_policy");
+    } catch (e) {
+        console.error("getArchivePolicy failed", e);
+        throw e;
+    }
+}
+
+export async function updateArchivePolicy(policy: ArchivePolicy): Promise<ArchivePolicy> {
+    try {
+        return await invoke<ArchivePolicy>("update_archive_policy", { policy });
+    } catch (e) {
+        console.error("updateArchivePolicy failed", e);
+        throw e;
+    }
+}
+
+export async function archiveCompletedTasks(): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("archive_completed_tasks");
+    } catch (e) {
+        console.error("archiveCompletedTasks failed", e);
+        throw e;
+    }
+}
+
+export async function archiveTask(id: string): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("archive_task", { id });
+    } catch (e) {
+        console.error("archiveTask failed", e);
+        throw e;
+    }
+}
+
+export async function unarchiveTask(id: string): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("unarchive_task", { id });
+    } catch (e) {
+        console.error("unarchiveTask failed", e);
+        throw e;
+    }
+}
+
+export async function getArchivedTasks(): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("get_archived_tasks");
+    } catch (e) {
+        console.error("getArchivedTasks failed", e);
+        throw e;
+    }
+}
+
+export async function searchArchivedTasks(query: TaskQuery): Promise<Task[]> {
+    try {
+        return await invoke<Task[]>("search_archived_tasks", { query });
+    } catch (e) {
+        console.error("searchArchivedTasks failed", e);
+        throw e;
+    }
+}
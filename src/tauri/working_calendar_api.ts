@@ -0,0 +1,96 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+export interface WorkingCalendarSettings {
+    non_working_weekdays: Weekday[];
+    bundled_holidays?: string | null;
+}
+
+export interface WorkingDay {
+    date: string; // YYYY-MM-DD
+    is_working_day: boolean;
+    holiday_name?: string | null;
+}
+
+export interface Holiday {
+    id: string;
+    date: string;
+    name: string;
+    created_at: string;
+    updated_at?: string;
+    deleted_at?: string;
+}
+
+export async function getWorkingCalendarSettings(): Promise<WorkingCalendarSettings> {
+    try {
+        return await invoke<WorkingCalendarSettings>("get_working_calendar_settings");
+    } catch (e) {
+        console.error("getWorkingCalendarSettings failed", e);
+        throw e;
+    }
+}
+
+export async function updateWorkingCalendarSettings(settings: WorkingCalendarSettings): Promise<WorkingCalendarSettings> {
+    try {
+        return await invoke<WorkingCalendarSettings>("update_working_calendar_settings", { settings });
+    } catch (e) {
+        console.error("updateWorkingCalendarSettings failed", e);
+        throw e;
+    }
+}
+
+// 同梱の祝日一覧がある地域 (bundled_holidays に指定できる値)
+export async function getHolidayRegions(): Promise<string[]> {
+    try {
+        return await invoke<string[]>("get_holiday_regions");
+    } catch (e) {
+        console.error("getHolidayRegions failed", e);
+        throw e;
+    }
+}
+
+export async function getWorkingDays(startDate: string, endDate: string): Promise<WorkingDay[]> {
+    try {
+        return await invoke<WorkingDay[]>("get_working_days", { startDate, endDate });
+    } catch (e) {
+        console.error("getWorkingDays failed", e);
+        throw e;
+    }
+}
+
+export async function getUserHolidays(): Promise<Holiday[]> {
+    try {
+        return await invoke<Holiday[]>("get_user_holidays");
+    } catch (e) {
+        console.error("getUserHolidays failed", e);
+        throw e;
+    }
+}
+
+export async function addUserHoliday(date: string, name: string): Promise<Holiday[]> {
+    try {
+        return await invoke<Holiday[]>("add_user_holiday", { date, name });
+    } catch (e) {
+        console.error("addUserHoliday failed", e);
+        throw e;
+    }
+}
+
+export async function deleteHoliday(id: string): Promise<Holiday[]> {
+    try {
+        return await invoke<Holiday[]>("delete_holiday", { id });
+    } catch (e) {
+        console.error("deleteHoliday failed", e);
+        throw e;
+    }
+}
+
+export async function importHolidays(path: string): Promise<number> {
+    try {
+        return await invoke<number>("import_holidays", { path });
+    } catch (e) {
+        console.error("importHolidays failed", e);
+        throw e;
+    }
+}
@@ -1,8 +1,20 @@
 import { Task } from "./index";
 
 export interface CalendarMemo {
+    id?: string;
     date: string;
     content: string;
+    created_at?: string;
+    updated_at?: string;
+    deleted_at?: string;
+}
+
+export interface CalendarMemoVersion {
+    id: string;
+    memo_id: string;
+    date: string;
+    content: string;
+    created_at: string;
 }
 
 export interface ReadingActivity {
@@ -13,7 +25,33 @@ export interface ReadingActivity {
     memo_id: string;
 }
 
+// タスクとは別の予定 (会議など)
+export interface ScheduleEvent {
+    id: string;
+    title: string;
+    location?: string;
+    all_day: boolean;
+    start_datetime: string;
+    end_datetime: string;
+    color?: string;
+    notes?: string;
+    created_at: string;
+    updated_at?: string;
+    deleted_at?: string;
+}
+
+export interface CreateEventDto {
+    title: string;
+    location?: string;
+    all_day?: boolean;
+    start_datetime: string;
+    end_datetime: string;
+    color?: string;
+    notes?: string;
+}
+
 export type CalendarEvent =
     | { type: 'task'; data: Task }
+    | { type: 'event'; data: ScheduleEvent }
     | { type: 'memo'; data: CalendarMemo }
     | { type: 'reading'; data: ReadingActivity };
@@ -10,6 +10,10 @@ export interface Task {
 	start_datetime?: string;
 	end_datetime?: string;
 	progress: number;
+	priority?: number;
+	tags?: string[];
+	completed_at?: string;
+	archived_at?: string;
 	created_at: string;
 	updated_at?: string;
 	deleted_at?: string;
@@ -24,6 +28,40 @@ export interface CreateTaskPayload {
 	group_id?: string;
 }
 
+// 期日による絞り込み (相対的な期間は実行時に解決される)
+export type DueFilter =
+	| { kind: "overdue" }
+	| { kind: "today" }
+	| { kind: "this_week" }
+	| { kind: "no_date" }
+	| { kind: "range"; from?: string; to?: string };
+
+// 指定しない条件は無視される
+export interface TaskQuery {
+	group_id?: string;
+	completed?: boolean;
+	due?: DueFilter;
+	text?: string;
+	tag?: string;
+	min_priority?: number;
+	archived?: boolean;
+}
+
+// 名前を付けて保存した絞り込み条件
+export interface SmartList {
+	id: string;
+	name: string;
+	query: TaskQuery;
+	order: number;
+	created_at: string;
+	updated_at?: string;
+	deleted_at?: string;
+}
+
+export interface SmartListWithCount extends SmartList {
+	count: number;
+}
+
 export interface TaskGroup {
 	id: string;
 	name: string;
@@ -42,3 +80,9 @@ export interface Subtask {
 	updated_at?: string;
 	deleted_at?: string;
 }
+
+// 完了後 days 日を過ぎたタスクを自動でアーカイブする (既定では無効)
+export interface ArchivePolicy {
+	enabled: boolean;
+	days: number;
+}